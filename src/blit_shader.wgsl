@@ -1,5 +1,6 @@
 struct VertexOutput {
     @location(0) tex_coord: vec2<f32>,
+    @location(1) @interpolate(flat) mip_level: u32,
     @builtin(position) position: vec4<f32>,
 };
 
@@ -7,6 +8,7 @@ struct VertexOutput {
 struct PushConstant {
     proj_mat: mat4x4<f32>,
     texture_size: vec2<f32>,
+    mip_level: u32,
 };
 var<push_constant> pc: PushConstant;
 
@@ -19,6 +21,7 @@ fn vs_main(
     var result: VertexOutput;
     result.position = pc.proj_mat * position;
     result.tex_coord = vec2(tex_coord.x, pc.texture_size.y - tex_coord.y);
+    result.mip_level = pc.mip_level;
 
     return result;
 }
@@ -32,7 +35,8 @@ var the_sampler: sampler;
 var color: texture_2d<u32>;
 
 @fragment
-fn fs_main(vertex: VertexOutput) -> @location(0) u32 {
-    let r = textureLoad(color, vec2<u32>(vertex.tex_coord), 0).r;
-    return r;
+fn fs_main(vertex: VertexOutput) -> @location(0) vec2<u32> {
+    let coord = vec2<u32>(vertex.tex_coord) >> vec2<u32>(vertex.mip_level);
+    let rg = textureLoad(color, coord, i32(vertex.mip_level)).rg;
+    return rg;
 }
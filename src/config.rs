@@ -0,0 +1,481 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use glam::{UVec2, Vec4};
+
+use crate::demos::Demo;
+use crate::fractal::FractalKind;
+use crate::iter_dump::IterDump;
+use crate::julia_morph::JuliaMorph;
+use crate::mandel_texture::{
+    ColoringMode, InteriorColoring, MipBlend, DEFAULT_INTERIOR_COLOR, DEFAULT_MAX_BLIT_ZOOM,
+    DEFAULT_PALETTE_FADE, DEFAULT_PALETTE_PERIOD, DEFAULT_TEXTURE_COUNT, MAX_DETAIL,
+    MAX_INTERIOR_BOOST, MAX_IN_FLIGHT_TILES, MAX_PALETTE_PERIOD, MAX_RENDER_SCALE,
+    MAX_SUB_TILE_SIZE, MAX_TEXTURE_COUNT, MIN_PALETTE_PERIOD, MIN_RENDER_SCALE, MIN_SUB_TILE_SIZE,
+    MIN_TEXTURE_COUNT, TEXTURE_SIZE,
+};
+use crate::mandelbrot_simd::{IterStorage, Kernel, SamplePattern, DEFAULT_ITER_CAP, MIN_ITER};
+use crate::navigation::{Navigation, Replay};
+use crate::palette::PaletteKind;
+use crate::thumbnails::Thumbnails;
+use crate::tiled_fractal_app::{
+    ZoomAnchor, DEFAULT_AUTOFOCUS_RADIUS, DEFAULT_AUTOFOCUS_ZOOM, DEFAULT_IDLE_DELAY,
+    MAX_AUTOFOCUS_RADIUS, MAX_AUTOFOCUS_ZOOM, MIN_RENDER_REGION,
+};
+use crate::view_state::ViewState;
+use crate::zoom_record::ZoomRecord;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub msaa_samples: u32,
+    pub fractal_kind: FractalKind,
+    pub view: Option<ViewState>,
+    // pixels saved with Shift+R, shown instead of computing the view
+    pub iter_dump: Option<IterDump>,
+    pub tile_timings: bool,
+    pub invert_pan: bool,
+    pub pan_sensitivity: f64,
+    pub zoom_speed: f64,
+    pub zoom_anchor: ZoomAnchor,
+    // how far Shift+E zooms in, and the cells of 8 texels around a point its detail is summed over
+    pub autofocus_zoom: f64,
+    pub autofocus_radius: u32,
+    pub max_blit_zoom: f64,
+    pub boundary_color: Vec4,
+    pub crosshair_color: Vec4,
+    pub interior_coloring: InteriorColoring,
+    pub interior_color: Vec4,
+    pub color_offset: f32,
+    // iterations of one pass through the palette
+    pub palette_period: u32,
+    pub coloring_mode: ColoringMode,
+    // derivative-based smoothing of band edges, see `MandelTexture::set_band_aa`
+    pub band_aa: bool,
+    pub palette: PaletteKind,
+    // the .ggr or .csv file behind `PaletteKind::Gradient`
+    pub gradient: Option<PathBuf>,
+    pub palette_fade: Duration,
+    pub tile_time_budget: Option<Duration>,
+    // side of the pieces tiles are computed in, see `MandelTexture::set_sub_tile_size`
+    pub sub_tile_size: u32,
+    // iteration multiplier for mostly interior tiles, see `MandelTexture::set_interior_boost`
+    pub interior_boost: u32,
+    pub fps_cap: Option<u32>,
+    // tiles computing at once, `None` scales with the core count
+    pub max_in_flight: Option<usize>,
+    pub input_latency: bool,
+    pub supersample: Option<SamplePattern>,
+    // supersampling once the view has settled, see `MandelTexture::set_settle_supersample`
+    pub settle_supersample: Option<SamplePattern>,
+    pub idle_delay: Duration,
+    // single quality knob, see `MandelTexture::set_detail`
+    pub detail: u32,
+    pub iter_cap: u32,
+    // iteration limit of a quick first pass at startup, lifted once it's on screen
+    pub warmup_iters: Option<u32>,
+    pub iter_storage: IterStorage,
+    // how zoomed out views average log stored data, see `MipBlend`
+    pub mip_blend: MipBlend,
+    pub kernel: Kernel,
+    pub texture_size: UVec2,
+    // length of the blit ring, see `MandelTexture::set_texture_count`
+    pub texture_count: usize,
+    pub render_scale: f64,
+    // share of the window the view is drawn in, bars around the rest
+    pub render_region: f64,
+    // letterbox at the startup aspect instead of filling the window
+    pub fit: bool,
+    // headless recording instead of opening the window
+    pub zoom_record: Option<ZoomRecord>,
+    // headless Julia constant sweep instead of opening the window
+    pub julia_morph: Option<JuliaMorph>,
+    // a recorded navigation played back in the window, or headless with `--replay-out`
+    pub replay: Option<Replay>,
+    // headless contact sheet instead of opening the window
+    pub thumbnails: Option<Thumbnails>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            msaa_samples: 1,
+            fractal_kind: FractalKind::Mandelbrot,
+            view: None,
+            iter_dump: None,
+            tile_timings: false,
+            invert_pan: false,
+            pan_sensitivity: 1.0,
+            zoom_speed: 1.0,
+            zoom_anchor: ZoomAnchor::Cursor,
+            autofocus_zoom: DEFAULT_AUTOFOCUS_ZOOM,
+            autofocus_radius: DEFAULT_AUTOFOCUS_RADIUS,
+            max_blit_zoom: DEFAULT_MAX_BLIT_ZOOM,
+            boundary_color: Vec4::ONE,
+            crosshair_color: Vec4::ONE,
+            interior_coloring: InteriorColoring::Solid,
+            interior_color: DEFAULT_INTERIOR_COLOR,
+            color_offset: 0.0,
+            palette_period: DEFAULT_PALETTE_PERIOD,
+            coloring_mode: ColoringMode::Bands,
+            band_aa: false,
+            palette: PaletteKind::Classic,
+            gradient: None,
+            palette_fade: DEFAULT_PALETTE_FADE,
+            tile_time_budget: None,
+            sub_tile_size: MAX_SUB_TILE_SIZE,
+            interior_boost: 1,
+            fps_cap: None,
+            max_in_flight: None,
+            input_latency: false,
+            supersample: None,
+            settle_supersample: None,
+            idle_delay: DEFAULT_IDLE_DELAY,
+            detail: 0,
+            iter_cap: DEFAULT_ITER_CAP,
+            warmup_iters: None,
+            iter_storage: IterStorage::Linear,
+            mip_blend: MipBlend::Perceptual,
+            kernel: Kernel::Simd,
+            texture_size: TEXTURE_SIZE,
+            texture_count: DEFAULT_TEXTURE_COUNT,
+            render_scale: 1.0,
+            render_region: 1.0,
+            fit: false,
+            zoom_record: None,
+            julia_morph: None,
+            replay: None,
+            thumbnails: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn from_args() -> anyhow::Result<Self> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse<I>(args: I) -> anyhow::Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut config = Self::default();
+        let mut args = args.into_iter();
+        let mut zoom_record_dir: Option<PathBuf> = None;
+        let mut zoom_ratio = ZoomRecord::DEFAULT_RATIO;
+        let mut zoom_frames = ZoomRecord::DEFAULT_FRAMES;
+        let mut julia_morph_dir: Option<PathBuf> = None;
+        let mut julia_path = JuliaMorph::DEFAULT_PATH;
+        let mut julia_frames = JuliaMorph::DEFAULT_FRAMES;
+        let mut replay_navigation: Option<Navigation> = None;
+        let mut replay_speed = Replay::DEFAULT_SPEED;
+        let mut replay_dir: Option<PathBuf> = None;
+        let mut thumbnail_links: Option<PathBuf> = None;
+        let mut thumbnail_output = PathBuf::from(Thumbnails::DEFAULT_OUTPUT);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--msaa" => config.msaa_samples = parse_value(&arg, args.next())?,
+                "--fractal" => config.fractal_kind = parse_value(&arg, args.next())?,
+                "--link" => config.set_view(parse_value(&arg, args.next())?),
+                "--load" => {
+                    let path: PathBuf = parse_value(&arg, args.next())?;
+                    config.set_view(ViewState::load(&path)?);
+                }
+                "--demo" => {
+                    let name: String = parse_value(&arg, args.next())?;
+                    config.set_view(name.parse::<Demo>()?.view);
+                }
+                "--load-dump" => {
+                    let path: PathBuf = parse_value(&arg, args.next())?;
+                    config.iter_dump = Some(IterDump::load(&path)?);
+                }
+                "--tile-timings" => config.tile_timings = true,
+                "--input-latency" => config.input_latency = true,
+                "--supersample" => config.supersample = Some(parse_value(&arg, args.next())?),
+                "--settle-supersample" => {
+                    config.settle_supersample = Some(parse_value(&arg, args.next())?)
+                }
+                "--idle-delay-ms" => {
+                    let millis = parse_value(&arg, args.next())?;
+                    config.idle_delay = Duration::from_millis(millis);
+                }
+                "--detail" => {
+                    config.detail = parse_value(&arg, args.next())?;
+                    if config.detail > MAX_DETAIL {
+                        return Err(anyhow!("--detail must be at most {}", MAX_DETAIL));
+                    }
+                }
+                "--invert-pan" => config.invert_pan = true,
+                "--fit" => config.fit = true,
+                "--pan-sensitivity" => {
+                    config.pan_sensitivity = parse_value(&arg, args.next())?;
+                    if !(config.pan_sensitivity > 0.0 && config.pan_sensitivity.is_finite()) {
+                        return Err(anyhow!("--pan-sensitivity must be greater than 0"));
+                    }
+                }
+                "--zoom-speed" => {
+                    config.zoom_speed = parse_value(&arg, args.next())?;
+                    if !(config.zoom_speed > 0.0 && config.zoom_speed.is_finite()) {
+                        return Err(anyhow!("--zoom-speed must be greater than 0"));
+                    }
+                }
+                "--zoom-anchor" => config.zoom_anchor = parse_value(&arg, args.next())?,
+                "--autofocus-zoom" => {
+                    config.autofocus_zoom = parse_value(&arg, args.next())?;
+                    if !(1.0..=MAX_AUTOFOCUS_ZOOM).contains(&config.autofocus_zoom) {
+                        return Err(anyhow!(
+                            "--autofocus-zoom must be between 1 and {}",
+                            MAX_AUTOFOCUS_ZOOM
+                        ));
+                    }
+                }
+                "--autofocus-radius" => {
+                    config.autofocus_radius = parse_value(&arg, args.next())?;
+                    if config.autofocus_radius > MAX_AUTOFOCUS_RADIUS {
+                        return Err(anyhow!(
+                            "--autofocus-radius must be at most {}",
+                            MAX_AUTOFOCUS_RADIUS
+                        ));
+                    }
+                }
+                "--max-blit-zoom" => {
+                    config.max_blit_zoom = parse_value(&arg, args.next())?;
+                    if config.max_blit_zoom < 1.0 {
+                        return Err(anyhow!("--max-blit-zoom must be at least 1"));
+                    }
+                }
+                "--boundary-color" => config.boundary_color = parse_color(&arg, args.next())?,
+                "--crosshair-color" => config.crosshair_color = parse_color(&arg, args.next())?,
+                "--interior" => config.interior_coloring = parse_value(&arg, args.next())?,
+                "--interior-color" => config.interior_color = parse_color(&arg, args.next())?,
+                "--color-offset" => {
+                    config.color_offset = parse_value(&arg, args.next())?;
+                    if config.color_offset < 0.0 {
+                        return Err(anyhow!("--color-offset must not be negative"));
+                    }
+                }
+                "--palette-period" => {
+                    config.palette_period = parse_value(&arg, args.next())?;
+                    if !(MIN_PALETTE_PERIOD..=MAX_PALETTE_PERIOD).contains(&config.palette_period) {
+                        return Err(anyhow!(
+                            "--palette-period must be between {} and {}",
+                            MIN_PALETTE_PERIOD,
+                            MAX_PALETTE_PERIOD
+                        ));
+                    }
+                }
+                "--coloring" => config.coloring_mode = parse_value(&arg, args.next())?,
+                "--smooth" => config.coloring_mode = ColoringMode::Smooth,
+                "--band-aa" => config.band_aa = true,
+                "--palette" => config.palette = parse_value(&arg, args.next())?,
+                "--gradient" => {
+                    config.gradient = Some(parse_value(&arg, args.next())?);
+                    config.palette = PaletteKind::Gradient;
+                }
+                "--palette-fade-ms" => {
+                    let millis = parse_value(&arg, args.next())?;
+                    config.palette_fade = Duration::from_millis(millis);
+                }
+                "--tile-budget-ms" => {
+                    let millis = parse_value(&arg, args.next())?;
+                    config.tile_time_budget = Some(Duration::from_millis(millis));
+                }
+                "--fps-cap" => {
+                    let fps = parse_value(&arg, args.next())?;
+                    if fps == 0 {
+                        return Err(anyhow!("--fps-cap must be at least 1"));
+                    }
+                    config.fps_cap = Some(fps);
+                }
+                "--max-in-flight" => {
+                    let tiles = parse_value(&arg, args.next())?;
+                    if !(1..=MAX_IN_FLIGHT_TILES).contains(&tiles) {
+                        return Err(anyhow!(
+                            "--max-in-flight must be between 1 and {}",
+                            MAX_IN_FLIGHT_TILES
+                        ));
+                    }
+                    config.max_in_flight = Some(tiles);
+                }
+                "--iter-cap" => config.iter_cap = parse_value(&arg, args.next())?,
+                "--warmup-iters" => {
+                    let iters: u32 = parse_value(&arg, args.next())?;
+                    if iters < MIN_ITER {
+                        return Err(anyhow!("--warmup-iters must be at least {}", MIN_ITER));
+                    }
+                    config.warmup_iters = Some(iters);
+                }
+                "--iter-storage" => config.iter_storage = parse_value(&arg, args.next())?,
+                "--mip-blend" => config.mip_blend = parse_value(&arg, args.next())?,
+                "--kernel" => config.kernel = parse_value(&arg, args.next())?,
+                "--texture-size" => config.texture_size = parse_size(&arg, args.next())?,
+                "--sub-tile-size" => {
+                    config.sub_tile_size = parse_value(&arg, args.next())?;
+                    let sizes = MIN_SUB_TILE_SIZE..=MAX_SUB_TILE_SIZE;
+                    if !config.sub_tile_size.is_power_of_two()
+                        || !sizes.contains(&config.sub_tile_size)
+                    {
+                        return Err(anyhow!(
+                            "--sub-tile-size must be a power of two from {} to {}",
+                            MIN_SUB_TILE_SIZE,
+                            MAX_SUB_TILE_SIZE
+                        ));
+                    }
+                }
+                "--interior-boost" => {
+                    config.interior_boost = parse_value(&arg, args.next())?;
+                    if !(1..=MAX_INTERIOR_BOOST).contains(&config.interior_boost) {
+                        return Err(anyhow!(
+                            "--interior-boost must be between 1 and {}",
+                            MAX_INTERIOR_BOOST
+                        ));
+                    }
+                }
+                "--texture-count" => {
+                    config.texture_count = parse_value(&arg, args.next())?;
+                    if !(MIN_TEXTURE_COUNT..=MAX_TEXTURE_COUNT).contains(&config.texture_count) {
+                        return Err(anyhow!(
+                            "--texture-count must be between {} and {}",
+                            MIN_TEXTURE_COUNT,
+                            MAX_TEXTURE_COUNT
+                        ));
+                    }
+                }
+                "--render-scale" => {
+                    config.render_scale = parse_value(&arg, args.next())?;
+                    if !(MIN_RENDER_SCALE..=MAX_RENDER_SCALE).contains(&config.render_scale) {
+                        return Err(anyhow!(
+                            "--render-scale must be between {} and {}",
+                            MIN_RENDER_SCALE,
+                            MAX_RENDER_SCALE
+                        ));
+                    }
+                }
+                "--render-region" => {
+                    config.render_region = parse_value(&arg, args.next())?;
+                    if !(MIN_RENDER_REGION..=1.0).contains(&config.render_region) {
+                        return Err(anyhow!(
+                            "--render-region must be between {} and 1",
+                            MIN_RENDER_REGION
+                        ));
+                    }
+                }
+                "--zoom-record" => zoom_record_dir = Some(parse_value(&arg, args.next())?),
+                "--zoom-ratio" => {
+                    zoom_ratio = parse_value(&arg, args.next())?;
+                    if zoom_ratio <= 1.0 {
+                        return Err(anyhow!("--zoom-ratio must be greater than 1"));
+                    }
+                }
+                "--zoom-frames" => zoom_frames = parse_value(&arg, args.next())?,
+                "--julia-morph" => julia_morph_dir = Some(parse_value(&arg, args.next())?),
+                "--julia-path" => julia_path = parse_value(&arg, args.next())?,
+                "--julia-frames" => {
+                    julia_frames = parse_value(&arg, args.next())?;
+                    if julia_frames == 0 {
+                        return Err(anyhow!("--julia-frames must be at least 1"));
+                    }
+                }
+                "--replay" => {
+                    let path: PathBuf = parse_value(&arg, args.next())?;
+                    let navigation = Navigation::load(&path)?;
+                    config.set_view(navigation.view_at(0.0));
+                    replay_navigation = Some(navigation);
+                }
+                "--replay-speed" => {
+                    replay_speed = parse_value(&arg, args.next())?;
+                    if !(replay_speed > 0.0 && replay_speed.is_finite()) {
+                        return Err(anyhow!("--replay-speed must be greater than 0"));
+                    }
+                }
+                "--replay-out" => replay_dir = Some(parse_value(&arg, args.next())?),
+                "--thumbnails" => thumbnail_links = Some(parse_value(&arg, args.next())?),
+                "--thumbnails-out" => thumbnail_output = parse_value(&arg, args.next())?,
+                _ => return Err(anyhow!("Unknown argument: {}", arg)),
+            }
+        }
+
+        let iter_limit = config.iter_storage.iter_limit();
+        if config.iter_cap > iter_limit {
+            return Err(anyhow!(
+                "--iter-cap can be at most {} with {} iteration storage, the texture stores 16-bit counts",
+                iter_limit,
+                config.iter_storage.name()
+            ));
+        }
+        if config.palette == PaletteKind::Gradient && config.gradient.is_none() {
+            return Err(anyhow!("--palette gradient needs a file from --gradient"));
+        }
+
+        config.zoom_record = zoom_record_dir.map(|dir| ZoomRecord {
+            dir,
+            ratio: zoom_ratio,
+            frames: zoom_frames,
+        });
+        config.julia_morph = julia_morph_dir.map(|dir| JuliaMorph {
+            dir,
+            path: julia_path,
+            frames: julia_frames,
+        });
+        config.replay = replay_navigation.map(|navigation| Replay {
+            navigation,
+            speed: replay_speed,
+            dir: replay_dir,
+        });
+        config.thumbnails = thumbnail_links.map(|links| Thumbnails {
+            links,
+            output: thumbnail_output,
+        });
+
+        Ok(config)
+    }
+
+    // later flags still override the view's coloring
+    fn set_view(&mut self, mut view: ViewState) {
+        if let Some(coloring) = view.coloring.take() {
+            self.palette = coloring.palette;
+            self.interior_coloring = coloring.interior_coloring;
+            self.interior_color = coloring.interior_color;
+            self.coloring_mode = coloring.coloring_mode;
+            self.color_offset = coloring.color_offset;
+            self.palette_period = coloring.palette_period;
+        }
+        self.view = Some(view);
+    }
+}
+
+fn parse_value<T: FromStr>(name: &str, value: Option<String>) -> anyhow::Result<T> {
+    let value = value.ok_or_else(|| anyhow!("Missing value for {}", name))?;
+    value
+        .parse()
+        .map_err(|_| anyhow!("Invalid value for {}: {}", name, value))
+}
+
+/// Parses `WIDTHxHEIGHT`, or a single number for a square.
+fn parse_size(name: &str, value: Option<String>) -> anyhow::Result<UVec2> {
+    let value: String = parse_value(name, value)?;
+    let invalid = || anyhow!("Invalid value for {}: {}", name, value);
+
+    match value.split_once('x') {
+        Some((width, height)) => Ok(UVec2::new(
+            width.parse().map_err(|_| invalid())?,
+            height.parse().map_err(|_| invalid())?,
+        )),
+        None => Ok(UVec2::splat(value.parse().map_err(|_| invalid())?)),
+    }
+}
+
+/// Parses an opaque `RRGGBB` hex color, a leading `#` is optional.
+fn parse_color(name: &str, value: Option<String>) -> anyhow::Result<Vec4> {
+    let value: String = parse_value(name, value)?;
+    let hex = value.strip_prefix('#').unwrap_or(&value);
+    let rgb = u32::from_str_radix(hex, 16)
+        .ok()
+        .filter(|_| hex.len() == 6)
+        .ok_or_else(|| anyhow!("Invalid value for {}: {}", name, value))?;
+
+    let channel = |shift: u32| ((rgb >> shift) & 0xff) as f32 / 255.0;
+    Ok(Vec4::new(channel(16), channel(8), channel(0), 1.0))
+}
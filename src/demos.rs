@@ -0,0 +1,111 @@
+use anyhow::anyhow;
+use glam::DVec2;
+
+use crate::fractal::{Fractal, FractalKind};
+use crate::locations::LOCATIONS;
+use crate::mandel_texture::{
+    ColoringMode, InteriorColoring, DEFAULT_INTERIOR_COLOR, DEFAULT_PALETTE_PERIOD,
+};
+use crate::palette::PaletteKind;
+use crate::view_state::{Coloring, ViewState};
+
+/// A view with its coloring for reproducible showcase renders, selected with `--demo`.
+#[derive(Debug, Clone, Copy)]
+pub struct Demo {
+    pub name: &'static str,
+    pub view: ViewState,
+}
+
+const fn demo(
+    name: &'static str,
+    view: ViewState,
+    palette: PaletteKind,
+    coloring_mode: ColoringMode,
+    interior_coloring: InteriorColoring,
+) -> Demo {
+    Demo {
+        name,
+        view: ViewState {
+            coloring: Some(Coloring {
+                palette,
+                interior_coloring,
+                interior_color: DEFAULT_INTERIOR_COLOR,
+                coloring_mode,
+                color_offset: 0.0,
+                palette_period: DEFAULT_PALETTE_PERIOD,
+            }),
+            ..view
+        },
+    }
+}
+
+const MULTIBROT: ViewState = ViewState {
+    center: DVec2::ZERO,
+    height: 3.0,
+    fractal: Fractal {
+        kind: FractalKind::Multibrot,
+        julia_c: DVec2::new(-0.8, 0.156),
+        multibrot_power: 3,
+    },
+    iter_override: Some(300),
+    coloring: None,
+};
+
+pub const DEMOS: [Demo; 6] = [
+    demo(
+        "seahorse",
+        LOCATIONS[0].view,
+        PaletteKind::Inferno,
+        ColoringMode::Smooth,
+        InteriorColoring::Solid,
+    ),
+    demo(
+        "elephants",
+        LOCATIONS[1].view,
+        PaletteKind::Viridis,
+        ColoringMode::Smooth,
+        InteriorColoring::Solid,
+    ),
+    demo(
+        "spiral",
+        LOCATIONS[2].view,
+        PaletteKind::Classic,
+        ColoringMode::Bands,
+        InteriorColoring::Solid,
+    ),
+    demo(
+        "minibrot",
+        LOCATIONS[4].view,
+        PaletteKind::Cividis,
+        ColoringMode::Smooth,
+        InteriorColoring::Phase,
+    ),
+    demo(
+        "rabbit",
+        LOCATIONS[7].view,
+        PaletteKind::Inferno,
+        ColoringMode::Smooth,
+        InteriorColoring::Phase,
+    ),
+    demo(
+        "multibrot",
+        MULTIBROT,
+        PaletteKind::Viridis,
+        ColoringMode::Smooth,
+        InteriorColoring::Solid,
+    ),
+];
+
+impl std::str::FromStr for Demo {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DEMOS
+            .into_iter()
+            .find(|demo| demo.name == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = DEMOS.iter().map(|demo| demo.name).collect();
+                anyhow!("Unknown demo: {}, one of {}", s, names.join(", "))
+            })
+    }
+}
@@ -0,0 +1,108 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// A number as the unevaluated sum of two f64, about 106 bits of mantissa.
+///
+/// `lo` is at most half an ulp of `hi`, so `hi` alone is the nearest f64.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+pub type DoubleDoubleComplex = (DoubleDouble, DoubleDouble);
+
+// splits an f64 into two halves of 26 bits, their products are exact
+const SPLITTER: f64 = 134_217_729.0;
+
+impl DoubleDouble {
+    /// Relative precision, the f64 machine epsilon for both halves together.
+    pub const EPSILON: f64 = f64::EPSILON * f64::EPSILON;
+
+    pub fn abs(self) -> Self {
+        if self.hi < 0.0 {
+            -self
+        } else {
+            self
+        }
+    }
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(hi: f64) -> Self {
+        Self { hi, lo: 0.0 }
+    }
+}
+
+// a + b as the rounded sum and its exact error
+fn two_sum(a: f64, b: f64) -> DoubleDouble {
+    let hi = a + b;
+    let b_part = hi - a;
+    let lo = (a - (hi - b_part)) + (b - b_part);
+    DoubleDouble { hi, lo }
+}
+
+// `two_sum` for |a| >= |b|
+fn quick_two_sum(a: f64, b: f64) -> DoubleDouble {
+    let hi = a + b;
+    DoubleDouble {
+        hi,
+        lo: b - (hi - a),
+    }
+}
+
+fn split(a: f64) -> (f64, f64) {
+    let t = SPLITTER * a;
+    let hi = t - (t - a);
+    (hi, a - hi)
+}
+
+// a * b as the rounded product and its exact error, Dekker's without relying on FMA
+fn two_prod(a: f64, b: f64) -> DoubleDouble {
+    let hi = a * b;
+    let (a_hi, a_lo) = split(a);
+    let (b_hi, b_lo) = split(b);
+    let lo = ((a_hi * b_hi - hi) + a_hi * b_lo + a_lo * b_hi) + a_lo * b_lo;
+    DoubleDouble { hi, lo }
+}
+
+impl Add for DoubleDouble {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let sum = two_sum(self.hi, other.hi);
+        let low_sum = two_sum(self.lo, other.lo);
+        let sum = quick_two_sum(sum.hi, sum.lo + low_sum.hi);
+        quick_two_sum(sum.hi, sum.lo + low_sum.lo)
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let product = two_prod(self.hi, other.hi);
+        quick_two_sum(
+            product.hi,
+            product.lo + (self.hi * other.lo + self.lo * other.hi),
+        )
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
@@ -1,4 +1,11 @@
-use glam::{IVec2, UVec2};
+use glam::{DVec2, IVec2, UVec2};
+
+// touchpad scroll distance in logical pixels that counts as one wheel notch
+const PIXELS_PER_WHEEL_STEP: f64 = 40.0;
+// wheel notches per unit of pinch magnification, a 2% pinch is about one notch
+const PINCH_WHEEL_STEPS: f64 = 50.0;
+// largest zoom a single event may apply, so one flick can't skip several zoom levels
+const MAX_WHEEL_STEPS: f64 = 3.0;
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum MouseButtons {
@@ -16,17 +23,37 @@ pub enum ElementState {
     Released,
 }
 
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum TouchPhase {
+    Started,
+    Moved,
+    // lifted or cancelled by the system
+    Ended,
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum Event<UserEvent> {
     Resized(UVec2),
     WindowClose,
     RedrawFinished,
+    /// Zoom in wheel notches, positive when scrolling up, see `wheel_steps`.
     MouseWheel(UVec2, f32),
-    MouseMove { position: UVec2, delta: IVec2 },
+    MouseMove {
+        position: UVec2,
+        delta: IVec2,
+    },
     MouseButton(MouseButtons, ElementState, UVec2),
     Custom(UserEvent),
     TouchpadMagnify(UVec2, f32),
+    /// A finger on a touchscreen, `id` stays the same from `Started` to `Ended`.
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        position: DVec2,
+    },
     KeyboardInput(winit::event::KeyEvent),
+    ModifiersChanged(winit::keyboard::ModifiersState),
+    ScaleFactorChanged(f64),
     Unknown,
 }
 
@@ -45,6 +72,17 @@ impl From<winit::event::ElementState> for ElementState {
         }
     }
 }
+impl From<winit::event::TouchPhase> for TouchPhase {
+    fn from(value: winit::event::TouchPhase) -> Self {
+        match value {
+            winit::event::TouchPhase::Started => TouchPhase::Started,
+            winit::event::TouchPhase::Moved => TouchPhase::Moved,
+            winit::event::TouchPhase::Ended | winit::event::TouchPhase::Cancelled => {
+                TouchPhase::Ended
+            }
+        }
+    }
+}
 impl From<winit::event::MouseButton> for MouseButtons {
     fn from(value: winit::event::MouseButton) -> Self {
         match value {
@@ -57,3 +95,144 @@ impl From<winit::event::MouseButton> for MouseButtons {
         }
     }
 }
+
+/// Converts a wheel or touchpad scroll into wheel notches, the unit `Event::MouseWheel` carries.
+/// Pixel deltas arrive in physical pixels and are scaled by `scale_factor` first.
+pub fn wheel_steps(delta: winit::event::MouseScrollDelta, scale_factor: f64) -> f32 {
+    let steps = match delta {
+        winit::event::MouseScrollDelta::LineDelta(_x, y) => y as f64,
+        winit::event::MouseScrollDelta::PixelDelta(position) => {
+            position.y / scale_factor / PIXELS_PER_WHEEL_STEP
+        }
+    };
+
+    steps.clamp(-MAX_WHEEL_STEPS, MAX_WHEEL_STEPS) as f32
+}
+
+/// Converts a pinch magnification delta into wheel notches, spreading the fingers zooms in.
+pub fn pinch_steps(delta: f64) -> f32 {
+    (-delta * PINCH_WHEEL_STEPS).clamp(-MAX_WHEEL_STEPS, MAX_WHEEL_STEPS) as f32
+}
+
+/// The pan and zoom of one finger move, in window pixels.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct TouchGesture {
+    // midpoint of the fingers after the move
+    pub position: DVec2,
+    pub delta: DVec2,
+    // finger distance after over before the move, 1 with a single finger
+    pub spread: f64,
+}
+
+/// The fingers on the screen, the first two make the gesture and any further ones are ignored.
+#[derive(Debug, Default)]
+pub struct Touches {
+    fingers: Vec<(u64, DVec2)>,
+}
+
+impl Touches {
+    /// Tracks the finger, returning the gesture when one of the first two moved.
+    /// Fingers landing or lifting mid-gesture only change which fingers the next move measures.
+    pub fn update(&mut self, id: u64, phase: TouchPhase, position: DVec2) -> Option<TouchGesture> {
+        let index = self.fingers.iter().position(|&(finger, _)| finger == id);
+        match (phase, index) {
+            (TouchPhase::Started, None) => {
+                self.fingers.push((id, position));
+                None
+            }
+            (TouchPhase::Ended, Some(index)) => {
+                self.fingers.remove(index);
+                None
+            }
+            (TouchPhase::Moved, Some(index)) if index < 2 => {
+                let (before, before_spread) = self.midpoint_spread();
+                self.fingers[index].1 = position;
+                let (after, after_spread) = self.midpoint_spread();
+
+                Some(TouchGesture {
+                    position: after,
+                    delta: after - before,
+                    spread: match (before_spread, after_spread) {
+                        (Some(before), Some(after)) if before > 0.0 && after > 0.0 => {
+                            after / before
+                        }
+                        _ => 1.0,
+                    },
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn midpoint_spread(&self) -> (DVec2, Option<f64>) {
+        match self.fingers[..] {
+            [(_, first), (_, second), ..] => ((first + second) / 2.0, Some(first.distance(second))),
+            [(_, first)] => (first, None),
+            [] => (DVec2::ZERO, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use winit::dpi::PhysicalPosition;
+    use winit::event::MouseScrollDelta;
+
+    use super::*;
+
+    #[test]
+    fn wheel_steps_are_device_independent_and_clamped() {
+        let line = wheel_steps(MouseScrollDelta::LineDelta(0.0, 1.0), 1.0);
+        let pixels = wheel_steps(
+            MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, 80.0)),
+            2.0,
+        );
+        assert_eq!(line, 1.0);
+        // 80 physical pixels at 200% is 40 logical pixels, one notch
+        assert_eq!(pixels, 1.0);
+
+        assert_eq!(
+            wheel_steps(MouseScrollDelta::LineDelta(0.0, -20.0), 1.0),
+            -3.0
+        );
+        assert_eq!(pinch_steps(1.0), -3.0);
+        assert_eq!(pinch_steps(-0.02), 1.0);
+    }
+
+    #[test]
+    fn touches_pan_pinch_and_survive_finger_changes() {
+        let mut touches = Touches::default();
+        assert_eq!(
+            touches.update(1, TouchPhase::Started, DVec2::new(100.0, 100.0)),
+            None
+        );
+        let pan = touches
+            .update(1, TouchPhase::Moved, DVec2::new(110.0, 95.0))
+            .unwrap();
+        assert_eq!(pan.delta, DVec2::new(10.0, -5.0));
+        assert_eq!(pan.spread, 1.0);
+
+        // the second finger landing doesn't jump the midpoint
+        touches.update(2, TouchPhase::Started, DVec2::new(210.0, 95.0));
+        let pinch = touches
+            .update(2, TouchPhase::Moved, DVec2::new(310.0, 95.0))
+            .unwrap();
+        assert_eq!(pinch.position, DVec2::new(210.0, 95.0));
+        assert_eq!(pinch.delta, DVec2::new(50.0, 0.0));
+        assert_eq!(pinch.spread, 2.0);
+
+        // a third finger is ignored, lifting the others leaves the second panning from where it is
+        touches.update(3, TouchPhase::Started, DVec2::new(0.0, 0.0));
+        assert_eq!(
+            touches.update(3, TouchPhase::Moved, DVec2::new(5.0, 5.0)),
+            None
+        );
+        touches.update(3, TouchPhase::Ended, DVec2::new(5.0, 5.0));
+        touches.update(1, TouchPhase::Ended, DVec2::new(110.0, 95.0));
+        let pan = touches
+            .update(2, TouchPhase::Moved, DVec2::new(300.0, 90.0))
+            .unwrap();
+        assert_eq!(pan.delta, DVec2::new(-10.0, -5.0));
+        assert_eq!(pan.spread, 1.0);
+    }
+}
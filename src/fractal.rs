@@ -0,0 +1,373 @@
+use std::simd::prelude::*;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use glam::DVec2;
+
+use crate::double_double::DoubleDoubleComplex;
+use crate::mandelbrot_simd::f64simd;
+use crate::math::DRect;
+
+/// A point is escaped once `|z|²` reaches this, any radius of at least 2 gives the same set.
+pub const ESCAPE_RADIUS_SQUARED: f64 = 5.0;
+
+/// A lane batch of complex numbers as `(re, im)`.
+pub type ComplexSimd = (f64simd, f64simd);
+
+/// Escape-time iteration of a single fractal family, evaluated for a batch of SIMD lanes.
+pub trait EscapeFractal {
+    /// Maps a point of the complex plane to the starting `z` and the constant `c`.
+    fn initial(&self, point: ComplexSimd) -> (ComplexSimd, ComplexSimd);
+
+    /// Computes the next `z` from the current `z` and `c`.
+    fn iterate(&self, z: ComplexSimd, c: ComplexSimd) -> ComplexSimd;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FractalKind {
+    Mandelbrot,
+    Julia,
+    BurningShip,
+    Multibrot,
+    Tricorn,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fractal {
+    pub kind: FractalKind,
+    pub julia_c: DVec2,
+    pub multibrot_power: u32,
+}
+
+pub struct Mandelbrot;
+
+pub struct Julia {
+    pub c: DVec2,
+}
+
+pub struct BurningShip;
+
+pub struct Multibrot {
+    pub power: u32,
+}
+
+/// Powers the Multibrot iteration supports, below 2 it degenerates and the cost grows with the power.
+pub const MULTIBROT_POWERS: std::ops::RangeInclusive<u32> = 2..=16;
+
+/// Mandelbar, the Mandelbrot iteration on the conjugate of `z`.
+pub struct Tricorn;
+
+impl FractalKind {
+    pub const ALL: [FractalKind; 5] = [
+        FractalKind::Mandelbrot,
+        FractalKind::Julia,
+        FractalKind::BurningShip,
+        FractalKind::Multibrot,
+        FractalKind::Tricorn,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FractalKind::Mandelbrot => "mandelbrot",
+            FractalKind::Julia => "julia",
+            FractalKind::BurningShip => "burning-ship",
+            FractalKind::Multibrot => "multibrot",
+            FractalKind::Tricorn => "tricorn",
+        }
+    }
+
+    /// Center and height of a frame showing the whole set.
+    pub fn default_view(&self) -> (DVec2, f64) {
+        match self {
+            FractalKind::Mandelbrot => (DVec2::new(-0.5, 0.0), 3.0),
+            FractalKind::Julia => (DVec2::new(0.0, 0.0), 3.0),
+            FractalKind::BurningShip => (DVec2::new(-0.5, -0.55), 2.4),
+            FractalKind::Multibrot => (DVec2::new(0.0, 0.0), 2.8),
+            FractalKind::Tricorn => (DVec2::new(-0.65, 0.0), 2.8),
+        }
+    }
+
+    pub fn next(&self) -> FractalKind {
+        let index = Self::ALL.iter().position(|kind| kind == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl FromStr for FractalKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| anyhow!("Unknown fractal kind: {}", s))
+    }
+}
+
+impl Default for Fractal {
+    fn default() -> Self {
+        Self {
+            kind: FractalKind::Mandelbrot,
+            julia_c: DVec2::new(-0.8, 0.156),
+            multibrot_power: 3,
+        }
+    }
+}
+
+impl Fractal {
+    /// Power of `z` in the iteration, how fast |z| grows past the escape radius.
+    pub fn degree(&self) -> u32 {
+        match self.kind {
+            FractalKind::Multibrot => self.multibrot_power,
+            _ => 2,
+        }
+    }
+
+    /// `EscapeFractal::initial` for a single point, for the scalar kernel.
+    pub fn scalar_initial(&self, point: DVec2) -> (DVec2, DVec2) {
+        match self.kind {
+            FractalKind::Julia => (point, self.julia_c),
+            _ => (DVec2::ZERO, point),
+        }
+    }
+
+    /// The `z` values of `point` from the start up to the escape, or `max_iterations` steps
+    /// when it stays bounded.
+    pub fn orbit(&self, point: DVec2, max_iterations: u32) -> Vec<DVec2> {
+        let (mut z, c) = self.scalar_initial(point);
+        let mut orbit = vec![z];
+        for _ in 0..max_iterations {
+            z = self.scalar_iterate(z, c);
+            orbit.push(z);
+            if z.length_squared() >= ESCAPE_RADIUS_SQUARED {
+                break;
+            }
+        }
+
+        orbit
+    }
+
+    /// `EscapeFractal::iterate` for a single point, with the same operations in the same order.
+    pub fn scalar_iterate(&self, z: DVec2, c: DVec2) -> DVec2 {
+        match self.kind {
+            FractalKind::Mandelbrot | FractalKind::Julia => {
+                DVec2::new(z.x * z.x - z.y * z.y + c.x, z.x * z.y + z.x * z.y + c.y)
+            }
+            FractalKind::BurningShip => {
+                let xy = (z.x * z.y).abs();
+                DVec2::new(z.x * z.x - z.y * z.y + c.x, xy + xy + c.y)
+            }
+            FractalKind::Multibrot => {
+                let mut p = z;
+                for _ in 1..self.multibrot_power {
+                    p = DVec2::new(p.x * z.x - p.y * z.y, p.x * z.y + p.y * z.x);
+                }
+                p + c
+            }
+            FractalKind::Tricorn => {
+                DVec2::new(z.x * z.x - z.y * z.y + c.x, -(z.x * z.y + z.x * z.y) + c.y)
+            }
+        }
+    }
+    /// `scalar_initial` in double-double.
+    pub fn double_double_initial(
+        &self,
+        point: DoubleDoubleComplex,
+    ) -> (DoubleDoubleComplex, DoubleDoubleComplex) {
+        match self.kind {
+            FractalKind::Julia => (point, (self.julia_c.x.into(), self.julia_c.y.into())),
+            _ => (Default::default(), point),
+        }
+    }
+
+    /// `scalar_iterate` in double-double.
+    pub fn double_double_iterate(
+        &self,
+        (zx, zy): DoubleDoubleComplex,
+        (cx, cy): DoubleDoubleComplex,
+    ) -> DoubleDoubleComplex {
+        match self.kind {
+            FractalKind::Mandelbrot | FractalKind::Julia => {
+                let xy = zx * zy;
+                (zx * zx - zy * zy + cx, xy + xy + cy)
+            }
+            FractalKind::BurningShip => {
+                let xy = (zx * zy).abs();
+                (zx * zx - zy * zy + cx, xy + xy + cy)
+            }
+            FractalKind::Multibrot => {
+                let (mut px, mut py) = (zx, zy);
+                for _ in 1..self.multibrot_power {
+                    (px, py) = (px * zx - py * zy, px * zy + py * zx);
+                }
+                (px + cx, py + cy)
+            }
+            FractalKind::Tricorn => {
+                let xy = zx * zy;
+                (zx * zx - zy * zy + cx, -(xy + xy) + cy)
+            }
+        }
+    }
+}
+
+impl EscapeFractal for Mandelbrot {
+    fn initial(&self, point: ComplexSimd) -> (ComplexSimd, ComplexSimd) {
+        let zero = f64simd::splat(0.0);
+        ((zero, zero), point)
+    }
+
+    fn iterate(&self, (zx, zy): ComplexSimd, (cx, cy): ComplexSimd) -> ComplexSimd {
+        (zx * zx - zy * zy + cx, zx * zy + zx * zy + cy)
+    }
+}
+
+/// Inside the main cardioid of the Mandelbrot set, where `z` settles on a fixed point.
+pub fn is_in_main_cardioid(c: DVec2) -> bool {
+    let x = c.x - 0.25;
+    let q = x * x + c.y * c.y;
+    q * (q + x) < c.y * c.y / 4.0
+}
+
+/// Inside the period 2 bulb of the Mandelbrot set, the disk of radius 1/4 around -1.
+pub fn is_in_main_circle(c: DVec2) -> bool {
+    (c + DVec2::X).length_squared() < 1.0 / 16.0
+}
+
+/// True only if every point of `rect` is inside the cardioid or the bulb, so nothing in it escapes.
+pub fn is_rect_interior(rect: DRect) -> bool {
+    let max = rect.pos + rect.size;
+    let corners = [
+        rect.pos,
+        DVec2::new(max.x, rect.pos.y),
+        DVec2::new(rect.pos.x, max.y),
+        max,
+    ];
+
+    // the bulb is a disk and each half of the cardioid either side of the real axis is convex,
+    // so containing the corners is enough unless the rect reaches into the cusp at 1/4
+    let reaches_cusp = rect.pos.y <= 0.0 && max.y >= 0.0 && max.x >= 0.25;
+    corners.into_iter().all(is_in_main_circle)
+        || !reaches_cusp && corners.into_iter().all(is_in_main_cardioid)
+}
+
+impl EscapeFractal for Julia {
+    fn initial(&self, point: ComplexSimd) -> (ComplexSimd, ComplexSimd) {
+        (point, (f64simd::splat(self.c.x), f64simd::splat(self.c.y)))
+    }
+
+    fn iterate(&self, z: ComplexSimd, c: ComplexSimd) -> ComplexSimd {
+        Mandelbrot.iterate(z, c)
+    }
+}
+
+impl EscapeFractal for BurningShip {
+    fn initial(&self, point: ComplexSimd) -> (ComplexSimd, ComplexSimd) {
+        Mandelbrot.initial(point)
+    }
+
+    fn iterate(&self, (zx, zy): ComplexSimd, (cx, cy): ComplexSimd) -> ComplexSimd {
+        let xy = (zx * zy).abs();
+        (zx * zx - zy * zy + cx, xy + xy + cy)
+    }
+}
+
+impl EscapeFractal for Multibrot {
+    fn initial(&self, point: ComplexSimd) -> (ComplexSimd, ComplexSimd) {
+        Mandelbrot.initial(point)
+    }
+
+    fn iterate(&self, (zx, zy): ComplexSimd, (cx, cy): ComplexSimd) -> ComplexSimd {
+        let (mut px, mut py) = (zx, zy);
+        for _ in 1..self.power {
+            (px, py) = (px * zx - py * zy, px * zy + py * zx);
+        }
+        (px + cx, py + cy)
+    }
+}
+
+impl EscapeFractal for Tricorn {
+    fn initial(&self, point: ComplexSimd) -> (ComplexSimd, ComplexSimd) {
+        Mandelbrot.initial(point)
+    }
+
+    fn iterate(&self, (zx, zy): ComplexSimd, (cx, cy): ComplexSimd) -> ComplexSimd {
+        (zx * zx - zy * zy + cx, -(zx * zy + zx * zy) + cy)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // iterations until |z|² reaches the escape radius for each lane, `None` if it stays bounded
+    fn escape_counts<F: EscapeFractal>(fractal: &F, c: ComplexSimd) -> [Option<u32>; 8] {
+        let (mut z, c) = fractal.initial(c);
+        let mut counts = [None; 8];
+        for i in 0..1000 {
+            z = fractal.iterate(z, c);
+            let norm = z.0 * z.0 + z.1 * z.1;
+            for (lane, count) in counts.iter_mut().enumerate() {
+                if count.is_none() && norm[lane] >= ESCAPE_RADIUS_SQUARED {
+                    *count = Some(i + 1);
+                }
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn tricorn_known_points() {
+        let re = f64simd::from_array([0.0, -1.0, 0.0, 1.0, -0.2, 0.0, 0.0, 0.0]);
+        let im = f64simd::from_array([0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        let tricorn = escape_counts(&Tricorn, (re, im));
+        let mandelbrot = escape_counts(&Mandelbrot, (re, im));
+
+        // the origin, the period 2 cycle at -1 and a point of the main body stay bounded
+        assert_eq!(tricorn[0], None);
+        assert_eq!(tricorn[1], None);
+        assert_eq!(tricorn[4], None);
+        // 0 -> i -> -1 + i -> 3i, while i is a bounded Misiurewicz point of the Mandelbrot set
+        assert_eq!(tricorn[2], Some(3));
+        assert_eq!(mandelbrot[2], None);
+        // 0 -> 1 -> 2 -> 5
+        assert_eq!(tricorn[3], Some(3));
+    }
+
+    #[test]
+    fn orbits_stop_at_the_escape() {
+        let mandelbrot = Fractal::default();
+        assert_eq!(
+            mandelbrot.orbit(DVec2::new(1.0, 0.0), 100),
+            [0.0, 1.0, 2.0, 5.0].map(|x| DVec2::new(x, 0.0))
+        );
+        // the period 2 cycle runs to the limit
+        let orbit = mandelbrot.orbit(DVec2::new(-1.0, 0.0), 100);
+        assert_eq!(orbit.len(), 101);
+        assert_eq!(orbit[100], DVec2::ZERO);
+    }
+
+    #[test]
+    fn interior_rects_stay_clear_of_the_boundary() {
+        let rect = |min: DVec2, max: DVec2| DRect::from_pos_size(min, max - min);
+
+        assert!(is_rect_interior(rect(
+            DVec2::new(-0.5, -0.3),
+            DVec2::new(0.0, 0.3)
+        )));
+        assert!(is_rect_interior(rect(
+            DVec2::new(-1.1, -0.1),
+            DVec2::new(-0.9, 0.1)
+        )));
+        // the corners are inside, but the cusp at 1/4 cuts into the middle
+        let cusp = rect(DVec2::new(0.2, -0.1), DVec2::new(0.3, 0.1));
+        assert!(is_in_main_cardioid(DVec2::new(0.3, 0.1)));
+        assert!(!is_in_main_cardioid(DVec2::new(0.3, 0.0)));
+        assert!(!is_rect_interior(cusp));
+        // spans the pinch between the cardioid and the bulb
+        assert!(!is_rect_interior(rect(
+            DVec2::new(-0.8, -0.01),
+            DVec2::new(-0.7, 0.01)
+        )));
+    }
+}
@@ -0,0 +1,325 @@
+use std::f32::consts::PI;
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+
+use crate::palette::{lerp_color, rasterize};
+
+/// Rasterizes a GIMP `.ggr` gradient or a `.csv` of `position,r,g,b` stops into palette entries.
+pub fn load_gradient(path: &Path) -> anyhow::Result<Vec<[u8; 4]>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read gradient {}", path.display()))?;
+    let extension = path.extension().and_then(|extension| extension.to_str());
+
+    match extension {
+        Some("ggr") => parse_ggr(&text),
+        Some("csv") => parse_csv(&text),
+        _ => Err(anyhow!(
+            "Unknown gradient format, expected a .ggr or .csv file"
+        )),
+    }
+    .with_context(|| format!("Invalid gradient {}", path.display()))
+}
+
+// how the color moves from the left to the right end of a segment, in GIMP's numbering
+#[derive(Debug, Clone, Copy)]
+enum Blend {
+    Linear,
+    Curved,
+    Sine,
+    SphereIncreasing,
+    SphereDecreasing,
+    Step,
+}
+
+// the space colors are interpolated in, HSV going either way around the hue circle
+#[derive(Debug, Clone, Copy)]
+enum Coloring {
+    Rgb,
+    HsvCounterClockwise,
+    HsvClockwise,
+}
+
+#[derive(Debug)]
+struct Segment {
+    left: f32,
+    middle: f32,
+    right: f32,
+    left_color: [f32; 3],
+    right_color: [f32; 3],
+    blend: Blend,
+    coloring: Coloring,
+}
+
+impl Segment {
+    fn color(&self, position: f32) -> [f32; 3] {
+        let width = self.right - self.left;
+        if width <= f32::EPSILON {
+            return self.left_color;
+        }
+
+        let position = (position - self.left) / width;
+        let middle = (self.middle - self.left) / width;
+        let factor = match self.blend {
+            Blend::Linear => linear_factor(middle, position),
+            Blend::Curved if middle < f32::EPSILON => 1.0,
+            Blend::Curved => position.powf(0.5f32.ln() / middle.ln()),
+            Blend::Sine => ((-PI / 2.0 + PI * linear_factor(middle, position)).sin() + 1.0) / 2.0,
+            Blend::SphereIncreasing => {
+                let p = linear_factor(middle, position) - 1.0;
+                (1.0 - p * p).max(0.0).sqrt()
+            }
+            Blend::SphereDecreasing => {
+                let p = linear_factor(middle, position);
+                1.0 - (1.0 - p * p).max(0.0).sqrt()
+            }
+            Blend::Step if position >= middle => 1.0,
+            Blend::Step => 0.0,
+        };
+
+        match self.coloring {
+            Coloring::Rgb => lerp_color(self.left_color, self.right_color, factor),
+            Coloring::HsvCounterClockwise | Coloring::HsvClockwise => {
+                let left = rgb_to_hsv(self.left_color);
+                let right = rgb_to_hsv(self.right_color);
+                let mut hue_delta = right[0] - left[0];
+                match self.coloring {
+                    Coloring::HsvCounterClockwise if hue_delta < 0.0 => hue_delta += 1.0,
+                    Coloring::HsvClockwise if hue_delta > 0.0 => hue_delta -= 1.0,
+                    _ => {}
+                }
+
+                let mut hsv = lerp_color(left, right, factor);
+                hsv[0] = (left[0] + hue_delta * factor).rem_euclid(1.0);
+                hsv_to_rgb(hsv)
+            }
+        }
+    }
+}
+
+// GIMP's piecewise linear ramp that puts 0.5 at the segment's midpoint
+fn linear_factor(middle: f32, position: f32) -> f32 {
+    if position <= middle {
+        if middle < f32::EPSILON {
+            0.0
+        } else {
+            0.5 * position / middle
+        }
+    } else if middle > 1.0 - f32::EPSILON {
+        1.0
+    } else {
+        0.5 + 0.5 * (position - middle) / (1.0 - middle)
+    }
+}
+
+fn parse_ggr(text: &str) -> anyhow::Result<Vec<[u8; 4]>> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()));
+
+    match lines.next() {
+        Some((_, "GIMP Gradient")) => {}
+        _ => return Err(anyhow!("Line 1: expected the `GIMP Gradient` header")),
+    }
+    let mut lines = lines.skip_while(|(_, line)| line.starts_with("Name:"));
+
+    let (number, count) = lines
+        .next()
+        .ok_or_else(|| anyhow!("Missing the segment count"))?;
+    let count: usize = count.parse().map_err(|_| {
+        anyhow!(
+            "Line {}: expected the segment count, found `{}`",
+            number,
+            count
+        )
+    })?;
+    if count == 0 {
+        return Err(anyhow!(
+            "Line {}: a gradient needs at least one segment",
+            number
+        ));
+    }
+
+    let segments = lines
+        .filter(|(_, line)| !line.is_empty())
+        .take(count)
+        .map(|(number, line)| {
+            parse_segment(line).with_context(|| format!("Line {}: invalid segment", number))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if segments.len() != count {
+        return Err(anyhow!(
+            "Expected {} segments, found {}",
+            count,
+            segments.len()
+        ));
+    }
+
+    Ok(rasterize(|position| {
+        let segment = segments
+            .iter()
+            .find(|segment| position <= segment.right)
+            .unwrap_or(&segments[segments.len() - 1]);
+        segment.color(position)
+    }))
+}
+
+fn parse_segment(line: &str) -> anyhow::Result<Segment> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    // newer GIMP versions append two endpoint color types, only fixed colors are stored
+    if fields.len() < 13 {
+        return Err(anyhow!(
+            "expected at least 13 fields, found {}",
+            fields.len()
+        ));
+    }
+
+    let values = fields[..11]
+        .iter()
+        .map(|field| field.parse::<f32>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let blend = match fields[11] {
+        "0" => Blend::Linear,
+        "1" => Blend::Curved,
+        "2" => Blend::Sine,
+        "3" => Blend::SphereIncreasing,
+        "4" => Blend::SphereDecreasing,
+        "5" => Blend::Step,
+        blend => return Err(anyhow!("unknown blend type {}", blend)),
+    };
+    let coloring = match fields[12] {
+        "0" => Coloring::Rgb,
+        "1" => Coloring::HsvCounterClockwise,
+        "2" => Coloring::HsvClockwise,
+        coloring => return Err(anyhow!("unknown coloring type {}", coloring)),
+    };
+
+    let [left, middle, right] = [values[0], values[1], values[2]];
+    if !(0.0 <= left && left <= middle && middle <= right && right <= 1.0) {
+        return Err(anyhow!("positions must rise within 0..1"));
+    }
+
+    // the alpha at values[6] and values[10] is left out, the palette is opaque
+    Ok(Segment {
+        left,
+        middle,
+        right,
+        left_color: [values[3], values[4], values[5]],
+        right_color: [values[7], values[8], values[9]],
+        blend,
+        coloring,
+    })
+}
+
+fn parse_csv(text: &str) -> anyhow::Result<Vec<[u8; 4]>> {
+    let stops = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| {
+            parse_stop(line).with_context(|| format!("Line {}: expected `position,r,g,b`", number))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    if stops.is_empty() {
+        return Err(anyhow!("No color stops"));
+    }
+    if stops.windows(2).any(|pair| pair[1].0 < pair[0].0) {
+        return Err(anyhow!("Stop positions must not decrease"));
+    }
+
+    Ok(rasterize(|position| {
+        let next = stops.partition_point(|(stop, _)| *stop < position);
+        match (stops.get(next.wrapping_sub(1)), stops.get(next)) {
+            (Some(&(from, from_color)), Some(&(to, to_color))) if to > from => {
+                lerp_color(from_color, to_color, (position - from) / (to - from))
+            }
+            (_, Some(&(_, color))) | (Some(&(_, color)), None) => color,
+            (None, None) => unreachable!(),
+        }
+    }))
+}
+
+fn parse_stop(line: &str) -> anyhow::Result<(f32, [f32; 3])> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [position, r, g, b] = fields[..] else {
+        return Err(anyhow!("found {} fields", fields.len()));
+    };
+
+    let position: f32 = position.parse()?;
+    if !(0.0..=1.0).contains(&position) {
+        return Err(anyhow!("position {} is outside 0..1", position));
+    }
+    let channel = |value: &str| value.parse::<u8>().map(|value| value as f32 / 255.0);
+
+    Ok((position, [channel(r)?, channel(g)?, channel(b)?]))
+}
+
+fn rgb_to_hsv([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+    if delta <= 0.0 {
+        return [0.0, 0.0, max];
+    }
+
+    let hue = if max == r {
+        (g - b) / delta
+    } else if max == g {
+        2.0 + (b - r) / delta
+    } else {
+        4.0 + (r - g) / delta
+    };
+
+    [(hue / 6.0).rem_euclid(1.0), delta / max, max]
+}
+
+fn hsv_to_rgb([h, s, v]: [f32; 3]) -> [f32; 3] {
+    let sector = h * 6.0;
+    let channel = |offset: f32| {
+        let k = (offset + sector) % 6.0;
+        v - v * s * k.min(4.0 - k).clamp(0.0, 1.0)
+    };
+
+    [channel(5.0), channel(3.0), channel(1.0)]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::palette::PALETTE_SIZE;
+
+    #[test]
+    fn gradients_rasterize_between_their_end_colors() {
+        let ggr = "GIMP Gradient\nName: Test\n2\n\
+            0 0.25 0.5 0 0 0 1 1 0 0 1 0 0\n\
+            0.5 0.75 1 1 0 0 1 0 0 1 1 0 2 0 0\n";
+        let csv = "# position,r,g,b\n0,0,0,0\n0.5,255,0,0\n1,0,0,255\n";
+
+        for colors in [parse_ggr(ggr).unwrap(), parse_csv(csv).unwrap()] {
+            assert_eq!(colors.len(), PALETTE_SIZE);
+            assert_eq!(colors[0], [0, 0, 0, 255]);
+            assert_eq!(colors[PALETTE_SIZE - 1], [0, 0, 255, 255]);
+            // red at the middle stop, rising from black before it
+            assert!(colors[PALETTE_SIZE / 2][0] > 240);
+            assert!(colors[PALETTE_SIZE / 4][0] < colors[PALETTE_SIZE / 2][0]);
+        }
+
+        // the clockwise HSV segment passes through magenta rather than a dark purple
+        let magenta = parse_ggr(ggr).unwrap()[PALETTE_SIZE * 3 / 4];
+        assert!(magenta[0] > 200 && magenta[2] > 200, "{:?}", magenta);
+    }
+
+    #[test]
+    fn malformed_gradients_name_the_line() {
+        let error = parse_csv("0,0,0,0\n0.5,300,0,0\n").unwrap_err();
+        assert!(format!("{:#}", error).starts_with("Line 2:"), "{:#}", error);
+
+        let error = parse_ggr("GIMP Gradient\nName: Test\n1\n0 0.5 1 0 0 0 1\n").unwrap_err();
+        assert!(format!("{:#}", error).contains("13 fields"), "{:#}", error);
+
+        assert!(parse_csv("1,0,0,0\n0,0,0,0\n").is_err());
+        assert!(parse_ggr("GIMP Palette\n").is_err());
+    }
+}
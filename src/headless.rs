@@ -0,0 +1,335 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use glam::{DVec2, UVec2, Vec3};
+
+use crate::config::Config;
+use crate::fractal::ESCAPE_RADIUS_SQUARED;
+use crate::mandel_texture::{
+    calc_max_iters, detail_split, ColoringMode, DEFAULT_INTERIOR_COLOR, DEFAULT_PALETTE_PERIOD,
+};
+use crate::mandelbrot_simd::{
+    mandelbrot_simd, IterStorage, KernelParams, Pixel, SamplePattern, MIN_ITER,
+};
+use crate::math::{DRect, URect};
+use crate::palette::PaletteKind;
+use crate::view_state::ViewState;
+
+// size of the recorded frames, the width must be a multiple of the kernel's SIMD lane count
+pub const FRAME_SIZE: UVec2 = UVec2::new(1280, 720);
+
+/// Kernel settings for rendering `view` over `frame` without the window's `MandelTexture`.
+pub fn kernel_params(view: &ViewState, frame: DRect, config: &Config) -> KernelParams {
+    let (doublings, supersample) = detail_split(config.detail, frame);
+    KernelParams {
+        max_iterations: view.iter_override.map_or_else(
+            || (calc_max_iters(frame, config.iter_cap) << doublings).min(config.iter_cap),
+            |iters| iters.clamp(MIN_ITER, config.iter_cap),
+        ),
+        fractal: view.fractal,
+        detect_glitches: false,
+        time_budget: None,
+        supersample: config
+            .supersample
+            .or(supersample.then_some(SamplePattern::RotatedGrid)),
+        iter_storage: config.iter_storage,
+        kernel: config.kernel,
+    }
+}
+
+/// The part of the plane `view` shows in a `size` image, `view.height` tall.
+pub fn frame_rect(view: &ViewState, size: UVec2) -> DRect {
+    let aspect = size.x as f64 / size.y as f64;
+    DRect::from_center_size(view.center, DVec2::new(aspect, 1.0) * view.height)
+}
+
+/// Renders and colors `view` as a `size` image.
+pub fn render_image(
+    view: &ViewState,
+    size: UVec2,
+    config: &Config,
+    palette: &Palette,
+    thread_count: u32,
+) -> anyhow::Result<image::RgbImage> {
+    let params = kernel_params(view, frame_rect(view, size), config);
+    let pixels = render(size, view.center, 1.0 / view.height, &params, thread_count)?;
+
+    Ok(palette.colorize(size, &pixels, &params))
+}
+
+/// Saves numbered `FRAME_SIZE` frames to a directory, for the recordings that run without a window.
+pub struct Frames<'a> {
+    config: &'a Config,
+    dir: &'a Path,
+    palette: Palette,
+    thread_count: u32,
+}
+
+impl<'a> Frames<'a> {
+    /// Creates `dir` if needed, the frames use every core.
+    pub fn new(config: &'a Config, dir: &'a Path) -> anyhow::Result<Self> {
+        let palette = Palette::from_config(config)?;
+        let thread_count = std::thread::available_parallelism()?.get() as u32;
+        std::fs::create_dir_all(dir)?;
+
+        Ok(Self {
+            config,
+            dir,
+            palette,
+            thread_count,
+        })
+    }
+
+    /// Renders `view` and saves it as frame `index`, returning the file's path.
+    pub fn save(&self, index: u32, view: &ViewState) -> anyhow::Result<PathBuf> {
+        let image = render_image(
+            view,
+            FRAME_SIZE,
+            self.config,
+            &self.palette,
+            self.thread_count,
+        )?;
+        let path = self.dir.join(format!("frame_{:05}.png", index));
+        image.save(&path)?;
+
+        Ok(path)
+    }
+}
+
+/// Computes a `size` image, splitting its rows across `thread_count` threads.
+/// The width must be a multiple of the kernel's SIMD lane count.
+pub fn render(
+    size: UVec2,
+    center: DVec2,
+    scale: f64,
+    params: &KernelParams,
+    thread_count: u32,
+) -> anyhow::Result<Vec<Pixel>> {
+    let cancel_token = Arc::new(AtomicBool::new(false));
+    render_cancellable(size, center, scale, params, thread_count, &cancel_token)
+}
+
+/// `render` that stops early once `cancel_token` is set, the pixels are incomplete then.
+pub fn render_cancellable(
+    size: UVec2,
+    center: DVec2,
+    scale: f64,
+    params: &KernelParams,
+    thread_count: u32,
+    cancel_token: &Arc<AtomicBool>,
+) -> anyhow::Result<Vec<Pixel>> {
+    let band_height = size.y.div_ceil(thread_count);
+    let mut pixels = vec![Pixel::default(); size.x as usize * size.y as usize];
+
+    std::thread::scope(|scope| {
+        let bands = pixels.chunks_mut(band_height as usize * size.x as usize);
+        let handles: Vec<_> = bands
+            .enumerate()
+            .map(|(index, band)| {
+                let tex_rect = URect::from_pos_size(
+                    UVec2::new(0, index as u32 * band_height),
+                    UVec2::new(size.x, band.len() as u32 / size.x),
+                );
+                let cancel_token = cancel_token.clone();
+
+                scope.spawn(move || {
+                    mandelbrot_simd(size, tex_rect, center, scale, params, cancel_token, band)
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().unwrap().map(|_| ()))
+    })?;
+
+    Ok(pixels)
+}
+
+/// CPU copy of the screen shader's coloring.
+pub struct Palette {
+    colors: Vec<Vec3>,
+    interior_color: Vec3,
+    color_offset: f32,
+    palette_period: u32,
+    coloring_mode: ColoringMode,
+}
+
+impl Palette {
+    pub fn new(kind: PaletteKind, gradient: Option<&Path>) -> anyhow::Result<Self> {
+        let colors = kind
+            .colors(gradient)?
+            .into_iter()
+            .map(|color| Vec3::new(color[0] as f32, color[1] as f32, color[2] as f32) / 255.0)
+            .collect();
+
+        Ok(Self {
+            colors,
+            interior_color: DEFAULT_INTERIOR_COLOR.truncate(),
+            color_offset: 0.0,
+            palette_period: DEFAULT_PALETTE_PERIOD,
+            coloring_mode: ColoringMode::default(),
+        })
+    }
+
+    /// The configured palette, interior color, color offset, period and coloring mode.
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        Ok(Self {
+            interior_color: config.interior_color.truncate(),
+            color_offset: config.color_offset,
+            palette_period: config.palette_period,
+            coloring_mode: config.coloring_mode,
+            ..Self::new(config.palette, config.gradient.as_deref())?
+        })
+    }
+
+    /// Colors a `render` result, flipped so the image's top row is the top of the view.
+    /// `params` are the ones the pixels were rendered with.
+    pub fn colorize(
+        &self,
+        size: UVec2,
+        pixels: &[Pixel],
+        params: &KernelParams,
+    ) -> image::RgbImage {
+        let smooth_degree =
+            (self.coloring_mode == ColoringMode::Smooth).then(|| params.fractal.degree());
+        // texture row 0 is the bottom of the screen, image row 0 the top
+        image::RgbImage::from_fn(size.x, size.y, |x, y| {
+            let row = size.y - 1 - y;
+            let pixel = pixels[row as usize * size.x as usize + x as usize];
+            image::Rgb(self.color(pixel, params.iter_storage, smooth_degree))
+        })
+    }
+
+    /// Linear filtered lookup with clamped edges, like the shader's sampler.
+    fn sample(&self, u: f32) -> Vec3 {
+        let last = self.colors.len() - 1;
+        let texel = (u * self.colors.len() as f32 - 0.5).clamp(0.0, last as f32);
+        let index = texel as usize;
+        let next = (index + 1).min(last);
+
+        self.colors[index].lerp(self.colors[next], texel.fract())
+    }
+
+    /// Matches `palette_color` and `smooth_count` in screen_shader.wgsl,
+    /// encoded to sRGB like the surface.
+    fn color(
+        &self,
+        pixel: Pixel,
+        iter_storage: IterStorage,
+        smooth_degree: Option<u32>,
+    ) -> [u8; 3] {
+        if pixel.count() == 0 {
+            return self.interior_color.to_array().map(linear_to_srgb);
+        }
+
+        let mut iters = iter_storage.decode(pixel.count()) as f32 + self.color_offset;
+        if let Some(degree) = smooth_degree {
+            let ratio = (pixel.escape_log2_norm() / ESCAPE_RADIUS_SQUARED.log2()).max(1.0);
+            iters += 1.0 - (ratio.log2() / (degree as f64).log2()).clamp(0.0, 1.0) as f32;
+        }
+        let period = self.palette_period as f32;
+        let norm = (iters % period) / period;
+        let brightness = iters.min(16.0) / 16.0;
+        let rgb = self.sample(norm.powf(0.4)) * brightness;
+
+        rgb.to_array().map(linear_to_srgb)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let encoded = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+
+    (encoded * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fractal::Fractal;
+    use glam::Vec4;
+
+    #[test]
+    fn renders_are_reproducible() {
+        let size = UVec2::new(128, 64);
+        let view = ViewState {
+            center: DVec2::new(-0.7453, 0.1127),
+            height: 0.01,
+            fractal: Fractal::default(),
+            iter_override: None,
+            coloring: None,
+        };
+        let config = Config {
+            supersample: Some(SamplePattern::RotatedGrid),
+            ..Config::default()
+        };
+        let params = kernel_params(&view, frame_rect(&view, size), &config);
+
+        // the split across threads must not show in the output either
+        let palette = Palette::from_config(&Config {
+            coloring_mode: ColoringMode::Smooth,
+            ..Config::default()
+        })
+        .unwrap();
+        let first = render(size, view.center, 1.0 / view.height, &params, 1).unwrap();
+        let second = render(size, view.center, 1.0 / view.height, &params, 3).unwrap();
+
+        assert_eq!(
+            bytemuck::cast_slice::<Pixel, u8>(&first),
+            bytemuck::cast_slice::<Pixel, u8>(&second)
+        );
+        assert_eq!(
+            palette.colorize(size, &first, &params).into_raw(),
+            palette.colorize(size, &second, &params).into_raw()
+        );
+    }
+
+    #[test]
+    fn interior_color_and_offset_leave_a_gap() {
+        let config = Config {
+            interior_color: Vec4::new(0.0, 0.0, 1.0, 1.0),
+            color_offset: 16.0,
+            ..Config::default()
+        };
+        let palette = Palette::from_config(&config).unwrap();
+        let default = Palette::new(PaletteKind::Classic, None).unwrap();
+        let [interior, first_band]: [Pixel; 2] = bytemuck::cast([0u16, 0, 1, 0]);
+
+        assert_eq!(
+            default.color(interior, IterStorage::Linear, None),
+            [0, 0, 0]
+        );
+        assert_eq!(
+            palette.color(interior, IterStorage::Linear, None),
+            [0, 0, 255]
+        );
+
+        // the first band fades in from black by default, at full brightness past the offset
+        assert_eq!(
+            default.color(first_band, IterStorage::Linear, None),
+            [0, 0, 0]
+        );
+        let offset = palette.color(first_band, IterStorage::Linear, None);
+        assert!(offset.iter().any(|&channel| channel > 0), "{:?}", offset);
+    }
+
+    #[test]
+    fn palette_repeats_after_its_period() {
+        let palette = Palette::from_config(&Config {
+            palette_period: 96,
+            ..Config::default()
+        })
+        .unwrap();
+        let [band, next_pass, halfway]: [Pixel; 3] = bytemuck::cast([20u16, 0, 116, 0, 68, 0]);
+
+        let color = |pixel| palette.color(pixel, IterStorage::Linear, None);
+        assert_eq!(color(band), color(next_pass));
+        assert_ne!(color(band), color(halfway));
+    }
+}
@@ -0,0 +1,211 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use glam::{DVec2, UVec2};
+
+use crate::fractal::Fractal;
+use crate::mandelbrot_simd::{IterStorage, Pixel};
+use crate::math::DRect;
+use crate::view_state::{clamp_power, ViewState};
+
+const MAGIC: &[u8; 8] = b"MANDITER";
+const VERSION: u32 = 1;
+
+/// The window's pixels of a still as computed, with what it takes to show them again.
+///
+/// Little endian: the magic, a version, the frame rect as x, y, width and height, the fractal
+/// kind and storage as length-prefixed names, the Julia constant, the Multibrot power,
+/// the iteration count, width and height, then the pixels row by row from the bottom
+/// as pairs of u16, see `Pixel`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IterDump {
+    pub frame_rect: DRect,
+    pub fractal: Fractal,
+    pub max_iterations: u32,
+    pub iter_storage: IterStorage,
+    pub size: UVec2,
+    pub pixels: Vec<Pixel>,
+}
+
+impl IterDump {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let bytes = std::fs::read(path)
+            .map_err(|err| anyhow!("Failed to read {}: {}", path.display(), err))?;
+        Self::from_bytes(&bytes)
+            .map_err(|err| anyhow!("Invalid iteration dump {}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, self.to_bytes())?;
+
+        Ok(())
+    }
+
+    /// The view to show the dump in, its iterations as a manual count.
+    pub fn view_state(&self) -> ViewState {
+        ViewState {
+            center: self.frame_rect.center(),
+            height: self.frame_rect.size.y,
+            fractal: self.fractal,
+            iter_override: Some(self.max_iterations),
+            coloring: None,
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(128 + self.pixels.len() * size_of::<Pixel>());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        for value in [
+            self.frame_rect.pos.x,
+            self.frame_rect.pos.y,
+            self.frame_rect.size.x,
+            self.frame_rect.size.y,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for name in [self.fractal.kind.name(), self.iter_storage.name()] {
+            bytes.push(name.len() as u8);
+            bytes.extend_from_slice(name.as_bytes());
+        }
+        bytes.extend_from_slice(&self.fractal.julia_c.x.to_le_bytes());
+        bytes.extend_from_slice(&self.fractal.julia_c.y.to_le_bytes());
+        for value in [
+            self.fractal.multibrot_power,
+            self.max_iterations,
+            self.size.x,
+            self.size.y,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in bytemuck::cast_slice::<Pixel, u16>(&self.pixels) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(anyhow!("Not an iteration dump"));
+        }
+        let version = reader.u32()?;
+        if version != VERSION {
+            return Err(anyhow!("Unsupported iteration dump version: {}", version));
+        }
+
+        let frame_rect = DRect::from_pos_size(
+            DVec2::new(reader.f64()?, reader.f64()?),
+            DVec2::new(reader.f64()?, reader.f64()?),
+        );
+        let kind = reader.name()?.parse()?;
+        let iter_storage = reader.name()?.parse()?;
+        let julia_c = DVec2::new(reader.f64()?, reader.f64()?);
+        // a damaged power would break smooth coloring or iterate for ages
+        let multibrot_power = clamp_power(reader.u32()? as u64);
+        let max_iterations = reader.u32()?;
+        let size = UVec2::new(reader.u32()?, reader.u32()?);
+
+        let len = size.x as usize * size.y as usize;
+        if reader.bytes.len() != len * size_of::<Pixel>() {
+            return Err(anyhow!(
+                "Iteration dump has {} bytes of pixels, {}x{} needs {}",
+                reader.bytes.len(),
+                size.x,
+                size.y,
+                len * size_of::<Pixel>()
+            ));
+        }
+        let values = (0..2 * len)
+            .map(|_| reader.u16())
+            .collect::<anyhow::Result<Vec<u16>>>()?;
+        let pixels = bytemuck::cast_slice(&values).to_vec();
+
+        Ok(Self {
+            frame_rect,
+            fractal: Fractal {
+                kind,
+                julia_c,
+                multibrot_power,
+            },
+            max_iterations,
+            iter_storage,
+            size,
+            pixels,
+        })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(anyhow!("Iteration dump is truncated"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn u16(&mut self) -> anyhow::Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn f64(&mut self) -> anyhow::Result<f64> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn name(&mut self) -> anyhow::Result<&'a str> {
+        let len = self.take(1)?[0] as usize;
+        Ok(std::str::from_utf8(self.take(len)?)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::fractal::{FractalKind, MULTIBROT_POWERS};
+
+    #[test]
+    fn dumps_round_trip_and_reject_damage() {
+        let dump = IterDump {
+            frame_rect: DRect::from_pos_size(DVec2::new(-2.0, -1.0), DVec2::new(3.0, 2.0)),
+            fractal: Fractal {
+                kind: FractalKind::Julia,
+                julia_c: DVec2::new(-0.8, 0.156),
+                multibrot_power: 3,
+            },
+            max_iterations: 1234,
+            iter_storage: IterStorage::Log,
+            size: UVec2::new(3, 2),
+            pixels: bytemuck::cast_slice(&[1u16, 0, 2, 1000, 0, 2000, 4, 3000, 5, 4000, 6, 5000])
+                .to_vec(),
+        };
+
+        let bytes = dump.to_bytes();
+        assert_eq!(IterDump::from_bytes(&bytes).unwrap(), dump);
+
+        assert!(IterDump::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(IterDump::from_bytes(&bytes[1..]).is_err());
+
+        for (power, loaded) in [
+            (0, *MULTIBROT_POWERS.start()),
+            (1 << 30, *MULTIBROT_POWERS.end()),
+        ] {
+            let mut damaged = dump.clone();
+            damaged.fractal.multibrot_power = power;
+            let damaged = IterDump::from_bytes(&damaged.to_bytes()).unwrap();
+            assert_eq!(damaged.fractal.multibrot_power, loaded);
+        }
+    }
+}
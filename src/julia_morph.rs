@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use glam::DVec2;
+
+use crate::config::Config;
+use crate::fractal::{Fractal, FractalKind};
+use crate::headless::Frames;
+use crate::view_state::ViewState;
+
+/// Sweeps the Julia constant along `path` over a fixed view, saving every frame to `dir`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JuliaMorph {
+    pub dir: PathBuf,
+    pub path: JuliaPath,
+    pub frames: u32,
+}
+
+impl JuliaMorph {
+    // the classic loop, it passes the dendrites, the rabbit-like sets and the dust between them
+    pub const DEFAULT_PATH: JuliaPath = JuliaPath::Circle {
+        center: DVec2::ZERO,
+        radius: 0.7885,
+    };
+    pub const DEFAULT_FRAMES: u32 = 360;
+}
+
+/// Where the Julia constant goes, `circle:x,y,radius` or `line:x0,y0,x1,y1` on the command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JuliaPath {
+    /// Once around, counterclockwise from the point right of the center, so the video loops.
+    Circle { center: DVec2, radius: f64 },
+    /// From one point to the other, both ends included.
+    Line { from: DVec2, to: DVec2 },
+}
+
+impl JuliaPath {
+    /// The constant of frame `index` out of `frames`.
+    pub fn at(&self, index: u32, frames: u32) -> DVec2 {
+        match *self {
+            JuliaPath::Circle { center, radius } => {
+                let angle = std::f64::consts::TAU * index as f64 / frames as f64;
+                center + DVec2::from_angle(angle) * radius
+            }
+            JuliaPath::Line { from, to } => {
+                let t = index as f64 / (frames.max(2) - 1) as f64;
+                from.lerp(to, t)
+            }
+        }
+    }
+}
+
+impl FromStr for JuliaPath {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, values) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Expected `circle:x,y,radius` or `line:x0,y0,x1,y1`"))?;
+        let values = values
+            .split(',')
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match (kind, &values[..]) {
+            ("circle", &[x, y, radius]) if radius > 0.0 => Ok(JuliaPath::Circle {
+                center: DVec2::new(x, y),
+                radius,
+            }),
+            ("line", &[x0, y0, x1, y1]) => Ok(JuliaPath::Line {
+                from: DVec2::new(x0, y0),
+                to: DVec2::new(x1, y1),
+            }),
+            _ => Err(anyhow!("Invalid Julia path: {}", s)),
+        }
+    }
+}
+
+/// Renders the frames without opening a window, the view stays put and only the constant moves.
+pub fn run(config: &Config, morph: &JuliaMorph) -> anyhow::Result<()> {
+    let (center, height) = FractalKind::Julia.default_view();
+    let view = config.view.unwrap_or(ViewState {
+        center,
+        height,
+        ..ViewState::default()
+    });
+    let frames = Frames::new(config, &morph.dir)?;
+
+    for index in 0..morph.frames {
+        let view = ViewState {
+            fractal: Fractal {
+                kind: FractalKind::Julia,
+                julia_c: morph.path.at(index, morph.frames),
+                ..view.fractal
+            },
+            ..view
+        };
+        let path = frames.save(index, &view)?;
+        println!("Saved {} (c = {})", path.display(), view.fractal.julia_c);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn paths_parse_and_cover_their_ends() {
+        let circle: JuliaPath = "circle:-0.5,0,0.25".parse().unwrap();
+        assert!((circle.at(0, 4) - DVec2::new(-0.25, 0.0)).length() < 1e-12);
+        assert!((circle.at(1, 4) - DVec2::new(-0.5, 0.25)).length() < 1e-12);
+
+        let line: JuliaPath = "line:-0.8,0.156,-0.7,0.2".parse().unwrap();
+        assert_eq!(line.at(0, 11), DVec2::new(-0.8, 0.156));
+        assert!((line.at(10, 11) - DVec2::new(-0.7, 0.2)).length() < 1e-12);
+
+        assert!("circle:0,0".parse::<JuliaPath>().is_err());
+        assert!("circle:0,0,-1".parse::<JuliaPath>().is_err());
+        assert!("spiral:0,0,1".parse::<JuliaPath>().is_err());
+    }
+}
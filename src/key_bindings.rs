@@ -0,0 +1,212 @@
+use winit::keyboard::KeyCode;
+
+/// Everything the keyboard can do outside of debug builds, dispatched by `TiledFractalApp::update`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    ToggleHelp,
+    CycleMsaa,
+    NextFractalKind,
+    JuliaAtCursor,
+    JuliaRealUp,
+    JuliaRealDown,
+    JuliaImagUp,
+    JuliaImagDown,
+    MoreIterations,
+    FewerIterations,
+    AutoIterations,
+    ToggleIterationLock,
+    MoreDetail,
+    LessDetail,
+    RenderScaleUp,
+    RenderScaleDown,
+    RenderRegionUp,
+    RenderRegionDown,
+    ToggleGlitchDetection,
+    ToggleLegend,
+    ToggleBoundary,
+    Autofocus,
+    ToggleCrosshair,
+    CycleZoomAnchor,
+    PinZoomAnchor,
+    CycleInteriorColoring,
+    CycleColoringMode,
+    ToggleBandAa,
+    CyclePalette,
+    LongerPalettePeriod,
+    ShorterPalettePeriod,
+    ToggleProbe,
+    ToggleOrbit,
+    ToggleMeasure,
+    CycleFpsCap,
+    ToggleFit,
+    Screenshot,
+    ExportPalette,
+    PrintLink,
+    SaveViewState,
+    ToggleNavigationRecording,
+    CopyLocation,
+    PrintTileTimings,
+    ToggleComputeStats,
+    ToggleAdapterInfo,
+    RenderStill,
+    DumpIterations,
+    Recompute,
+    ResetView,
+    NextLocation,
+    PreviousLocation,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct KeyBinding {
+    pub key: KeyCode,
+    pub shift: bool,
+    pub action: Action,
+    pub description: &'static str,
+}
+
+const fn bind(key: KeyCode, action: Action, description: &'static str) -> KeyBinding {
+    KeyBinding {
+        key,
+        shift: false,
+        action,
+        description,
+    }
+}
+
+const fn bind_shift(key: KeyCode, action: Action, description: &'static str) -> KeyBinding {
+    KeyBinding {
+        key,
+        shift: true,
+        action,
+        description,
+    }
+}
+
+// the single list of key bindings, the help text is generated from it
+#[rustfmt::skip]
+pub const KEY_BINDINGS: [KeyBinding; 52] = [
+    bind(KeyCode::F1,           Action::ToggleHelp,            "show or hide this help"),
+    bind_shift(KeyCode::Slash,  Action::ToggleHelp,            "show or hide this help"),
+    bind(KeyCode::KeyM,         Action::CycleMsaa,             "cycle MSAA sample count"),
+    bind(KeyCode::KeyK,         Action::NextFractalKind,       "next fractal kind"),
+    bind(KeyCode::KeyJ,         Action::JuliaAtCursor,         "Julia set at the cursor"),
+    bind_shift(KeyCode::ArrowRight, Action::JuliaRealUp,       "nudge the Julia constant right"),
+    bind_shift(KeyCode::ArrowLeft,  Action::JuliaRealDown,     "nudge the Julia constant left"),
+    bind_shift(KeyCode::ArrowUp,    Action::JuliaImagUp,       "nudge the Julia constant up"),
+    bind_shift(KeyCode::ArrowDown,  Action::JuliaImagDown,     "nudge the Julia constant down"),
+    bind(KeyCode::BracketRight, Action::MoreIterations,        "more iterations"),
+    bind(KeyCode::BracketLeft,  Action::FewerIterations,       "fewer iterations"),
+    bind_shift(KeyCode::BracketRight, Action::LongerPalettePeriod, "more iterations per palette pass, wider bands"),
+    bind_shift(KeyCode::BracketLeft,  Action::ShorterPalettePeriod, "fewer iterations per palette pass, tighter bands"),
+    bind(KeyCode::Backslash,    Action::AutoIterations,        "automatic iterations"),
+    bind_shift(KeyCode::Backslash, Action::ToggleIterationLock, "lock iterations at the current count while zooming"),
+    bind(KeyCode::Equal,        Action::MoreDetail,            "more detail, edges when shallow, iterations when deep"),
+    bind(KeyCode::Minus,        Action::LessDetail,            "less detail"),
+    bind(KeyCode::Period,       Action::RenderScaleUp,         "higher render scale"),
+    bind(KeyCode::Comma,        Action::RenderScaleDown,       "lower render scale"),
+    bind_shift(KeyCode::Period, Action::RenderRegionUp,        "draw into more of the window"),
+    bind_shift(KeyCode::Comma,  Action::RenderRegionDown,      "draw into less of the window, for slow machines"),
+    bind(KeyCode::KeyG,         Action::ToggleGlitchDetection, "toggle glitch detection"),
+    bind(KeyCode::KeyB,         Action::ToggleLegend,          "toggle palette legend"),
+    bind_shift(KeyCode::KeyB,   Action::ToggleBandAa,          "toggle derivative anti-aliasing of the palette, best with smooth coloring"),
+    bind(KeyCode::KeyE,         Action::ToggleBoundary,        "toggle boundary overlay"),
+    bind_shift(KeyCode::KeyE,   Action::Autofocus,             "glide into the busiest part of the boundary in view"),
+    bind(KeyCode::KeyV,         Action::ToggleCrosshair,       "toggle crosshair at the view center"),
+    bind_shift(KeyCode::KeyV,   Action::CycleZoomAnchor,       "cycle what zooms keep in place: cursor, view center or pinned point"),
+    bind_shift(KeyCode::KeyC,   Action::PinZoomAnchor,         "pin the point under the cursor as the zoom anchor"),
+    bind(KeyCode::KeyI,         Action::CycleInteriorColoring, "cycle interior coloring"),
+    bind_shift(KeyCode::KeyI,   Action::ToggleAdapterInfo,     "show the GPU and graphics backend in the title"),
+    bind(KeyCode::KeyO,         Action::CyclePalette,          "cycle palette, color-blind safe ones included"),
+    bind_shift(KeyCode::KeyO,   Action::CycleColoringMode,     "cycle coloring mode, bands or smooth"),
+    bind(KeyCode::KeyH,         Action::ToggleProbe,           "show iterations under the cursor"),
+    bind(KeyCode::KeyY,         Action::ToggleOrbit,           "click a point to draw its orbit"),
+    bind_shift(KeyCode::KeyY,   Action::ToggleMeasure,         "click two points to measure their distance and angle"),
+    bind(KeyCode::KeyF,         Action::CycleFpsCap,           "cycle frame rate cap"),
+    bind_shift(KeyCode::KeyF,   Action::ToggleFit,             "fill the window or fit the view's aspect with bars"),
+    bind(KeyCode::KeyP,         Action::Screenshot,            "save a screenshot"),
+    bind_shift(KeyCode::KeyP,   Action::ExportPalette,         "save the palette"),
+    bind(KeyCode::KeyL,         Action::PrintLink,             "print a link to the view"),
+    bind_shift(KeyCode::KeyL,   Action::SaveViewState,         "save the view and its coloring, load with --load"),
+    bind(KeyCode::KeyC,         Action::CopyLocation,          "copy the location"),
+    bind(KeyCode::KeyT,         Action::PrintTileTimings,      "print tile timings"),
+    bind_shift(KeyCode::KeyT,   Action::ToggleNavigationRecording, "start or stop recording the navigation, replay with --replay"),
+    bind(KeyCode::KeyU,         Action::ToggleComputeStats,    "toggle compute stats in the title"),
+    bind(KeyCode::KeyR,         Action::RenderStill,           "render every window pixel exactly"),
+    bind_shift(KeyCode::KeyR,   Action::DumpIterations,        "render every window pixel and save the iterations"),
+    bind(KeyCode::F5,           Action::Recompute,             "recompute every tile in view, after changing what the kernel computes"),
+    bind(KeyCode::Home,         Action::ResetView,             "reset the view"),
+    bind(KeyCode::KeyN,         Action::NextLocation,          "next famous location"),
+    bind_shift(KeyCode::KeyN,   Action::PreviousLocation,      "previous famous location"),
+];
+
+/// The action bound to `key`, a binding without Shift also applies with Shift held.
+pub fn action_for(key: KeyCode, shift: bool) -> Option<Action> {
+    let find = |shift| {
+        KEY_BINDINGS
+            .iter()
+            .find(|binding| binding.key == key && binding.shift == shift)
+    };
+
+    find(shift)
+        .or_else(|| find(false))
+        .map(|binding| binding.action)
+}
+
+/// One line per binding, keys padded into a column.
+pub fn help_text() -> String {
+    let keys: Vec<String> = KEY_BINDINGS.iter().map(key_name).collect();
+    let width = keys.iter().map(String::len).max().unwrap_or(0);
+
+    KEY_BINDINGS
+        .iter()
+        .zip(&keys)
+        .map(|(binding, key)| format!("  {:width$}  {}\n", key, binding.description))
+        .collect()
+}
+
+fn key_name(binding: &KeyBinding) -> String {
+    let key = match binding.key {
+        KeyCode::Slash if binding.shift => return "?".to_string(),
+        KeyCode::Period if binding.shift => return ">".to_string(),
+        KeyCode::Comma if binding.shift => return "<".to_string(),
+        KeyCode::BracketRight if binding.shift => return "}".to_string(),
+        KeyCode::BracketLeft if binding.shift => return "{".to_string(),
+        KeyCode::BracketRight => "]".to_string(),
+        KeyCode::BracketLeft => "[".to_string(),
+        KeyCode::Backslash => "\\".to_string(),
+        KeyCode::Equal => "=".to_string(),
+        KeyCode::Minus => "-".to_string(),
+        KeyCode::Period => ".".to_string(),
+        KeyCode::Comma => ",".to_string(),
+        key => {
+            let name = format!("{:?}", key);
+            name.strip_prefix("Key").unwrap_or(&name).to_string()
+        }
+    };
+
+    if binding.shift {
+        format!("Shift+{}", key)
+    } else {
+        key
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn every_binding_is_reachable() {
+        for (index, binding) in KEY_BINDINGS.iter().enumerate() {
+            let duplicate = KEY_BINDINGS[..index]
+                .iter()
+                .any(|other| other.key == binding.key && other.shift == binding.shift);
+            assert!(!duplicate, "{:?} is bound twice", binding);
+            assert_eq!(action_for(binding.key, binding.shift), Some(binding.action));
+        }
+
+        // Shift falls back to the plain binding
+        assert_eq!(action_for(KeyCode::KeyM, true), Some(Action::CycleMsaa));
+        assert_eq!(action_for(KeyCode::KeyZ, false), None);
+    }
+}
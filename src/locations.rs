@@ -0,0 +1,62 @@
+use glam::DVec2;
+
+use crate::fractal::{Fractal, FractalKind};
+use crate::view_state::ViewState;
+
+/// A well-known spot worth visiting, cycled with N.
+#[derive(Debug, Clone, Copy)]
+pub struct Location {
+    pub name: &'static str,
+    pub view: ViewState,
+}
+
+const fn mandelbrot(name: &'static str, x: f64, y: f64, height: f64, iters: u32) -> Location {
+    Location {
+        name,
+        view: ViewState {
+            center: DVec2::new(x, y),
+            height,
+            fractal: Fractal {
+                kind: FractalKind::Mandelbrot,
+                julia_c: DVec2::new(-0.8, 0.156),
+                multibrot_power: 3,
+            },
+            iter_override: Some(iters),
+            coloring: None,
+        },
+    }
+}
+
+const fn julia(name: &'static str, c: DVec2, iters: u32) -> Location {
+    Location {
+        name,
+        view: ViewState {
+            center: DVec2::ZERO,
+            height: 3.0,
+            fractal: Fractal {
+                kind: FractalKind::Julia,
+                julia_c: c,
+                multibrot_power: 3,
+            },
+            iter_override: Some(iters),
+            coloring: None,
+        },
+    }
+}
+
+pub const LOCATIONS: [Location; 8] = [
+    mandelbrot("Seahorse Valley", -0.7453, 0.1127, 0.01, 1000),
+    mandelbrot("Elephant Valley", 0.2925, 0.0149, 0.01, 1000),
+    mandelbrot("Triple Spiral Valley", -0.0881, 0.6547, 0.01, 1500),
+    mandelbrot("Scepter Valley", -1.25066, 0.02012, 0.0005, 1500),
+    mandelbrot("Period-3 minibrot", -1.7548776662466927, 0.0, 0.04, 1000),
+    mandelbrot("Misiurewicz point i", 0.0, 1.0, 0.002, 1000),
+    mandelbrot(
+        "Misiurewicz point -0.1011 + 0.9563i",
+        -0.10109636384562,
+        0.95628651080914,
+        0.002,
+        1500,
+    ),
+    julia("Douady rabbit", DVec2::new(-0.123, 0.745), 500),
+];
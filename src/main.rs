@@ -1,33 +1,62 @@
 #![feature(portable_simd)]
-#![feature(test)]
 #![allow(dead_code)]
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytemuck::Zeroable;
-use glam::{IVec2, UVec2};
+use glam::{DVec2, IVec2, UVec2};
 use pollster::FutureExt;
 use tokio::time::Instant;
 use wgpu::Limits;
 use winit::application::ApplicationHandler;
+use winit::dpi::PhysicalSize;
 use winit::event::{DeviceEvent, DeviceId};
-use winit::event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy};
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
 use winit::window::WindowId;
 
-use crate::event::{ElementState, Event, EventResult, MouseButtons};
+use crate::config::Config;
+use crate::env::is_debug_build;
+use crate::event::{
+    pinch_steps, wheel_steps, ElementState, Event, EventResult, MouseButtons, TouchPhase,
+};
+use crate::mandel_texture::TimingSummary;
 use crate::tiled_fractal_app::UserEvent;
+use crate::window_geometry::WindowGeometry;
 
 mod buffer_pool;
+mod config;
+mod demos;
+mod double_double;
 mod env;
 mod event;
+mod fractal;
+mod gradient;
+mod headless;
+mod iter_dump;
+mod julia_morph;
+mod key_bindings;
+mod locations;
 mod mandel_texture;
 mod mandelbrot_simd;
 mod math;
+mod navigation;
+mod palette;
+mod readback;
 mod render_pods;
+mod thumbnails;
 mod tiled_fractal_app;
+mod view_state;
+mod window_geometry;
+mod zoom_record;
 
 type UserEventType = UserEvent;
 
+const READBACK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+// tries to get a surface texture per frame, and the wait before the next frame once they fail
+const SURFACE_ACQUIRE_ATTEMPTS: u32 = 3;
+const SURFACE_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
 struct WindowContext<'window> {
     window: Arc<winit::window::Window>,
     surface: wgpu::Surface<'window>,
@@ -39,6 +68,7 @@ struct WindowContext<'window> {
 }
 
 struct AppState<'window> {
+    config: Config,
     window: Option<WindowContext<'window>>,
     fractal_app: Option<tiled_fractal_app::TiledFractalApp>,
 
@@ -49,42 +79,92 @@ struct AppState<'window> {
     is_redrawing: bool,
     is_resizing: bool,
     is_redraw_requested: bool,
+    is_minimized: bool,
+    // start of the last rendered frame, for the frame rate cap
+    last_frame: Option<std::time::Instant>,
 
     mouse_position: Option<UVec2>,
+    title: String,
+
+    // oldest input not yet on screen, and the input-to-present latencies when measured
+    pending_input: Option<Instant>,
+    input_latencies: Option<Vec<Duration>>,
 }
 
 pub struct RenderContext<'a> {
     pub device: &'a wgpu::Device,
     pub queue: &'a wgpu::Queue,
     pub view: &'a wgpu::TextureView,
+    // the size of `view`'s texture
+    pub view_size: UVec2,
     pub time: f64,
 }
 
 fn main() {
-    let event_loop: EventLoop<UserEventType> = EventLoop::<UserEventType>::with_user_event()
-        .build()
-        .unwrap();
+    // a bad flag or a failed headless run is reported, not a panic with a backtrace
+    if let Err(err) = run() {
+        eprintln!("Error: {:#}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> anyhow::Result<()> {
+    let config = Config::from_args()?;
+    if let Some(zoom_record) = &config.zoom_record {
+        return zoom_record::run(&config, zoom_record);
+    }
+    if let Some(julia_morph) = &config.julia_morph {
+        return julia_morph::run(&config, julia_morph);
+    }
+    if let Some(replay) = &config.replay {
+        if let Some(dir) = &replay.dir {
+            return navigation::run(&config, replay, dir);
+        }
+    }
+    if let Some(thumbnails) = &config.thumbnails {
+        return thumbnails::run(&config, thumbnails);
+    }
+
+    let event_loop: EventLoop<UserEventType> =
+        EventLoop::<UserEventType>::with_user_event().build()?;
     let mut app_state = AppState {
         window: None,
         fractal_app: None,
         is_redrawing: false,
         is_resizing: false,
         is_redraw_requested: true,
+        is_minimized: false,
+        last_frame: None,
         start: Instant::now(),
         mouse_position: None,
+        title: String::new(),
+        pending_input: None,
+        input_latencies: config.input_latency.then(Vec::new),
         event_loop_proxy: event_loop.create_proxy(),
+        config,
     };
-    event_loop.run_app(&mut app_state).unwrap();
+    event_loop.run_app(&mut app_state)?;
+
+    Ok(())
 }
 
-impl<'a> ApplicationHandler<UserEventType> for AppState<'_> {
+impl ApplicationHandler<UserEventType> for AppState<'_> {
     fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: winit::event::StartCause) {
         let _ = (event_loop, cause);
     }
 
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        let window_attr =
+        let mut window_attr =
             winit::window::Window::default_attributes().with_title("Mandelbrot explorer");
+        if let Some(geometry) = WindowGeometry::load() {
+            window_attr = geometry.apply(event_loop, window_attr);
+        }
+        let iter_dump = self.config.iter_dump.clone();
+        // the dump only covers a window of its own size
+        if let Some(iter_dump) = &iter_dump {
+            window_attr =
+                window_attr.with_inner_size(PhysicalSize::new(iter_dump.size.x, iter_dump.size.y));
+        }
         let window = event_loop.create_window(window_attr).unwrap();
         let window = Arc::new(window);
 
@@ -104,8 +184,6 @@ impl<'a> ApplicationHandler<UserEventType> for AppState<'_> {
             .block_on()
             .expect("No suitable GPU adapters found on the system.");
 
-        dbg!(adapter.get_info());
-
         // Make sure we use the texture resolution limits from the adapter, so we can support images the size of the surface.
         let limits = Limits {
             max_push_constant_size: 256,
@@ -113,7 +191,9 @@ impl<'a> ApplicationHandler<UserEventType> for AppState<'_> {
         }
         .using_resolution(adapter.limits());
 
-        let features = wgpu::Features::PUSH_CONSTANTS | wgpu::Features::TEXTURE_FORMAT_16BIT_NORM;
+        let features = wgpu::Features::PUSH_CONSTANTS
+            | wgpu::Features::TEXTURE_FORMAT_16BIT_NORM
+            | (adapter.features() & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
 
         let (device, queue) = adapter
             .request_device(
@@ -146,10 +226,15 @@ impl<'a> ApplicationHandler<UserEventType> for AppState<'_> {
         });
         let window_state = self.window.as_ref().unwrap();
 
-        self.fractal_app = Some(tiled_fractal_app::TiledFractalApp::new(
-            window_state,
-            self.event_loop_proxy.clone(),
-        ));
+        self.fractal_app = Some(
+            tiled_fractal_app::TiledFractalApp::new(
+                window_state,
+                &self.config,
+                iter_dump,
+                self.event_loop_proxy.clone(),
+            )
+            .unwrap(),
+        );
     }
 
     fn user_event(&mut self, event_loop: &ActiveEventLoop, event: UserEventType) {
@@ -185,20 +270,14 @@ impl<'a> ApplicationHandler<UserEventType> for AppState<'_> {
         let event_result = match event {
             winit::event::WindowEvent::Resized(_)
             | winit::event::WindowEvent::ScaleFactorChanged { .. } => {
-                let window_state = self.window.as_mut().unwrap();
-                let window_size = window_state.window.inner_size();
-
-                let window_size = UVec2::new(window_size.width.max(1), window_size.height.max(1));
-                window_state.surface_config.width = window_size.x;
-                window_state.surface_config.height = window_size.y;
-                window_state
-                    .surface
-                    .configure(&window_state.device, &window_state.surface_config);
-
-                self.fractal_app
-                    .as_mut()
-                    .unwrap()
-                    .update(Event::Resized(window_size))
+                if let winit::event::WindowEvent::ScaleFactorChanged { scale_factor, .. } = event {
+                    self.fractal_app
+                        .as_mut()
+                        .unwrap()
+                        .update(Event::ScaleFactorChanged(scale_factor));
+                }
+
+                self.configure_surface()
             }
 
             winit::event::WindowEvent::RedrawRequested => {
@@ -213,9 +292,22 @@ impl<'a> ApplicationHandler<UserEventType> for AppState<'_> {
                     .mouse_position
                     .as_mut()
                     .unwrap_or(&mut empty_mouse_position);
-                let event = process_window_event(event, mouse_position);
-
-                self.fractal_app.as_mut().unwrap().update(event)
+                let scale_factor = self.window.as_ref().unwrap().window.scale_factor();
+                let event = process_window_event(event, mouse_position, scale_factor);
+                let is_input = matches!(
+                    event,
+                    Event::MouseWheel(..)
+                        | Event::MouseMove { .. }
+                        | Event::MouseButton(..)
+                        | Event::Touch { .. }
+                        | Event::KeyboardInput(_)
+                );
+
+                let result = self.fractal_app.as_mut().unwrap().update(event);
+                if is_input && result == EventResult::Redraw && self.pending_input.is_none() {
+                    self.pending_input = Some(Instant::now());
+                }
+                result
             }
         };
 
@@ -239,7 +331,24 @@ impl<'a> ApplicationHandler<UserEventType> for AppState<'_> {
         let result = self.finish_resizing();
         self.process_event_result(event_loop, result);
 
-        self.redraw_if_needed();
+        self.update_title();
+        let next_frame = self.redraw_if_needed();
+        let readback_pending = self.poll_readbacks();
+
+        // keep waking up until the readback is done, nothing else would poll the device
+        let readback_poll =
+            readback_pending.then(|| std::time::Instant::now() + READBACK_POLL_INTERVAL);
+        // the title drops the label on the wake-up, nothing needs drawing until then
+        let label_expiry = self.fractal_app.as_ref().unwrap().coloring_label_expiry();
+        let wake_up = next_frame
+            .into_iter()
+            .chain(readback_poll)
+            .chain(label_expiry)
+            .min();
+        event_loop.set_control_flow(match wake_up {
+            Some(wake_up) => ControlFlow::WaitUntil(wake_up),
+            None => ControlFlow::Wait,
+        });
     }
 
     fn suspended(&mut self, event_loop: &ActiveEventLoop) {
@@ -248,6 +357,22 @@ impl<'a> ApplicationHandler<UserEventType> for AppState<'_> {
 
     fn exiting(&mut self, event_loop: &ActiveEventLoop) {
         let _ = event_loop;
+        if let Some(geometry) = self
+            .window
+            .as_ref()
+            .and_then(|window| WindowGeometry::of(&window.window))
+        {
+            if let Err(err) = geometry.save() {
+                println!("Failed to save the window geometry: {}", err);
+            }
+        }
+        if let Some(summary) = self
+            .input_latencies
+            .as_ref()
+            .and_then(|latencies| TimingSummary::from_durations(latencies))
+        {
+            println!("Input to present latency: {}", summary);
+        }
         self.window = None;
         self.fractal_app = None;
     }
@@ -257,7 +382,7 @@ impl<'a> ApplicationHandler<UserEventType> for AppState<'_> {
     }
 }
 
-impl<'a> AppState<'_> {
+impl AppState<'_> {
     fn process_event_result(&mut self, event_loop: &ActiveEventLoop, event_result: EventResult) {
         match event_result {
             EventResult::Continue => {}
@@ -272,8 +397,85 @@ impl<'a> AppState<'_> {
         }
     }
 
-    fn redraw_if_needed(&mut self) {
-        if self.is_redrawing {
+    fn update_title(&mut self) {
+        let title = self.fractal_app.as_ref().unwrap().title();
+        if title != self.title {
+            self.window.as_ref().unwrap().window.set_title(&title);
+            self.title = title;
+        }
+    }
+
+    fn poll_readbacks(&mut self) -> bool {
+        let window = self.window.as_ref().unwrap();
+        self.fractal_app
+            .as_mut()
+            .unwrap()
+            .poll_readbacks(&window.device, &window.queue)
+    }
+
+    /// Configures the surface for the window's current size and resizes the view along with it,
+    /// so no frame is drawn at one size into a surface of another.
+    fn configure_surface(&mut self) -> EventResult {
+        let window_state = self.window.as_mut().unwrap();
+        let window_size = window_state.window.inner_size();
+        let window_size = UVec2::new(window_size.width, window_size.height);
+
+        // a minimized window reports a zero size, which the surface can't be configured with
+        self.is_minimized = window_size.min_element() == 0;
+        if !self.is_minimized {
+            window_state.surface_config.width = window_size.x;
+            window_state.surface_config.height = window_size.y;
+            // another monitor may not offer the format the pipelines were built for
+            let formats = window_state
+                .surface
+                .get_capabilities(&window_state.adapter)
+                .formats;
+            if !formats.is_empty() && !formats.contains(&window_state.surface_config.format) {
+                let format = formats[0];
+                println!("Surface format changed to {:?}", format);
+                window_state.surface_config.format = format;
+                window_state.surface_config.view_formats = vec![format.add_srgb_suffix()];
+                self.fractal_app
+                    .as_mut()
+                    .unwrap()
+                    .set_surface_format(window_state);
+            }
+            window_state
+                .surface
+                .configure(&window_state.device, &window_state.surface_config);
+        }
+
+        self.fractal_app
+            .as_mut()
+            .unwrap()
+            .update(Event::Resized(window_size))
+    }
+
+    // an outdated or lost surface is reconfigured for the window as it is now, mid-resize that can
+    // take a few tries, `None` once they're used up or the window got minimized
+    fn acquire_surface_texture(&mut self) -> Option<wgpu::SurfaceTexture> {
+        for _ in 0..SURFACE_ACQUIRE_ATTEMPTS {
+            match self.window.as_ref().unwrap().surface.get_current_texture() {
+                Ok(surface_texture) => return Some(surface_texture),
+                Err(wgpu::SurfaceError::Timeout) => {}
+                Err(_) => {
+                    self.configure_surface();
+                    if self.is_minimized {
+                        return None;
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Renders a requested frame, returning when to try again if the frame rate cap holds it back
+    /// or an animation needs the next one.
+    fn redraw_if_needed(&mut self) -> Option<std::time::Instant> {
+        // validation errors still panic through the uncaptured error handler,
+        // the scope only pins them to a frame and costs a blocking pop, so keep it to debug builds
+        if self.is_redrawing && is_debug_build() {
             let error = self
                 .window
                 .as_ref()
@@ -287,27 +489,38 @@ impl<'a> AppState<'_> {
         }
         self.is_redrawing = false;
 
-        if !self.is_redraw_requested {
-            return;
+        // the request is kept and served once the window is restored
+        if !self.is_redraw_requested || self.is_minimized {
+            return None;
+        }
+
+        // the request is kept, events arriving meanwhile are still applied to the deferred frame
+        let frame_interval = self.fractal_app.as_ref().unwrap().frame_interval();
+        if let (Some(frame_interval), Some(last_frame)) = (frame_interval, self.last_frame) {
+            let next_frame = last_frame + frame_interval;
+            if std::time::Instant::now() < next_frame {
+                return Some(next_frame);
+            }
+        }
+        // the last frame stays on screen while the tiles of a large zoom come in, the request is kept
+        if let Some(hold_until) = self.fractal_app.as_ref().unwrap().frame_hold() {
+            return Some(hold_until);
         }
+        self.last_frame = Some(std::time::Instant::now());
+
+        // the request is kept for the next try, the window may still be settling
+        let Some(surface_texture) = self.acquire_surface_texture() else {
+            if self.is_minimized {
+                return None;
+            }
+            println!("Surface unavailable, skipping the frame");
+            return Some(std::time::Instant::now() + SURFACE_RETRY_INTERVAL);
+        };
 
         self.is_redraw_requested = false;
         self.is_redrawing = true;
 
         let window_state = self.window.as_mut().unwrap();
-
-        let surface_texture = match window_state.surface.get_current_texture() {
-            Ok(frame) => frame,
-            Err(_) => {
-                window_state
-                    .surface
-                    .configure(&window_state.device, &window_state.surface_config);
-                window_state
-                    .surface
-                    .get_current_texture()
-                    .expect("Failed to acquire next surface texture.")
-            }
-        };
         let surface_texture_view =
             surface_texture
                 .texture
@@ -316,18 +529,38 @@ impl<'a> AppState<'_> {
                     ..wgpu::TextureViewDescriptor::default()
                 });
 
-        window_state
-            .device
-            .push_error_scope(wgpu::ErrorFilter::Validation);
+        if is_debug_build() {
+            window_state
+                .device
+                .push_error_scope(wgpu::ErrorFilter::Validation);
+        }
 
         self.fractal_app.as_mut().unwrap().render(&RenderContext {
             device: &window_state.device,
             queue: &window_state.queue,
             view: &surface_texture_view,
+            view_size: UVec2::new(
+                window_state.surface_config.width,
+                window_state.surface_config.height,
+            ),
             time: self.start.elapsed().as_secs_f64(),
         });
 
         surface_texture.present();
+
+        if let (Some(input_time), Some(input_latencies)) =
+            (self.pending_input.take(), &mut self.input_latencies)
+        {
+            input_latencies.push(input_time.elapsed());
+        }
+
+        // nothing else would wake the loop for the next fade or held zoom step, presenting paces it
+        if self.fractal_app.as_ref().unwrap().is_animating() {
+            self.is_redraw_requested = true;
+            return Some(std::time::Instant::now());
+        }
+
+        None
     }
 
     fn finish_resizing(&mut self) -> EventResult {
@@ -352,6 +585,7 @@ impl<'a> AppState<'_> {
 fn process_window_event<UserEvent>(
     event: winit::event::WindowEvent,
     mouse_position: &mut UVec2,
+    scale_factor: f64,
 ) -> Event<UserEvent> {
     match event {
         winit::event::WindowEvent::Resized(size) => {
@@ -377,29 +611,32 @@ fn process_window_event<UserEvent>(
         winit::event::WindowEvent::MouseInput { state, button, .. } => Event::MouseButton(
             MouseButtons::from(button),
             ElementState::from(state),
-            mouse_position.clone(),
+            *mouse_position,
         ),
         winit::event::WindowEvent::MouseWheel {
             delta,
             phase: _phase,
             ..
-        } => match delta {
-            winit::event::MouseScrollDelta::LineDelta(_l1, l2) => {
-                Event::MouseWheel(mouse_position.clone(), l2)
-            }
-            winit::event::MouseScrollDelta::PixelDelta(_pix) => Event::Unknown,
-        },
+        } => Event::MouseWheel(*mouse_position, wheel_steps(delta, scale_factor)),
         winit::event::WindowEvent::PinchGesture {
             device_id: _device_id,
             delta,
             phase: _phase,
         } => {
-            // Event::TouchpadMagnify(mouse_position.clone(), delta as f32)
-            Event::MouseWheel(mouse_position.clone(), -50.0 * delta as f32)
+            // Event::TouchpadMagnify(*mouse_position, delta as f32)
+            Event::MouseWheel(*mouse_position, pinch_steps(delta))
         }
+        winit::event::WindowEvent::Touch(touch) => Event::Touch {
+            id: touch.id,
+            phase: TouchPhase::from(touch.phase),
+            position: DVec2::new(touch.location.x, touch.location.y),
+        },
         winit::event::WindowEvent::CloseRequested => Event::WindowClose,
         winit::event::WindowEvent::Moved(_position) => Event::Unknown,
         winit::event::WindowEvent::KeyboardInput { event, .. } => Event::KeyboardInput(event),
+        winit::event::WindowEvent::ModifiersChanged(modifiers) => {
+            Event::ModifiersChanged(modifiers.state())
+        }
         _ => Event::Unknown,
     }
 }
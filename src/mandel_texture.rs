@@ -1,10 +1,15 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::mem::{size_of, swap};
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use anyhow::anyhow;
 use bytemuck::Zeroable;
-use glam::{DVec2, Mat4, UVec2, Vec2, Vec3};
+use glam::{DVec2, Mat4, UVec2, Vec2, Vec3, Vec4};
 use parking_lot::Mutex;
 use tokio::runtime::Runtime;
 use tokio::sync::Semaphore;
@@ -12,13 +17,205 @@ use tokio::task::JoinHandle;
 use wgpu::util::DeviceExt;
 
 use crate::buffer_pool::BufferPool;
-use crate::mandelbrot_simd::{mandelbrot_simd, Pixel, MAX_ITER};
+use crate::fractal::{is_rect_interior, Fractal, FractalKind};
+use crate::headless::render_cancellable;
+use crate::iter_dump::IterDump;
+use crate::mandelbrot_simd::{
+    fill_interior, is_beyond_precision, mandelbrot_simd, IterStorage, Kernel, KernelParams, Pixel,
+    SamplePattern, TileStatus, DEFAULT_ITER_CAP, MIN_ITER, SIMD_LANE_COUNT,
+};
 use crate::math::{DRect, URect};
+use crate::palette::{PaletteKind, PALETTE_SIZE};
+use crate::readback::{PendingReadback, ScreenshotRequest, TexelProbe};
 use crate::render_pods::{PushConst, ScreenRect};
 use crate::RenderContext;
 
 const TILE_SIZE: u32 = 128;
-const TEXTURE_SIZE: u32 = 4 * 1024;
+// the pieces a tile's compute is split into, see `MandelTexture::set_sub_tile_size`
+pub const MIN_SUB_TILE_SIZE: u32 = 32;
+pub const MAX_SUB_TILE_SIZE: u32 = TILE_SIZE;
+// upper bound for `--max-in-flight`, far past the point where tiles only contend for cores
+pub const MAX_IN_FLIGHT_TILES: usize = 256;
+// iteration multiplier applied once the view has been idle for a while
+const QUALITY_BOOST_ITERS: u32 = 4;
+// upper bound for `--interior-boost`, see `MandelTexture::set_interior_boost`
+pub const MAX_INTERIOR_BOOST: u32 = 16;
+// share of interior texels that has a tile computed again with the boosted count
+const INTERIOR_BOOST_FRACTION: f64 = 0.5;
+// highest `--detail` level, each level roughly doubles the compute
+pub const MAX_DETAIL: u32 = 4;
+// frame height below which detail goes to iterations before supersampling
+const DEEP_DETAIL_HEIGHT: f64 = 1e-4;
+pub const TEXTURE_SIZE: UVec2 = UVec2::splat(4 * 1024);
+// smallest side that still leaves a 64 pixel level at the bottom of the mip chain
+const MIN_TEXTURE_SIZE: u32 = 1024;
+// compute texels per window pixel, below 1 trades sharpness for speed, above 1 supersamples
+pub const MIN_RENDER_SCALE: f64 = 0.5;
+pub const MAX_RENDER_SCALE: f64 = 2.0;
+// every 8th texel in both directions feeds the uniform view check
+const SAMPLE_STRIDE: usize = 8;
+// share of the samples within one iteration of the most common count that makes a view uniform
+const UNIFORM_FRACTION: f64 = 0.99;
+// time after the last update before tiles in view that never got their data are computed again
+const TILE_WATCHDOG_INTERVAL: Duration = Duration::from_secs(2);
+// 4096 down to 256, enough for a 16x zoom out in one blit
+const MIP_LEVEL_COUNT: u32 = 5;
+// a zoom in beyond this in one blit drops the previous frame rather than showing giant texels
+pub const DEFAULT_MAX_BLIT_ZOOM: f64 = 8.0;
+// longest the last frame stays on screen after such a zoom, whatever tiles are in by then show
+const FRAME_HOLD: Duration = Duration::from_millis(150);
+// textures in the blit ring, two ping-pong
+pub const MIN_TEXTURE_COUNT: usize = 2;
+pub const MAX_TEXTURE_COUNT: usize = 4;
+pub const DEFAULT_TEXTURE_COUNT: usize = MIN_TEXTURE_COUNT;
+// a palette switch cross-fades this long unless `--palette-fade-ms 0` asks for an instant swap
+pub const DEFAULT_PALETTE_FADE: Duration = Duration::from_millis(300);
+// black like the palette's dark end the escapes fade in from
+pub const DEFAULT_INTERIOR_COLOR: Vec4 = Vec4::new(0.0, 0.0, 0.0, 1.0);
+// iterations of one pass through the palette, and the range `--palette-period` takes
+pub const DEFAULT_PALETTE_PERIOD: u32 = 768;
+pub const MIN_PALETTE_PERIOD: u32 = 12;
+pub const MAX_PALETTE_PERIOD: u32 = 1 << 16;
+// must match LEGEND_TICKS in screen_shader.wgsl
+pub const LEGEND_TICKS: u32 = 6;
+
+const ORBIT_VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 1] = [wgpu::VertexAttribute {
+    format: wgpu::VertexFormat::Float32x2,
+    offset: 0,
+    shader_location: 0,
+}];
+const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 2] = [
+    wgpu::VertexAttribute {
+        format: wgpu::VertexFormat::Float32x4,
+        offset: 0,
+        shader_location: 0,
+    },
+    wgpu::VertexAttribute {
+        format: wgpu::VertexFormat::Float32x2,
+        offset: 4 * 4,
+        shader_location: 1,
+    },
+];
+
+/// How the interior, the points that never escaped, is shaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InteriorColoring {
+    /// Plain black.
+    #[default]
+    Solid = 0,
+    /// Dimmed palette color by the angle of the final `z`.
+    Phase = 1,
+}
+
+impl InteriorColoring {
+    pub fn next(self) -> Self {
+        match self {
+            InteriorColoring::Solid => InteriorColoring::Phase,
+            InteriorColoring::Phase => InteriorColoring::Solid,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            InteriorColoring::Solid => "solid",
+            InteriorColoring::Phase => "phase",
+        }
+    }
+}
+
+impl FromStr for InteriorColoring {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "solid" => Ok(InteriorColoring::Solid),
+            "phase" => Ok(InteriorColoring::Phase),
+            _ => Err(anyhow!("Unknown interior coloring: {}", s)),
+        }
+    }
+}
+
+/// How the escapes are shaded, the screen shader picks it per frame so switching needs no recompute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColoringMode {
+    /// One palette step per iteration, the escape time bands.
+    #[default]
+    Bands = 0,
+    /// Blended between the bands by how far past the escape radius `z` went.
+    Smooth = 1,
+}
+
+impl ColoringMode {
+    pub fn next(self) -> Self {
+        match self {
+            ColoringMode::Bands => ColoringMode::Smooth,
+            ColoringMode::Smooth => ColoringMode::Bands,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColoringMode::Bands => "bands",
+            ColoringMode::Smooth => "smooth",
+        }
+    }
+}
+
+impl FromStr for ColoringMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bands" => Ok(ColoringMode::Bands),
+            "smooth" => Ok(ColoringMode::Smooth),
+            _ => Err(anyhow!("Unknown coloring mode: {}", s)),
+        }
+    }
+}
+
+/// How the mip chain drawn when zoomed out averages escapes. Only matters where the stored
+/// values aren't linear in what they stand for: counts with `IterStorage::Log`, and the escape
+/// magnitudes smooth coloring reads, which are stored as log2 |z|². Plain counts blend the same
+/// either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MipBlend {
+    /// Averages the stored values, for log data that's close to a geometric mean.
+    #[default]
+    Perceptual = 0,
+    /// Averages the iterations and |z|² the values decode to.
+    Linear = 1,
+}
+
+impl MipBlend {
+    pub fn name(self) -> &'static str {
+        match self {
+            MipBlend::Perceptual => "perceptual",
+            MipBlend::Linear => "linear",
+        }
+    }
+}
+
+impl FromStr for MipBlend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "perceptual" => Ok(MipBlend::Perceptual),
+            "linear" => Ok(MipBlend::Linear),
+            _ => Err(anyhow!("Unknown mip blend: {}", s)),
+        }
+    }
+}
+
+// called with the tile's index once it has something to upload
+#[derive(Clone)]
+struct TileReadyCallback(Arc<dyn Fn(usize) + Send + Sync>);
+
+impl std::fmt::Debug for TileReadyCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TileReadyCallback")
+    }
+}
 
 #[derive(Debug, Default)]
 pub enum TileState {
@@ -27,10 +224,35 @@ pub enum TileState {
     Computing {
         task_handle: JoinHandle<()>,
         cancel_token: Arc<AtomicBool>,
+        // sub-tiles done ahead of the whole tile, uploaded as they come in
+        parts: Vec<(URect, Vec<Pixel>)>,
     },
     WaitForUpload {
         buffer: Arc<Mutex<Vec<u8>>>,
+        // `cell_gradients` of the buffer, worked out with the compute
+        gradients: Vec<u32>,
     },
+    // the compute returned an error or panicked, recomputed with the next update
+    Failed,
+}
+
+/// What the tile workers are doing right now, for the compute stats line.
+#[derive(Debug, Clone, Copy)]
+pub struct ComputeStats {
+    pub computing: usize,
+    // waiting for a worker
+    pub queued: usize,
+    pub buffers_taken: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TimingSummary {
+    pub count: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+    pub total: Duration,
 }
 
 #[derive(Debug)]
@@ -38,43 +260,450 @@ pub struct Tile {
     pub index: usize,
     pub tex_rect: URect,
     pub state: Arc<Mutex<TileState>>,
+    // strided counts of the last upload, empty while the tile is being recomputed
+    pub samples: Vec<u16>,
+    // count differences per `SAMPLE_STRIDE` cell of the last upload, empty alongside `samples`
+    pub gradients: Vec<u32>,
 }
 
+// Iteration texture with a mip chain, lower levels are used when the blit shrinks it.
+// Holds raw escape counts only, all coloring happens in screen_shader.wgsl so color
+// settings never need tiles recomputed.
 #[derive(Debug)]
-pub struct MandelTexture {
-    texture1: wgpu::Texture,
-    texture1_view: wgpu::TextureView,
-    bind_group1: wgpu::BindGroup,
+struct TileTexture {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    // single-level views, the first one is the blit render target
+    mip_views: Vec<wgpu::TextureView>,
+    // `mip_bind_groups[i]` samples level `i` to generate level `i + 1`
+    mip_bind_groups: Vec<wgpu::BindGroup>,
+    mips_dirty: bool,
+}
+
+// Both palettes bound next to each iteration texture, the previous one only matters while fading.
+struct PaletteViews {
+    current: wgpu::TextureView,
+    previous: wgpu::TextureView,
+}
+
+// The window's own pixels computed directly on the CPU, requested with R for a still image.
+// Drawn instead of the tile texture while the view and the kernel settings stay the same.
+#[derive(Debug)]
+struct StillRender {
+    frame_rect: DRect,
+    window_size: UVec2,
+    params: KernelParams,
+    // the width padded to whole SIMD lanes, the extra columns are never drawn
+    size: UVec2,
+    cancel_token: Arc<AtomicBool>,
+    // filled by the compute task, taken by the upload
+    pixels: Arc<Mutex<Option<Vec<Pixel>>>>,
+    texture: Option<StillTexture>,
+    // where the upload saves the pixels to, see `IterDump`
+    dump_path: Option<PathBuf>,
+}
+
+// Points of the plane drawn as a line strip over the fractal, an orbit or a measurement.
+#[derive(Debug)]
+struct Polyline {
+    // the clicked point, the vertices are relative to it to keep f32 precision when deep
+    origin: DVec2,
+    points: Vec<DVec2>,
+    // made from `points` before the next draw
+    vertex_buf: Option<wgpu::Buffer>,
+}
 
-    texture2: wgpu::Texture,
-    texture2_view: wgpu::TextureView,
-    bind_group2: wgpu::BindGroup,
+#[derive(Debug)]
+struct StillTexture {
+    _texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    screen_rect_buf: wgpu::Buffer,
+}
+
+// everything drawn into the window, all sharing the window's sample count
+#[derive(Debug)]
+struct SurfacePipelines {
+    screen: wgpu::RenderPipeline,
+    boundary: wgpu::RenderPipeline,
+    legend: wgpu::RenderPipeline,
+    crosshair: wgpu::RenderPipeline,
+    orbit: wgpu::RenderPipeline,
+    measure: wgpu::RenderPipeline,
+}
+
+#[derive(Debug)]
+pub struct MandelTexture {
+    // the blit ring, newest first: the first is drawn and written by the tiles,
+    // each blit reads it into the last and rotates that to the front
+    textures: Vec<TileTexture>,
+    // ring length asked for, the ring is rebuilt to it before the next render
+    texture_count: usize,
 
     screen_rect_buf: wgpu::Buffer,
     bind_group_layout: wgpu::BindGroupLayout,
+    mip_bind_group_layout: wgpu::BindGroupLayout,
     sampler: wgpu::Sampler,
+    palette_texture: wgpu::Texture,
+    // the palette faded out after a switch, binding 3 of the screen shader
+    previous_palette_texture: wgpu::Texture,
+    palette: PaletteKind,
+    gradient: Option<PathBuf>,
+    // colors of a palette switch, uploaded with the next frame
+    pending_palette: Option<Vec<[u8; 4]>>,
+    // what the two palette textures hold, a switch mid-fade starts from their blend
+    palette_colors: Vec<[u8; 4]>,
+    previous_palette_colors: Vec<[u8; 4]>,
+    // zero swaps palettes instantly
+    palette_fade: Duration,
+    // `RenderContext::time` the running fade started at
+    palette_fade_start: Option<f64>,
+    palette_mix: f32,
 
     blit_pipeline: wgpu::RenderPipeline,
-    screen_pipeline: wgpu::RenderPipeline,
+    mip_pipeline: wgpu::RenderPipeline,
+    surface_pipelines: SurfacePipelines,
+    screen_pipeline_layout: wgpu::PipelineLayout,
+    screen_shader: wgpu::ShaderModule,
+    surface_format: wgpu::TextureFormat,
+
+    // the format and sample count `surface_pipelines` were built for
+    surface_pipelines_for: (wgpu::TextureFormat, u32),
+    msaa_samples: u32,
+    // the multisampled target and the size it was made for, which must match the texture drawn into
+    msaa_view: Option<(UVec2, wgpu::TextureView)>,
+    show_legend: bool,
+    show_boundary: bool,
+    boundary_color: Vec4,
+    show_crosshair: bool,
+    crosshair_color: Vec4,
+    orbit: Option<Polyline>,
+    // the line between two points picked for measuring
+    measure: Option<Polyline>,
+    interior_coloring: InteriorColoring,
+    // solid interior color, and iterations added to the escapes' palette lookup
+    interior_color: Vec4,
+    color_offset: f32,
+    coloring_mode: ColoringMode,
+    band_aa: bool,
+    palette_period: u32,
+
+    screenshot_request: Option<ScreenshotRequest>,
+    texture_dump_request: Option<PathBuf>,
+    palette_export_request: Option<PathBuf>,
+    pending_readbacks: Vec<PendingReadback>,
+    // hover readout: the fractal point to sample, whether it needs a fresh copy,
+    // the copy in flight and the last count read back
+    probe_point: Option<DVec2>,
+    probe_requested: bool,
+    pending_probe: Option<TexelProbe>,
+    probed_count: Option<u16>,
 
     pub(crate) buf_pool: BufferPool,
+    tile_timings: Option<Arc<Mutex<Vec<Duration>>>>,
 
     window_size: UVec2,
-    texture_size: u32,
+    // side of the pieces a tile is computed in, `TILE_SIZE` computes it in one go
+    sub_tile_size: u32,
+    // iteration multiplier for tiles that come back mostly interior, 1 never computes them again
+    interior_boost: u32,
+    // `--texture-size` at a render scale of 1, `texture_size` follows the render scale
+    base_texture_size: UVec2,
+    texture_size: UVec2,
+    render_scale: f64,
+    max_texture_dimension: u32,
 
     runtime: Runtime,
     semaphore: Arc<Semaphore>,
+    compute_permits: usize,
+    // tiles that ran out of their time budget are finished here
+    slow_semaphore: Arc<Semaphore>,
+    // from the last update, for the watchdog's reschedules
+    tile_ready_callback: Option<TileReadyCallback>,
+    // the last update or watchdog pass, see `watch_tiles`
+    watchdog_check: Instant,
+    tile_time_budget: Option<Duration>,
+    max_blit_zoom: f64,
+    // set by a zoom past `max_blit_zoom`, see `frame_hold`
+    hold_until: Option<Instant>,
+    supersample: Option<SamplePattern>,
+    // pattern the idle boost supersamples with, single samples while the view moves
+    settle_supersample: Option<SamplePattern>,
+    // one quality knob split into iterations and supersampling by zoom, 0 adds nothing
+    detail: u32,
     tiles: Vec<Tile>,
 
     frame_rect: DRect,
     fractal_rect: DRect,
     fractal_rect_prev: DRect,
+    // debug: draws the texture before the last blit, at the rect it covered
+    show_previous_texture: bool,
+    previous_texture_rect: DRect,
     frame_changed: bool,
+
+    fractal: Fractal,
+    iter_override: Option<u32>,
+    // count pinned while zooming, ahead of the override, detail, boost and warmup
+    iter_lock: Option<u32>,
+    iter_cap: u32,
+    iter_storage: IterStorage,
+    mip_blend: MipBlend,
+    kernel: Kernel,
+    quality_boost: bool,
+    // iteration limit of the startup preview pass, cleared for the full pass
+    warmup_iters: Option<u32>,
+    detect_glitches: bool,
+    // every visible tile is done and they are practically one color
+    uniform_view: bool,
+    still: Option<StillRender>,
+}
+
+/// Checks `texture_size` against what the tiles, the mip chain and the device can handle.
+fn validate_texture_size(texture_size: UVec2, max_dimension: u32) -> anyhow::Result<()> {
+    if texture_size.min_element() < MIN_TEXTURE_SIZE {
+        return Err(anyhow!(
+            "Texture size {}x{} is too small, both sides must be at least {} for the mip chain",
+            texture_size.x,
+            texture_size.y,
+            MIN_TEXTURE_SIZE
+        ));
+    }
+    if texture_size % TILE_SIZE != UVec2::ZERO {
+        return Err(anyhow!(
+            "Texture size {}x{} must be a multiple of the {} pixel tile size",
+            texture_size.x,
+            texture_size.y,
+            TILE_SIZE
+        ));
+    }
+    if texture_size.max_element() > max_dimension {
+        return Err(anyhow!(
+            "Texture size {}x{} exceeds the device limit of {}",
+            texture_size.x,
+            texture_size.y,
+            max_dimension
+        ));
+    }
+
+    Ok(())
+}
+
+/// Texture size for `render_scale`, keeping the margin around the window the same in fractal space.
+fn scaled_texture_size(base: UVec2, render_scale: f64, max_dimension: u32) -> UVec2 {
+    let tiles = (base.as_dvec2() * render_scale / TILE_SIZE as f64).ceil();
+    let max_size = (max_dimension / TILE_SIZE * TILE_SIZE).max(MIN_TEXTURE_SIZE);
+
+    (tiles.as_uvec2() * TILE_SIZE).clamp(UVec2::splat(MIN_TEXTURE_SIZE), UVec2::splat(max_size))
+}
+
+/// Tightly packed rows of `size` pixels, the row pitch computed in u64 so a wide texture can't wrap it.
+fn pixel_copy_layout(size: UVec2) -> wgpu::TexelCopyBufferLayout {
+    let bytes_per_row = size_of::<Pixel>() as u64 * size.x as u64;
+    let bytes_per_row = u32::try_from(bytes_per_row)
+        .unwrap_or_else(|_| panic!("{} pixel rows exceed u32 byte counts", size.x));
+
+    wgpu::TexelCopyBufferLayout {
+        offset: 0,
+        bytes_per_row: Some(bytes_per_row),
+        rows_per_image: Some(size.y),
+    }
+}
+
+/// Where `point` falls in a `texture_size` texture covering `fractal_rect`,
+/// texture row 0 is the bottom of the fractal rect.
+fn plane_to_texel(fractal_rect: DRect, texture_size: UVec2, point: DVec2) -> DVec2 {
+    (point - fractal_rect.pos) / fractal_rect.size * DVec2::from(texture_size)
+}
+
+fn texel_rect(fractal_rect: DRect, texture_size: UVec2, rect: DRect) -> DRect {
+    let pos = plane_to_texel(fractal_rect, texture_size, rect.pos);
+    let size = rect.size / fractal_rect.size * DVec2::from(texture_size);
+
+    DRect::from_pos_size(pos, size)
+}
+
+fn create_tiles(texture_size: UVec2) -> Vec<Tile> {
+    let tile_count = texture_size / TILE_SIZE;
+    let mut tiles = Vec::with_capacity(tile_count.x as usize * tile_count.y as usize);
+    for i in 0..tile_count.x {
+        for j in 0..tile_count.y {
+            let index = tiles.len();
+            let rect = URect {
+                pos: UVec2::new(i * TILE_SIZE, j * TILE_SIZE),
+                size: UVec2::new(TILE_SIZE, TILE_SIZE),
+            };
+            tiles.push(Tile {
+                index,
+                tex_rect: rect,
+                state: Arc::new(Mutex::new(TileState::Idle)),
+                samples: Vec::new(),
+                gradients: Vec::new(),
+            });
+        }
+    }
+
+    tiles
+}
+
+// every `SAMPLE_STRIDE`th count of a tile in both directions, glitches left out
+fn sample_counts(pixels: &[Pixel], width: u32) -> Vec<u16> {
+    pixels
+        .chunks(width as usize)
+        .step_by(SAMPLE_STRIDE)
+        .flat_map(|row| row.iter().step_by(SAMPLE_STRIDE))
+        .map(|pixel| pixel.count())
+        .filter(|&count| count != Pixel::GLITCH.count())
+        .collect()
+}
+
+// sum of the count differences to the right and upper neighbor of every texel,
+// per `SAMPLE_STRIDE` cell of a tile, rows of cells from the bottom, glitches left out
+fn cell_gradients(pixels: &[Pixel], width: u32) -> Vec<u32> {
+    let width = width as usize;
+    let height = pixels.len() / width;
+    let cells_x = width.div_ceil(SAMPLE_STRIDE);
+    let mut gradients = vec![0u32; cells_x * height.div_ceil(SAMPLE_STRIDE)];
+
+    let glitch = Pixel::GLITCH.count();
+    for y in 0..height {
+        for x in 0..width {
+            let count = pixels[y * width + x].count();
+            if count == glitch {
+                continue;
+            }
+            let neighbors = [
+                (x + 1 < width).then(|| pixels[y * width + x + 1]),
+                (y + 1 < height).then(|| pixels[(y + 1) * width + x]),
+            ];
+            let gradient: u32 = neighbors
+                .into_iter()
+                .flatten()
+                .map(|neighbor| neighbor.count())
+                .filter(|&neighbor| neighbor != glitch)
+                .map(|neighbor| neighbor.abs_diff(count) as u32)
+                .sum();
+            gradients[y / SAMPLE_STRIDE * cells_x + x / SAMPLE_STRIDE] += gradient;
+        }
+    }
+
+    gradients
+}
+
+// the cell with the most gradient in the square of `radius` cells around it, `None` without any
+fn busiest_cell(gradients: &[u32], width: usize, radius: usize) -> Option<UVec2> {
+    let height = gradients.len() / width;
+    // summed-area table with a zero row and column in front
+    let mut sums = vec![0u64; (width + 1) * (height + 1)];
+    for y in 0..height {
+        for x in 0..width {
+            sums[(y + 1) * (width + 1) + x + 1] = gradients[y * width + x] as u64
+                + sums[y * (width + 1) + x + 1]
+                + sums[(y + 1) * (width + 1) + x]
+                - sums[y * (width + 1) + x];
+        }
+    }
+
+    let window_sum = |x: usize, y: usize| {
+        let (x0, y0) = (x.saturating_sub(radius), y.saturating_sub(radius));
+        let (x1, y1) = ((x + radius + 1).min(width), (y + radius + 1).min(height));
+        sums[y1 * (width + 1) + x1] + sums[y0 * (width + 1) + x0]
+            - sums[y0 * (width + 1) + x1]
+            - sums[y1 * (width + 1) + x0]
+    };
+
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| (window_sum(x, y), x, y))
+        .filter(|&(sum, _, _)| sum > 0)
+        .max_by_key(|&(sum, _, _)| sum)
+        .map(|(_, x, y)| UVec2::new(x as u32, y as u32))
+}
+
+// turns a panic inside the kernel into an error, so the tile isn't left computing forever
+fn catch_panic<F>(compute: F) -> anyhow::Result<TileStatus>
+where
+    F: FnOnce() -> anyhow::Result<TileStatus>,
+{
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(compute))
+        .unwrap_or_else(|_| Err(anyhow!("Tile compute panicked")))
+}
+
+// share of `pixels` that never escaped
+fn interior_fraction(pixels: &[Pixel]) -> f64 {
+    let interior = pixels.iter().filter(|pixel| pixel.count() == 0).count();
+    interior as f64 / pixels.len() as f64
+}
+
+// the pieces of `tex_rect` computed one after the other, row by row
+fn sub_rects(tex_rect: URect, sub_tile_size: u32) -> impl Iterator<Item = URect> {
+    let count = tex_rect.size / sub_tile_size;
+    (0..count.y).flat_map(move |j| {
+        (0..count.x).map(move |i| {
+            URect::from_pos_size(
+                tex_rect.pos + UVec2::new(i, j) * sub_tile_size,
+                UVec2::splat(sub_tile_size),
+            )
+        })
+    })
+}
+
+// copies the pixels of `part` into the buffer of the whole `tex_rect`
+fn copy_part(tex_rect: URect, part: URect, pixels: &[Pixel], buffer: &mut [Pixel]) {
+    let offset = part.pos - tex_rect.pos;
+    for (row, part_row) in pixels.chunks_exact(part.size.x as usize).enumerate() {
+        let start = (offset.y as usize + row) * tex_rect.size.x as usize + offset.x as usize;
+        buffer[start..start + part_row.len()].copy_from_slice(part_row);
+    }
+}
+
+// hands a finished sub-tile to the upload, false once the tile was cancelled
+fn publish_part(tile_state: &Mutex<TileState>, part: URect, pixels: Vec<Pixel>) -> bool {
+    match &mut *tile_state.lock() {
+        TileState::Computing { parts, .. } => {
+            parts.push((part, pixels));
+            true
+        }
+        _ => false,
+    }
+}
+
+// moves a tile out of `Computing` once its task is done, true if the app should hear about it
+fn finish_tile(
+    tile_state: &Mutex<TileState>,
+    tile_index: usize,
+    status: anyhow::Result<TileStatus>,
+    buffer: Arc<Mutex<Vec<u8>>>,
+    gradients: Vec<u32>,
+) -> bool {
+    let mut tile_state = tile_state.lock();
+    match status {
+        Ok(TileStatus::Complete) => {
+            *tile_state = TileState::WaitForUpload { buffer, gradients };
+            true
+        }
+        // a newer update owns the tile, or the budget retry is still to come
+        Ok(TileStatus::Cancelled | TileStatus::TimedOut) => false,
+        Err(err) => {
+            println!("Tile {} failed: {}", tile_index, err);
+            *tile_state = TileState::Failed;
+            true
+        }
+    }
+}
+
+/// Iteration doublings and whether to supersample for `detail` at the zoom of `frame_rect`.
+/// Shallow views spend the first level on smooth edges, deep ones the last.
+pub(crate) fn detail_split(detail: u32, frame_rect: DRect) -> (u32, bool) {
+    let detail = detail.min(MAX_DETAIL);
+    if frame_rect.size.y < DEEP_DETAIL_HEIGHT {
+        (detail.min(MAX_DETAIL - 1), detail == MAX_DETAIL)
+    } else {
+        (detail.saturating_sub(1), detail >= 1)
+    }
 }
 
-fn calc_max_iters(fractal_rect: DRect) -> u32 {
+pub(crate) fn calc_max_iters(fractal_rect: DRect, iter_cap: u32) -> u32 {
     let max_iterations =
-        (1000 + ((1.0 / fractal_rect.size.length_squared()).log2() * 50.0) as u32).min(MAX_ITER);
+        (1000 + ((1.0 / fractal_rect.size.length_squared()).log2() * 50.0) as u32).min(iter_cap);
     // println!("max_iterations: {}", max_iterations);
     max_iterations
 }
@@ -85,84 +714,25 @@ impl MandelTexture {
         queue: &wgpu::Queue,
         surface_config: &wgpu::SurfaceConfiguration,
         window_size: UVec2,
-    ) -> Self {
-        let texture_size = TEXTURE_SIZE;
-        assert!(texture_size >= 2048);
-        assert_eq!(texture_size % TILE_SIZE, 0);
-
-        let texture_extent = wgpu::Extent3d {
-            width: texture_size,
-            height: texture_size,
-            depth_or_array_layers: 1,
-        };
-
-        let texture1 = device.create_texture(&wgpu::TextureDescriptor {
-            size: texture_extent,
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::R16Uint,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING
-                | wgpu::TextureUsages::RENDER_ATTACHMENT
-                | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-            label: None,
-        });
-        let texture1_view = texture1.create_view(&wgpu::TextureViewDescriptor::default());
+        msaa_samples: u32,
+        texture_size: UVec2,
+        max_in_flight: Option<usize>,
+    ) -> anyhow::Result<Self> {
+        let max_texture_dimension = device.limits().max_texture_dimension_2d;
+        validate_texture_size(texture_size, max_texture_dimension)?;
 
-        let texture2 = device.create_texture(&wgpu::TextureDescriptor {
-            size: texture_extent,
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::R16Uint,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING
-                | wgpu::TextureUsages::RENDER_ATTACHMENT
-                | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-            label: None,
-        });
-        let texture2_view = texture2.create_view(&wgpu::TextureViewDescriptor::default());
-
-        let tile_count = texture_size / TILE_SIZE;
-        let mut tiles = Vec::with_capacity(tile_count as usize * tile_count as usize);
-        for i in 0..tile_count {
-            for j in 0..tile_count {
-                let index = tiles.len();
-                let rect = URect {
-                    pos: UVec2::new(i * TILE_SIZE, j * TILE_SIZE),
-                    size: UVec2::new(TILE_SIZE, TILE_SIZE),
-                };
-                tiles.push(Tile {
-                    index,
-                    tex_rect: rect,
-                    state: Arc::new(Mutex::new(TileState::Idle)),
-                });
-            }
-        }
+        let tiles = create_tiles(texture_size);
 
         let runtime = Runtime::new().unwrap();
-        let cpu_core_count = num_cpus::get_physical();
-        let semaphore = Arc::new(Semaphore::new(cpu_core_count * 2));
-
-        let vertex_buffers = [wgpu::VertexBufferLayout {
-            array_stride: ScreenRect::vert_size() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    format: wgpu::VertexFormat::Float32x4,
-                    offset: 0,
-                    shader_location: 0,
-                },
-                wgpu::VertexAttribute {
-                    format: wgpu::VertexFormat::Float32x2,
-                    offset: 4 * 4,
-                    shader_location: 1,
-                },
-            ],
-        }];
+        // tiles computing at once, two per physical core unless capped
+        let compute_permits = max_in_flight
+            .unwrap_or_else(|| num_cpus::get_physical() * 2)
+            .clamp(1, MAX_IN_FLIGHT_TILES);
+        let semaphore = Arc::new(Semaphore::new(compute_permits));
+
+        let vertex_buffers = vertex_buffer_layouts();
         let screen_rect_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            contents: ScreenRect::with_texture_size(UVec2::splat(texture_size)).as_bytes(),
+            contents: ScreenRect::with_texture_size(texture_size).as_bytes(),
             usage: wgpu::BufferUsages::VERTEX,
             label: None,
         });
@@ -176,43 +746,31 @@ impl MandelTexture {
             ..Default::default()
         });
 
-        let palette_texture = device.create_texture(&wgpu::TextureDescriptor {
-            size: wgpu::Extent3d {
-                width: 256,
-                height: 1,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D1,
-            format: wgpu::TextureFormat::Rgba8Unorm,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-            label: None,
-        });
-        let palette_view = palette_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let create_palette_texture = || {
+            device.create_texture(&wgpu::TextureDescriptor {
+                size: wgpu::Extent3d {
+                    width: PALETTE_SIZE as u32,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D1,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+                label: None,
+            })
+        };
+        let palette_texture = create_palette_texture();
+        let previous_palette_texture = create_palette_texture();
+        let palette_views = PaletteViews::new(&palette_texture, &previous_palette_texture);
 
-        let img = image::open("palette.png").unwrap();
-        let img = img.into_rgba8();
-        queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                texture: &palette_texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            &img.as_raw(),
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(256 * 4),
-                rows_per_image: Some(1),
-            },
-            wgpu::Extent3d {
-                width: 256,
-                height: 1,
-                depth_or_array_layers: 1,
-            },
-        );
+        let colors = PaletteKind::default().colors(None)?;
+        write_palette(queue, &palette_texture, &colors);
+        write_palette(queue, &previous_palette_texture, &colors);
 
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             entries: &[
@@ -242,6 +800,16 @@ impl MandelTexture {
                     },
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D1,
+                    },
+                    count: None,
+                },
             ],
             label: None,
         });
@@ -254,42 +822,33 @@ impl MandelTexture {
             label: None,
         });
 
-        let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::TextureView(&texture1_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: wgpu::BindingResource::TextureView(&palette_view),
-                },
-            ],
-            label: None,
-        });
-        let bind_group2 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
+        let mip_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::TextureView(&texture2_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: wgpu::BindingResource::TextureView(&palette_view),
-                },
-            ],
-            label: None,
-        });
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        sample_type: wgpu::TextureSampleType::Uint,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                }],
+                label: None,
+            });
+
+        let textures = (0..DEFAULT_TEXTURE_COUNT)
+            .map(|_| {
+                TileTexture::new(
+                    device,
+                    texture_size,
+                    &bind_group_layout,
+                    &mip_bind_group_layout,
+                    &sampler,
+                    &palette_views,
+                )
+            })
+            .collect();
 
         let blit_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
@@ -308,7 +867,7 @@ impl MandelTexture {
                 module: &blit_shader,
                 entry_point: Some("fs_main"),
                 compilation_options: Default::default(),
-                targets: &[Some(wgpu::TextureFormat::R16Uint.into())],
+                targets: &[Some(wgpu::TextureFormat::Rg16Uint.into())],
             }),
             primitive: wgpu::PrimitiveState {
                 cull_mode: None,
@@ -327,28 +886,43 @@ impl MandelTexture {
             label: None,
             source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("screen_shader.wgsl"))),
         });
-        let screen_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let surface_format = surface_config.view_formats[0];
+        let surface_pipelines = SurfacePipelines::new(
+            device,
+            &pipeline_layout,
+            &screen_shader,
+            surface_format,
+            msaa_samples,
+        );
+
+        let mip_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("mip_shader.wgsl"))),
+        });
+        let mip_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&mip_bind_group_layout],
+            push_constant_ranges: &[wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::FRAGMENT,
+                range: 0..size_of::<[u32; 2]>() as u32,
+            }],
+            label: None,
+        });
+        let mip_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: None,
-            layout: Some(&pipeline_layout),
+            layout: Some(&mip_pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &screen_shader,
+                module: &mip_shader,
                 entry_point: Some("vs_main"),
                 compilation_options: Default::default(),
-                buffers: &vertex_buffers,
+                buffers: &[],
             },
             fragment: Some(wgpu::FragmentState {
-                module: &screen_shader,
+                module: &mip_shader,
                 entry_point: Some("fs_main"),
                 compilation_options: Default::default(),
-                targets: &[Some(surface_config.view_formats[0].into())],
+                targets: &[Some(wgpu::TextureFormat::Rg16Uint.into())],
             }),
-            primitive: wgpu::PrimitiveState {
-                cull_mode: None,
-                front_face: wgpu::FrontFace::Cw,
-                topology: wgpu::PrimitiveTopology::TriangleStrip,
-
-                ..Default::default()
-            },
+            primitive: wgpu::PrimitiveState::default(),
             depth_stencil: None,
             multisample: wgpu::MultisampleState::default(),
             multiview: None,
@@ -357,36 +931,102 @@ impl MandelTexture {
 
         let buffer_size = (TILE_SIZE * TILE_SIZE) as usize * size_of::<Pixel>();
 
-        Self {
-            texture1,
-            texture1_view,
-            bind_group1,
-
-            texture2,
-            texture2_view,
-            bind_group2,
+        Ok(Self {
+            textures,
+            texture_count: DEFAULT_TEXTURE_COUNT,
 
             blit_pipeline,
+            mip_pipeline,
             window_size,
+            sub_tile_size: TILE_SIZE,
+            interior_boost: 1,
 
             runtime,
             semaphore,
-
+            compute_permits,
+            slow_semaphore: Arc::new(Semaphore::new(1)),
+            tile_ready_callback: None,
+            watchdog_check: Instant::now(),
+            tile_time_budget: None,
+            max_blit_zoom: DEFAULT_MAX_BLIT_ZOOM,
+            hold_until: None,
+            supersample: None,
+            settle_supersample: None,
+            detail: 0,
+
+            base_texture_size: texture_size,
             texture_size,
+            render_scale: 1.0,
+            max_texture_dimension,
             tiles,
 
             frame_rect: DRect::zeroed(),
             fractal_rect: DRect::zeroed(),
             fractal_rect_prev: DRect::zeroed(),
+            show_previous_texture: false,
+            previous_texture_rect: DRect::zeroed(),
             frame_changed: false,
 
+            fractal: Fractal::default(),
+            iter_override: None,
+            iter_lock: None,
+            iter_cap: DEFAULT_ITER_CAP,
+            iter_storage: IterStorage::default(),
+            mip_blend: MipBlend::default(),
+            kernel: Kernel::default(),
+            quality_boost: false,
+            warmup_iters: None,
+            detect_glitches: false,
+            uniform_view: false,
+            still: None,
+
             screen_rect_buf,
             bind_group_layout,
-            screen_pipeline,
+            mip_bind_group_layout,
+            surface_pipelines,
+            screen_pipeline_layout: pipeline_layout,
+            screen_shader,
+            surface_format,
             sampler,
+            palette_texture,
+            previous_palette_texture,
+            palette: PaletteKind::default(),
+            gradient: None,
+            pending_palette: None,
+            palette_colors: colors.clone(),
+            previous_palette_colors: colors,
+            palette_fade: Duration::ZERO,
+            palette_fade_start: None,
+            palette_mix: 1.0,
+
+            surface_pipelines_for: (surface_format, msaa_samples),
+            msaa_samples,
+            msaa_view: None,
+            show_legend: false,
+            show_boundary: false,
+            boundary_color: Vec4::ONE,
+            show_crosshair: false,
+            crosshair_color: Vec4::ONE,
+            orbit: None,
+            measure: None,
+            interior_coloring: InteriorColoring::default(),
+            interior_color: DEFAULT_INTERIOR_COLOR,
+            color_offset: 0.0,
+            coloring_mode: ColoringMode::default(),
+            band_aa: false,
+            palette_period: DEFAULT_PALETTE_PERIOD,
+            screenshot_request: None,
+            texture_dump_request: None,
+            palette_export_request: None,
+            pending_readbacks: Vec::new(),
+            probe_point: None,
+            probe_requested: false,
+            pending_probe: None,
+            probed_count: None,
 
             buf_pool: BufferPool::new(buffer_size, 1000),
-        }
+            tile_timings: None,
+        })
     }
 
     pub fn update<F>(&mut self, frame_rect: DRect, focus: DVec2, tile_ready_callback: F)
@@ -394,27 +1034,50 @@ impl MandelTexture {
         F: Fn(usize) + Clone + Send + Sync + 'static,
     {
         self.frame_rect = frame_rect;
+        self.uniform_view = false;
 
-        let new_fractal_rect = DRect::from_center_size(
-            frame_rect.center(),
-            DVec2::new(
-                frame_rect.size.x * self.texture_size as f64 / self.window_size.x as f64,
-                frame_rect.size.y * self.texture_size as f64 / self.window_size.y as f64,
-            ),
+        let new_fractal_rect = texture_fractal_rect(
+            frame_rect,
+            self.texture_size,
+            self.window_size,
+            self.render_scale,
         );
 
-        let frame_changed = !self.fractal_rect.contains(&frame_rect)
-            || self.fractal_rect.size != new_fractal_rect.size;
+        let frame_changed = is_frame_changed(self.fractal_rect, new_fractal_rect, frame_rect);
 
         if frame_changed {
+            self.fractal_rect_prev = blit_source(
+                self.fractal_rect_prev,
+                self.fractal_rect,
+                self.frame_changed,
+            );
             self.frame_changed = true;
-            self.fractal_rect_prev = self.fractal_rect;
             self.fractal_rect = new_fractal_rect;
+
+            // too large a zoom to carry the last frame over, it's held on screen instead
+            let zoom = (self.fractal_rect_prev.size / self.fractal_rect.size).max_element();
+            if zoom <= self.max_blit_zoom {
+                self.hold_until = None;
+            } else if self.hold_until.is_none() {
+                self.hold_until = Some(Instant::now() + FRAME_HOLD);
+            }
             // println!("frame_rect:   {:?}, center: {:?}", frame_rect, frame_rect.center());
             // println!("fractal_rect: {:?}, center: {:?}", self.fractal_rect, self.fractal_rect.center());
         }
 
-        let max_iters = calc_max_iters(self.fractal_rect);
+        let kernel_params = self.kernel_params();
+        let boosted_iterations = self.interior_boost_iters(kernel_params.max_iterations);
+        let still_outdated = self.still.as_ref().is_some_and(|still| {
+            still.frame_rect != frame_rect
+                || still.params
+                    != KernelParams {
+                        time_budget: None,
+                        ..kernel_params
+                    }
+        });
+        if still_outdated {
+            self.cancel_still();
+        }
 
         self.tiles.sort_unstable_by(|a, b| {
             let a_center = a
@@ -430,7 +1093,12 @@ impl MandelTexture {
             a_dist.partial_cmp(&b_dist).unwrap()
         });
 
-        self.tiles.iter_mut().for_each(|tile| {
+        let tile_ready_callback = TileReadyCallback(Arc::new(tile_ready_callback));
+        self.tile_ready_callback = Some(tile_ready_callback.clone());
+        self.watchdog_check = Instant::now();
+
+        let mut tiles = std::mem::take(&mut self.tiles);
+        for tile in &mut tiles {
             let mut tile_state = tile.state.lock();
 
             let tile_rect = tile.fractal_rect(self.texture_size, self.fractal_rect);
@@ -438,68 +1106,417 @@ impl MandelTexture {
 
             if !tile_in_view {
                 tile_state.cancel();
-                return;
+                continue;
             }
 
-            if tile_state.is_computing() && !frame_changed {
-                // when panning, tile could be already in progress
-                // or
-                // not in view, skip
-                return;
+            if !tile_state.needs_spawn(frame_changed) {
+                continue;
             }
 
             tile_state.cancel();
+            tile.samples.clear();
+            tile.gradients.clear();
+            *tile_state = self.spawn_tile(
+                tile,
+                kernel_params,
+                boosted_iterations,
+                tile_ready_callback.clone(),
+            );
+        }
+        self.tiles = tiles;
+    }
+
+    // starts computing `tile` at the current fractal rect, the returned state goes into the tile
+    fn spawn_tile(
+        &mut self,
+        tile: &Tile,
+        kernel_params: KernelParams,
+        boosted_iterations: u32,
+        callback: TileReadyCallback,
+    ) -> TileState {
+        let tile_rect = tile.fractal_rect(self.texture_size, self.fractal_rect);
+        // a texel of margin covers the supersampling offsets
+        let texel = tile_rect.size / tile.tex_rect.size.as_dvec2();
+        // the scalar reference kernel computes every point
+        let interior = kernel_params.fractal.kind == FractalKind::Mandelbrot
+            && kernel_params.kernel != Kernel::Scalar
+            && !kernel_params.detect_glitches
+            && is_rect_interior(DRect::from_pos_size(
+                tile_rect.pos - texel,
+                tile_rect.size + 2.0 * texel,
+            ));
+
+        let img_size = self.texture_size;
+        let tex_rect = tile.tex_rect;
+        let tile_index = tile.index;
+        let fractal_rect = self.fractal_rect;
+
+        // provably inside the set, filled in closed form right here, not a single iteration
+        if interior {
+            let buffer = self.buf_pool.take();
+            fill_interior(
+                img_size,
+                tex_rect,
+                fractal_rect.center(),
+                1.0 / fractal_rect.size.y,
+                kernel_params.max_iterations,
+                bytemuck::cast_slice_mut(&mut buffer.lock()),
+            );
+            (callback.0)(tile_index);
+            // the counts are the same everywhere inside
+            let cells = tex_rect
+                .size
+                .map(|side| side.div_ceil(SAMPLE_STRIDE as u32));
+            return TileState::WaitForUpload {
+                buffer,
+                gradients: vec![0; cells.element_product() as usize],
+            };
+        }
 
-            let img_size = self.texture_size;
-            let tex_rect = tile.tex_rect;
-            let tile_index = tile.index;
-            let fractal_rect = self.fractal_rect;
+        let cancel_token = Arc::new(AtomicBool::new(false));
+        let cancel_token_clone = cancel_token.clone();
+        let tile_state_clone = tile.state.clone();
+        let semaphore = self.semaphore.clone();
+        let slow_semaphore = self.slow_semaphore.clone();
+        let tile_timings = self.tile_timings.clone();
+        let sub_tile_size = self.sub_tile_size;
+
+        let buffer = self.buf_pool.take();
+
+        let task_handle = self.runtime.spawn(async move {
+            let start = Instant::now();
+            let part_count = (TILE_SIZE / sub_tile_size).pow(2) as usize;
+            let mut pixels = vec![Pixel::zeroed(); (sub_tile_size * sub_tile_size) as usize];
+            let mut status = Ok(TileStatus::Complete);
+            let mut params = kernel_params;
+            // a second pass with the boosted count for tiles mostly interior at the first
+            loop {
+                // every sub-tile waits for a permit of its own, so the tiles fill in together
+                for (index, part) in sub_rects(tex_rect, sub_tile_size).enumerate() {
+                    let compute = |params: &KernelParams, pixels: &mut [Pixel]| {
+                        catch_panic(|| {
+                            mandelbrot_simd(
+                                img_size,
+                                part,
+                                fractal_rect.center(),
+                                1.0 / fractal_rect.size.y,
+                                params,
+                                cancel_token_clone.clone(),
+                                pixels,
+                            )
+                        })
+                    };
+
+                    let permit = semaphore.acquire().await.unwrap();
+                    status = compute(&params, &mut pixels);
+                    drop(permit);
+
+                    if let Ok(TileStatus::TimedOut) = status {
+                        // let the regular tiles through, slow ones finish one at a time
+                        let _slow_permit = slow_semaphore.acquire().await.unwrap();
+                        status = compute(
+                            &KernelParams {
+                                time_budget: None,
+                                ..params
+                            },
+                            &mut pixels,
+                        );
+                    }
+                    if !matches!(status, Ok(TileStatus::Complete)) {
+                        break;
+                    }
+
+                    copy_part(
+                        tex_rect,
+                        part,
+                        &pixels,
+                        bytemuck::cast_slice_mut(&mut buffer.lock()),
+                    );
+                    // the last one goes up with the whole tile
+                    if index + 1 < part_count {
+                        if !publish_part(&tile_state_clone, part, pixels.clone()) {
+                            return;
+                        }
+                        (callback.0)(tile_index);
+                    }
+                }
 
-            let callback = tile_ready_callback.clone();
-            let cancel_token = Arc::new(AtomicBool::new(false));
-            let cancel_token_clone = cancel_token.clone();
-            let tile_state_clone = tile.state.clone();
-            let semaphore = self.semaphore.clone();
+                // bulbs confirmed at the first count may still hold points that escape later
+                let boost = matches!(status, Ok(TileStatus::Complete))
+                    && params.max_iterations < boosted_iterations
+                    && interior_fraction(bytemuck::cast_slice(&buffer.lock()))
+                        >= INTERIOR_BOOST_FRACTION;
+                if !boost {
+                    break;
+                }
+                params.max_iterations = boosted_iterations;
+            }
+            let compute_ok = matches!(status, Ok(TileStatus::Complete));
 
-            let buffer = self.buf_pool.take();
+            if let (true, Some(tile_timings)) = (compute_ok, &tile_timings) {
+                tile_timings.lock().push(start.elapsed());
+            }
 
-            let task_handle = self.runtime.spawn(async move {
-                let _permit = semaphore.acquire().await.unwrap();
+            // for `detail_peak`, here instead of on the render thread with the upload
+            let gradients = match compute_ok {
+                true => cell_gradients(bytemuck::cast_slice(&buffer.lock()), tex_rect.size.x),
+                false => Vec::new(),
+            };
+            if finish_tile(&tile_state_clone, tile_index, status, buffer, gradients) {
+                (callback.0)(tile_index);
+            }
+        });
 
-                let compute_ok = {
-                    let buffer = &mut *buffer.lock();
-                    let buffer: &mut [Pixel] = bytemuck::cast_slice_mut(buffer);
+        TileState::Computing {
+            task_handle,
+            cancel_token,
+            parts: Vec::new(),
+        }
+    }
 
-                    mandelbrot_simd(
-                        img_size,
-                        tex_rect,
-                        -fractal_rect.center(),
-                        1.0 / fractal_rect.size.y,
-                        max_iters,
-                        cancel_token_clone,
-                        buffer,
-                    )
-                    .is_ok()
-                };
+    pub(crate) fn kernel_params(&self) -> KernelParams {
+        KernelParams {
+            max_iterations: self.max_iters(),
+            fractal: self.fractal,
+            detect_glitches: self.detect_glitches,
+            time_budget: self.tile_time_budget,
+            supersample: self
+                .supersample
+                .or(self.settle_supersample.filter(|_| self.quality_boost))
+                .or_else(|| {
+                    detail_split(self.detail, self.frame_rect)
+                        .1
+                        .then_some(SamplePattern::RotatedGrid)
+                }),
+            iter_storage: self.iter_storage,
+            kernel: self.zoom_kernel(),
+        }
+    }
+
+    // the SIMD kernel hands over to double-double once f64 can't tell the texels apart,
+    // about twice the zoom depth at a fraction of the speed
+    fn zoom_kernel(&self) -> Kernel {
+        let texel = self.fractal_rect.size / self.texture_size.as_dvec2();
+        match self.kernel {
+            Kernel::Simd if is_beyond_precision(self.fractal_rect, texel) => Kernel::DoubleDouble,
+            kernel => kernel,
+        }
+    }
+
+    /// Computes every window pixel of the current view directly, without the texture's resampling.
+    /// Slower than the tiles, it replaces them on screen once done and until the view changes.
+    pub fn request_still<F>(&mut self, ready_callback: F)
+    where
+        F: Fn() + Send + 'static,
+    {
+        self.cancel_still();
 
-                let mut tile_state = tile_state_clone.lock();
-                if compute_ok {
-                    *tile_state = TileState::WaitForUpload { buffer };
-                    (callback)(tile_index);
+        let frame_rect = self.frame_rect;
+        let window_size = self.window_size;
+        let params = KernelParams {
+            time_budget: None,
+            ..self.kernel_params()
+        };
+        let size = UVec2::new(
+            window_size.x.next_multiple_of(SIMD_LANE_COUNT as u32),
+            window_size.y,
+        );
+        // the padding columns are on the right, shift the center so the window's pixels stay put
+        let pixel_size = frame_rect.size.y / window_size.y as f64;
+        let center = frame_rect.center()
+            + DVec2::new((size.x - window_size.x) as f64 / 2.0 * pixel_size, 0.0);
+
+        let cancel_token = Arc::new(AtomicBool::new(false));
+        let pixels = Arc::new(Mutex::new(None));
+        let task_cancel_token = cancel_token.clone();
+        let task_pixels = pixels.clone();
+        self.runtime.spawn_blocking(move || {
+            let thread_count = num_cpus::get_physical() as u32;
+            let result = render_cancellable(
+                size,
+                center,
+                1.0 / frame_rect.size.y,
+                &params,
+                thread_count,
+                &task_cancel_token,
+            );
+            match result {
+                _ if task_cancel_token.load(std::sync::atomic::Ordering::Relaxed) => {}
+                Ok(result) => {
+                    *task_pixels.lock() = Some(result);
+                    ready_callback();
                 }
-            });
+                Err(err) => println!("Still render failed: {}", err),
+            }
+        });
 
-            *tile_state = TileState::Computing {
-                task_handle,
-                cancel_token,
-            };
+        self.still = Some(StillRender {
+            frame_rect,
+            window_size,
+            params,
+            size,
+            cancel_token,
+            pixels,
+            texture: None,
+            dump_path: None,
+        });
+    }
+
+    /// Saves the pending still's pixels once computed, see `IterDump`.
+    pub fn request_iter_dump(&mut self, path: PathBuf) {
+        if let Some(still) = &mut self.still {
+            still.dump_path = Some(path);
+        }
+    }
+
+    /// Shows dumped pixels as the still of the current view, which should be the dump's.
+    pub fn show_iter_dump(&mut self, dump: IterDump) -> anyhow::Result<()> {
+        if dump.size != self.window_size {
+            return Err(anyhow!(
+                "The dump is {}x{}, the window {}x{}",
+                dump.size.x,
+                dump.size.y,
+                self.window_size.x,
+                self.window_size.y
+            ));
+        }
+
+        self.cancel_still();
+        self.still = Some(StillRender {
+            frame_rect: self.frame_rect,
+            window_size: self.window_size,
+            params: KernelParams {
+                time_budget: None,
+                ..self.kernel_params()
+            },
+            size: dump.size,
+            cancel_token: Arc::new(AtomicBool::new(false)),
+            pixels: Arc::new(Mutex::new(Some(dump.pixels))),
+            texture: None,
+            dump_path: None,
         });
+
+        Ok(())
+    }
+
+    fn cancel_still(&mut self) {
+        if let Some(still) = self.still.take() {
+            still
+                .cancel_token
+                .store(true, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    /// None without a still, false while it is still being computed.
+    pub fn still_ready(&self) -> Option<bool> {
+        self.still
+            .as_ref()
+            .map(|still| still.texture.is_some() || still.pixels.lock().is_some())
+    }
+
+    // false until every tile in view has been uploaded
+    fn is_view_uniform(&self) -> bool {
+        let mut histogram: HashMap<u16, usize> = HashMap::new();
+        for tile in &self.tiles {
+            let tile_rect = tile.fractal_rect(self.texture_size, self.fractal_rect);
+            if !self.frame_rect.intersects(&tile_rect) {
+                continue;
+            }
+            if tile.samples.is_empty() {
+                return false;
+            }
+            for &count in &tile.samples {
+                *histogram.entry(count).or_default() += 1;
+            }
+        }
+
+        let total: usize = histogram.values().sum();
+        let Some((&mode, _)) = histogram.iter().max_by_key(|(_, &samples)| samples) else {
+            return false;
+        };
+        let near_mode: usize = (mode.saturating_sub(1)..=mode.saturating_add(1))
+            .filter_map(|count| histogram.get(&count))
+            .sum();
+
+        near_mode as f64 >= total as f64 * UNIFORM_FRACTION
+    }
+
+    pub fn cancel_tiles(&mut self) {
+        self.tiles
+            .iter()
+            .for_each(|tile| tile.state.lock().cancel());
     }
 
+    /// Cancels every tile and the still, computed or in flight, so the next `update` schedules
+    /// the whole view again. Tiles in flight would otherwise finish with the settings they
+    /// started with, the old pixels stay on screen until the new ones come in.
+    pub fn invalidate(&mut self) {
+        self.cancel_tiles();
+        self.cancel_still();
+    }
+
+    /// Draws the view centered in `render_info.view`, letterboxed when the target is larger.
     pub fn render(&mut self, render_info: &RenderContext) {
+        let viewport = URect::from_pos_size(
+            render_info.view_size.saturating_sub(self.window_size) / 2,
+            self.window_size,
+        );
+        self.render_to(render_info, viewport);
+    }
+
+    /// Draws the view into `viewport` of `render_info.view`, which needn't be a window's surface.
+    /// A viewport of another size than the window's stretches the view's pixels onto it.
+    pub fn render_to(&mut self, render_info: &RenderContext, viewport: URect) {
+        self.hold_until = None;
+        self.watch_tiles();
+        self.prepare_textures(render_info);
+        self.prepare_msaa(render_info);
+        self.prepare_polylines(render_info);
+        self.upload_palette(render_info);
         self.blit_textures(render_info);
         self.upload_tiles(render_info);
-        self.surface_render(render_info);
+        self.upload_still(render_info);
+        self.surface_render(render_info, viewport);
+        self.capture_screenshot(render_info);
+        self.dump_texture(render_info);
+        self.export_palette(render_info);
+    }
+
+    // every in-view tile leaves an update computing, one still idle without data a while later
+    // slipped through a cancellation, it's computed again so its area doesn't stay blank
+    fn watch_tiles(&mut self) {
+        if self.watchdog_check.elapsed() < TILE_WATCHDOG_INTERVAL {
+            return;
+        }
+        self.watchdog_check = Instant::now();
+        let Some(callback) = self.tile_ready_callback.clone() else {
+            return;
+        };
+
+        let kernel_params = self.kernel_params();
+        let boosted_iterations = self.interior_boost_iters(kernel_params.max_iterations);
+        let tiles = std::mem::take(&mut self.tiles);
+        let mut stalled = 0;
+        for tile in &tiles {
+            let mut tile_state = tile.state.lock();
+            let tile_rect = tile.fractal_rect(self.texture_size, self.fractal_rect);
+            if matches!(*tile_state, TileState::Idle)
+                && tile.samples.is_empty()
+                && self.frame_rect.intersects(&tile_rect)
+            {
+                *tile_state =
+                    self.spawn_tile(tile, kernel_params, boosted_iterations, callback.clone());
+                stalled += 1;
+            }
+        }
+        self.tiles = tiles;
+
+        if stalled > 0 {
+            println!(
+                "Tile watchdog: {} tiles in view were idle without data, recomputing",
+                stalled
+            );
+        }
     }
 
     fn blit_textures(&mut self, render_info: &RenderContext) {
@@ -507,6 +1524,19 @@ impl MandelTexture {
             return;
         }
 
+        let scale = self.fractal_rect_prev.size / self.fractal_rect.size;
+        // the cleared texture is filled by the new tiles instead
+        let reuse_previous = scale.max_element() <= self.max_blit_zoom;
+
+        // zooming out shrinks the previous texture, read it from a matching mip level
+        let mip_level = (1.0 / scale.max_element())
+            .log2()
+            .floor()
+            .clamp(0.0, (MIP_LEVEL_COUNT - 1) as f64) as u32;
+        if mip_level > 0 {
+            self.generate_mips(render_info);
+        }
+
         let mut command_encoder = render_info
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
@@ -515,7 +1545,7 @@ impl MandelTexture {
             let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &self.texture2_view,
+                    view: &self.textures.last().unwrap().mip_views[0],
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
@@ -527,50 +1557,116 @@ impl MandelTexture {
                 occlusion_query_set: None,
             });
 
-            render_pass.set_pipeline(&self.blit_pipeline);
-            render_pass.set_vertex_buffer(0, self.screen_rect_buf.slice(..));
+            if reuse_previous {
+                render_pass.set_pipeline(&self.blit_pipeline);
+                render_pass.set_vertex_buffer(0, self.screen_rect_buf.slice(..));
 
-            let offset = (self.fractal_rect_prev.center() - self.fractal_rect.center())
-                / self.fractal_rect_prev.size;
-            let offset = 2.0 * DVec2::new(offset.x, -offset.y);
-            let scale = self.fractal_rect_prev.size / self.fractal_rect.size;
+                let mut pc = PushConst::new();
+                pc.proj_mat = blit_transform(self.fractal_rect_prev, self.fractal_rect);
+                pc.texture_size = self.texture_size.as_vec2();
+                pc.mip_level = mip_level;
 
-            let mut pc = PushConst::new();
-            pc.proj_mat = Mat4::from_scale(Vec3::new(scale.x as f32, scale.y as f32, 1.0))
-                * Mat4::from_translation(Vec3::new(offset.x as f32, offset.y as f32, 0.0));
-            pc.texture_size = Vec2::splat(self.texture_size as f32);
+                render_pass.set_push_constants(wgpu::ShaderStages::VERTEX, 0, pc.as_bytes());
 
-            render_pass.set_push_constants(wgpu::ShaderStages::VERTEX, 0, pc.as_bytes());
-
-            render_pass.set_bind_group(0, &self.bind_group1, &[]);
-            render_pass.draw(0..ScreenRect::vert_count(), 0..1);
+                render_pass.set_bind_group(0, &self.textures[0].bind_group, &[]);
+                render_pass.draw(0..ScreenRect::vert_count(), 0..1);
+            }
         }
 
         render_info.queue.submit(Some(command_encoder.finish()));
 
-        swap(&mut self.texture1, &mut self.texture2);
-        swap(&mut self.texture1_view, &mut self.texture2_view);
-        swap(&mut self.bind_group1, &mut self.bind_group2);
+        self.textures.rotate_right(1);
+        self.textures[0].mips_dirty = true;
 
         self.frame_changed = false;
+        self.previous_texture_rect = self.fractal_rect_prev;
         self.fractal_rect_prev = self.fractal_rect;
     }
 
+    fn generate_mips(&mut self, render_info: &RenderContext) {
+        if !self.textures[0].mips_dirty {
+            return;
+        }
+
+        let mut command_encoder = render_info
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        // `PushConstant` in mip_shader.wgsl
+        let pc = [self.mip_blend as u32, self.iter_storage as u32];
+
+        for level in 1..MIP_LEVEL_COUNT as usize {
+            let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.textures[0].mip_views[level],
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(&self.mip_pipeline);
+            render_pass.set_push_constants(
+                wgpu::ShaderStages::FRAGMENT,
+                0,
+                bytemuck::cast_slice(&pc),
+            );
+            render_pass.set_bind_group(0, &self.textures[0].mip_bind_groups[level - 1], &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        render_info.queue.submit(Some(command_encoder.finish()));
+
+        self.textures[0].mips_dirty = false;
+    }
+
     fn upload_tiles(&mut self, render_info: &RenderContext) {
-        self.tiles.iter().for_each(|tile| {
+        let mut uploaded = false;
+
+        self.tiles.iter_mut().for_each(|tile| {
             let mut tile_state = tile.state.lock();
+            if let TileState::Computing { parts, .. } = &mut *tile_state {
+                for (part, pixels) in parts.drain(..) {
+                    render_info.queue.write_texture(
+                        wgpu::TexelCopyTextureInfo {
+                            texture: &self.textures[0].texture,
+                            mip_level: 0,
+                            origin: wgpu::Origin3d {
+                                x: part.pos.x,
+                                y: part.pos.y,
+                                z: 0,
+                            },
+                            aspect: wgpu::TextureAspect::All,
+                        },
+                        bytemuck::cast_slice(&pixels),
+                        pixel_copy_layout(part.size),
+                        wgpu::Extent3d {
+                            width: part.size.x,
+                            height: part.size.y,
+                            depth_or_array_layers: 1,
+                        },
+                    );
+                    uploaded = true;
+                }
+            }
             if let TileState::WaitForUpload { .. } = *tile_state {
                 let mut ready = TileState::Idle;
                 swap(&mut ready, &mut *tile_state);
 
-                let TileState::WaitForUpload { buffer } = ready else {
+                let TileState::WaitForUpload { buffer, gradients } = ready else {
                     panic!();
                 };
                 let buffer = buffer.lock();
                 let buffer = buffer.as_slice();
                 render_info.queue.write_texture(
                     wgpu::TexelCopyTextureInfo {
-                        texture: &self.texture1,
+                        texture: &self.textures[0].texture,
                         mip_level: 0,
                         origin: wgpu::Origin3d {
                             x: tile.tex_rect.pos.x,
@@ -580,93 +1676,1833 @@ impl MandelTexture {
                         aspect: wgpu::TextureAspect::All,
                     },
                     buffer,
-                    wgpu::TexelCopyBufferLayout {
-                        offset: 0,
-                        bytes_per_row: Some(size_of::<Pixel>() as u32 * tile.tex_rect.size.x),
-                        rows_per_image: Some(tile.tex_rect.size.y),
-                    },
+                    pixel_copy_layout(tile.tex_rect.size),
                     wgpu::Extent3d {
                         width: tile.tex_rect.size.x,
                         height: tile.tex_rect.size.y,
                         depth_or_array_layers: 1,
                     },
                 );
+                let pixels: &[Pixel] = bytemuck::cast_slice(buffer);
+                tile.samples = sample_counts(pixels, tile.tex_rect.size.x);
+                tile.gradients = gradients;
+                uploaded = true;
             }
         });
+
+        if uploaded {
+            self.uniform_view = self.is_view_uniform();
+        }
+        self.textures[0].mips_dirty |= uploaded;
+        // the texel under the cursor may have just been computed
+        self.probe_requested |= uploaded;
     }
 
-    fn surface_render(&self, render_info: &RenderContext) {
-        let tex_size = Vec2::splat(self.texture_size as f32);
-        let win_size = Vec2::new(self.window_size.x as f32, self.window_size.y as f32);
-        let scale = tex_size / win_size;
-        let offset =
-            2.0 * (self.fractal_rect.center() - self.frame_rect.center()) / self.frame_rect.size;
+    fn upload_still(&mut self, render_info: &RenderContext) {
+        let Some(still) = &mut self.still else {
+            return;
+        };
+        let Some(pixels) = still.pixels.lock().take() else {
+            return;
+        };
+
+        if let Some(path) = still.dump_path.take() {
+            // without the padding columns
+            let pixels = pixels
+                .chunks(still.size.x as usize)
+                .flat_map(|row| &row[..still.window_size.x as usize])
+                .copied()
+                .collect();
+            let dump = IterDump {
+                frame_rect: still.frame_rect,
+                fractal: still.params.fractal,
+                max_iterations: still.params.max_iterations,
+                iter_storage: still.params.iter_storage,
+                size: still.window_size,
+                pixels,
+            };
+            self.runtime.spawn_blocking(move || match dump.save(&path) {
+                Ok(()) => println!("Saved {}", path.display()),
+                Err(err) => println!("Failed to save {}: {}", path.display(), err),
+            });
+        }
+
+        let size = wgpu::Extent3d {
+            width: still.size.x,
+            height: still.size.y,
+            depth_or_array_layers: 1,
+        };
+        let texture = render_info.device.create_texture(&wgpu::TextureDescriptor {
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rg16Uint,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+            label: None,
+        });
+        render_info.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            bytemuck::cast_slice(&pixels),
+            pixel_copy_layout(still.size),
+            size,
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let palette_views =
+            PaletteViews::new(&self.palette_texture, &self.previous_palette_texture);
+        let bind_group = render_info
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::TextureView(&palette_views.current),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::TextureView(&palette_views.previous),
+                    },
+                ],
+                label: None,
+            });
+        // texel coordinates only span the window, the padding columns stay off screen
+        let screen_rect_buf =
+            render_info
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    contents: ScreenRect::with_texture_size(still.window_size).as_bytes(),
+                    usage: wgpu::BufferUsages::VERTEX,
+                    label: None,
+                });
+
+        still.texture = Some(StillTexture {
+            _texture: texture,
+            bind_group,
+            screen_rect_buf,
+        });
+    }
 
+    fn surface_render(&self, render_info: &RenderContext, viewport: URect) {
         let mut command_encoder = render_info
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-        {
-            let mut pc = PushConst::new();
-            pc.proj_mat = Mat4::from_translation(Vec3::new(offset.x as f32, offset.y as f32, 0.0))
-                * Mat4::from_scale(Vec3::new(scale.x, scale.y, 1.0));
+        self.draw_screen(
+            &mut command_encoder,
+            render_info.view,
+            self.msaa_view.as_ref().map(|(_, view)| view),
+            self.frame_rect,
+            viewport.size,
+            viewport.pos,
+        );
+        render_info.queue.submit(Some(command_encoder.finish()));
+    }
 
-            let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: render_info.view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-            render_pass.set_pipeline(&self.screen_pipeline);
-            render_pass.set_vertex_buffer(0, self.screen_rect_buf.slice(..));
+    fn draw_screen(
+        &self,
+        command_encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        msaa_view: Option<&wgpu::TextureView>,
+        frame_rect: DRect,
+        target_size: UVec2,
+        viewport_offset: UVec2,
+    ) {
+        let (texture, fractal_rect) = if self.show_previous_texture {
+            (&self.textures[1], self.previous_texture_rect)
+        } else {
+            (&self.textures[0], self.fractal_rect)
+        };
+        let tex_size = self.texture_size.as_vec2();
+        let win_size = Vec2::new(target_size.x as f32, target_size.y as f32);
+        let scale = tex_size * (fractal_rect.size / self.fractal_rect.size).as_vec2()
+            / (win_size * self.render_scale as f32);
+        let offset = 2.0 * (fractal_rect.center() - frame_rect.center()) / frame_rect.size;
+
+        // a finished still of exactly this view covers the target one texel per pixel
+        let still = self.still.as_ref().and_then(|still| {
+            still.texture.as_ref().filter(|_| {
+                still.frame_rect == frame_rect
+                    && still.window_size == target_size
+                    && !self.show_previous_texture
+            })
+        });
+        let (bind_group, screen_rect_buf) = match still {
+            Some(still) => (&still.bind_group, &still.screen_rect_buf),
+            None => (&texture.bind_group, &self.screen_rect_buf),
+        };
+
+        let mut pc = PushConst::new();
+        pc.proj_mat = match still {
+            Some(_) => Mat4::IDENTITY,
+            None => {
+                Mat4::from_translation(Vec3::new(offset.x as f32, offset.y as f32, 0.0))
+                    * Mat4::from_scale(Vec3::new(scale.x, scale.y, 1.0))
+            }
+        };
+        pc.boundary_color = self.boundary_color;
+        pc.interior_coloring = self.interior_coloring as u32;
+        pc.coloring_mode = self.coloring_mode as u32;
+        pc.band_aa = self.band_aa as u32;
+        pc.palette_period = self.palette_period as f32;
+        pc.smooth_degree = self.fractal.degree() as f32;
+        pc.interior_color = self.interior_color;
+        pc.color_offset = self.color_offset;
+        pc.iter_storage = self.iter_storage as u32;
+        pc.palette_mix = self.palette_mix;
+        pc.target_size = target_size.as_vec2();
+        pc.viewport_offset = viewport_offset.as_vec2();
+        pc.crosshair_color = self.crosshair_color;
+
+        let color_attachment = match msaa_view {
+            Some(msaa_view) => wgpu::RenderPassColorAttachment {
+                view: msaa_view,
+                resolve_target: Some(view),
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Discard,
+                },
+            },
+            None => wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            },
+        };
+
+        let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(color_attachment)],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        // the rest of the target keeps the clear color
+        render_pass.set_viewport(
+            viewport_offset.x as f32,
+            viewport_offset.y as f32,
+            target_size.x as f32,
+            target_size.y as f32,
+            0.0,
+            1.0,
+        );
+        render_pass.set_pipeline(&self.surface_pipelines.screen);
+        render_pass.set_vertex_buffer(0, screen_rect_buf.slice(..));
+        render_pass.set_push_constants(wgpu::ShaderStages::VERTEX, 0, pc.as_bytes());
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..ScreenRect::vert_count(), 0..1);
+
+        if self.show_boundary {
+            render_pass.set_pipeline(&self.surface_pipelines.boundary);
+            // the GL backend drops push constants on a pipeline switch
             render_pass.set_push_constants(wgpu::ShaderStages::VERTEX, 0, pc.as_bytes());
-            render_pass.set_bind_group(0, &self.bind_group1, &[]);
             render_pass.draw(0..ScreenRect::vert_count(), 0..1);
         }
 
-        render_info.queue.submit(Some(command_encoder.finish()));
-    }
+        let polylines = [
+            (&self.orbit, &self.surface_pipelines.orbit),
+            (&self.measure, &self.surface_pipelines.measure),
+        ];
+        for (polyline, pipeline) in polylines {
+            let Some(polyline) = polyline else {
+                continue;
+            };
+            let mut polyline_pc = pc;
+            polyline_pc.proj_mat = orbit_transform(polyline.origin, frame_rect);
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_push_constants(wgpu::ShaderStages::VERTEX, 0, polyline_pc.as_bytes());
+            render_pass.set_vertex_buffer(0, polyline.vertex_buf.as_ref().unwrap().slice(..));
+            render_pass.draw(0..polyline.points.len() as u32, 0..1);
+        }
 
-    pub fn resize_window(&mut self, window_size: UVec2) {
-        self.window_size = window_size;
+        if self.show_legend {
+            render_pass.set_pipeline(&self.surface_pipelines.legend);
+            render_pass.draw(0..4, 0..1);
+        }
+
+        if self.show_crosshair {
+            render_pass.set_pipeline(&self.surface_pipelines.crosshair);
+            render_pass.set_push_constants(wgpu::ShaderStages::VERTEX, 0, pc.as_bytes());
+            render_pass.draw(0..4, 0..1);
+        }
     }
-}
 
-impl Tile {
-    pub(crate) fn fractal_rect(&self, tex_size: u32, fractal_rect: DRect) -> DRect {
-        let abs_frame_size = DVec2::splat(tex_size as f64);
-        let abs_tile_pos = DVec2::from(self.tex_rect.pos);
-        let abs_tile_size = DVec2::from(self.tex_rect.size);
+    /// Renders the requested screenshot off screen and starts reading it back.
+    fn capture_screenshot(&mut self, render_info: &RenderContext) {
+        let Some(request) = self.screenshot_request.take() else {
+            return;
+        };
 
-        let tile_size = fractal_rect.size * abs_tile_size / abs_frame_size;
-        let tile_pos = fractal_rect.pos + fractal_rect.size * abs_tile_pos / abs_frame_size;
+        let size = wgpu::Extent3d {
+            width: request.size.x,
+            height: request.size.y,
+            depth_or_array_layers: 1,
+        };
+        let texture = render_info.device.create_texture(&wgpu::TextureDescriptor {
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.surface_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+            label: None,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let msaa_view = (self.msaa_samples > 1).then(|| {
+            render_info
+                .device
+                .create_texture(&wgpu::TextureDescriptor {
+                    size,
+                    mip_level_count: 1,
+                    sample_count: self.msaa_samples,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: self.surface_format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                    label: None,
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        });
 
-        DRect::from_pos_size(tile_pos, tile_size)
+        let mut command_encoder = render_info
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        self.draw_screen(
+            &mut command_encoder,
+            &view,
+            msaa_view.as_ref(),
+            request.frame_rect,
+            request.size,
+            UVec2::ZERO,
+        );
+        self.read_back(render_info, command_encoder, &texture, request.path);
     }
-}
 
-impl TileState {
-    fn cancel(&mut self) {
-        if let TileState::Computing {
-            task_handle,
-            cancel_token,
-        } = self
-        {
-            cancel_token.store(true, std::sync::atomic::Ordering::Relaxed);
-            task_handle.abort();
+    /// Starts reading back the whole iteration texture, stale and uncomputed regions included.
+    fn dump_texture(&mut self, render_info: &RenderContext) {
+        let Some(path) = self.texture_dump_request.take() else {
+            return;
+        };
+
+        let command_encoder = render_info
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let texture = self.textures[0].texture.clone();
+        self.read_back(render_info, command_encoder, &texture, path);
+    }
+
+    fn upload_palette(&mut self, render_info: &RenderContext) {
+        if let Some(colors) = self.pending_palette.take() {
+            if !self.palette_fade.is_zero() {
+                // the colors on screen, halfway through a fade that's part of both palettes
+                let displayed = blend_palettes(
+                    &self.previous_palette_colors,
+                    &self.palette_colors,
+                    self.palette_mix,
+                );
+                write_palette(
+                    render_info.queue,
+                    &self.previous_palette_texture,
+                    &displayed,
+                );
+                self.previous_palette_colors = displayed;
+                self.palette_fade_start = Some(render_info.time);
+            }
+            write_palette(render_info.queue, &self.palette_texture, &colors);
+            self.palette_colors = colors;
         }
 
-        *self = TileState::Idle;
+        self.palette_mix = match self.palette_fade_start {
+            Some(start) => {
+                ((render_info.time - start) / self.palette_fade.as_secs_f64()).min(1.0) as f32
+            }
+            None => 1.0,
+        };
+        if self.palette_mix >= 1.0 {
+            self.palette_fade_start = None;
+        }
     }
 
-    fn is_computing(&self) -> bool {
-        matches!(self, TileState::Computing { .. })
+    /// Starts reading back the palette as the shader sees it, one pixel per entry.
+    fn export_palette(&mut self, render_info: &RenderContext) {
+        let Some(path) = self.palette_export_request.take() else {
+            return;
+        };
+
+        let command_encoder = render_info
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let texture = self.palette_texture.clone();
+        self.read_back(render_info, command_encoder, &texture, path);
+    }
+
+    // copies mip 0 of `texture` into a mappable buffer after whatever `command_encoder` already holds
+    fn read_back(
+        &mut self,
+        render_info: &RenderContext,
+        mut command_encoder: wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        path: PathBuf,
+    ) {
+        let size = UVec2::new(texture.width(), texture.height());
+        let padded_bytes_per_row = PendingReadback::padded_bytes_per_row(size.x, texture.format());
+        let buffer = render_info.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: padded_bytes_per_row as u64 * size.y as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        command_encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(size.y),
+                },
+            },
+            wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+        );
+        render_info.queue.submit(Some(command_encoder.finish()));
+
+        self.pending_readbacks
+            .push(PendingReadback::new(path, size, texture.format(), buffer));
+    }
+
+    /// Asks for a screenshot of the current view, written to `path` once the GPU is done with it.
+    pub fn request_screenshot(&mut self, path: PathBuf) {
+        self.screenshot_request = Some(ScreenshotRequest {
+            path,
+            frame_rect: self.frame_rect,
+            size: self.window_size,
+        });
+    }
+
+    /// Asks for the raw iteration texture as a 16-bit grayscale PNG, for debugging.
+    pub fn request_texture_dump(&mut self, path: PathBuf) {
+        self.texture_dump_request = Some(path);
+    }
+
+    pub fn palette(&self) -> PaletteKind {
+        self.palette
+    }
+
+    /// Switches the palette, shown from the next frame on.
+    pub fn set_palette(&mut self, palette: PaletteKind) -> anyhow::Result<()> {
+        self.pending_palette = Some(palette.colors(self.gradient.as_deref())?);
+        self.palette = palette;
+        Ok(())
+    }
+
+    /// How long a palette switch cross-fades, zero swaps instantly.
+    pub fn set_palette_fade(&mut self, palette_fade: Duration) {
+        self.palette_fade = palette_fade;
+    }
+
+    /// True while a palette switch is still fading in and needs further frames.
+    pub fn is_fading(&self) -> bool {
+        self.palette_fade_start.is_some()
+    }
+
+    pub fn has_gradient(&self) -> bool {
+        self.gradient.is_some()
+    }
+
+    /// The file `PaletteKind::Gradient` loads, read again on every switch to it.
+    pub fn set_gradient(&mut self, gradient: Option<PathBuf>) {
+        self.gradient = gradient;
+    }
+
+    /// Asks for the active palette as a 256x1 PNG strip, the same layout palette.png is loaded from.
+    pub fn request_palette_export(&mut self, path: PathBuf) {
+        self.palette_export_request = Some(path);
+    }
+
+    pub fn is_readback_pending(&self) -> bool {
+        self.screenshot_request.is_some()
+            || self.texture_dump_request.is_some()
+            || self.palette_export_request.is_some()
+            || !self.pending_readbacks.is_empty()
+            || self.pending_probe.is_some()
+            || (self.probe_requested && self.probe_point.is_some())
+    }
+
+    /// Reads the iteration count at `point` back for display, `None` stops probing.
+    pub fn set_probe_point(&mut self, point: Option<DVec2>) {
+        self.probe_point = point;
+        self.probe_requested = true;
+        if point.is_none() {
+            self.probed_count = None;
+        }
+    }
+
+    pub fn is_probing(&self) -> bool {
+        self.probe_point.is_some()
+    }
+
+    /// Raw count of the last probed texel, see `Pixel::count`.
+    pub fn probed_count(&self) -> Option<u16> {
+        self.probed_count
+    }
+
+    // one probe in flight at a time, later points wait and only the latest is read
+    fn start_probe(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if !self.probe_requested || self.pending_probe.is_some() {
+            return;
+        }
+        self.probe_requested = false;
+        let Some(point) = self.probe_point else {
+            return;
+        };
+
+        let texel = plane_to_texel(self.fractal_rect, self.texture_size, point);
+        if texel.min_element() < 0.0 || texel.cmpge(DVec2::from(self.texture_size)).any() {
+            self.probed_count = None;
+            return;
+        }
+        let texel = texel.as_uvec2();
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: size_of::<Pixel>() as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut command_encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        command_encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.textures[0].texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: texel.x,
+                    y: texel.y,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: None,
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(Some(command_encoder.finish()));
+
+        self.pending_probe = Some(TexelProbe::new(buffer));
+    }
+
+    /// Starts the next texel probe and checks on the readbacks without blocking,
+    /// the PNGs are encoded and written in the background.
+    pub fn poll_readbacks(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.start_probe(device, queue);
+        if self.pending_readbacks.is_empty() && self.pending_probe.is_none() {
+            return;
+        }
+
+        device.poll(wgpu::Maintain::Poll);
+
+        if self
+            .pending_probe
+            .as_ref()
+            .is_some_and(TexelProbe::is_mapped)
+        {
+            let probe = self.pending_probe.take().unwrap();
+            match probe.take_count() {
+                Ok(count) => self.probed_count = Some(count),
+                Err(err) => println!("Texel probe failed: {}", err),
+            }
+        }
+
+        let (mapped, pending) = std::mem::take(&mut self.pending_readbacks)
+            .into_iter()
+            .partition(|readback| readback.is_mapped());
+        self.pending_readbacks = pending;
+
+        for readback in mapped {
+            let path = readback.path().clone();
+            let image = match readback.take_image() {
+                Ok(image) => image,
+                Err(err) => {
+                    println!("Readback of {} failed: {}", path.display(), err);
+                    continue;
+                }
+            };
+
+            self.runtime
+                .spawn_blocking(move || match image.save(&path) {
+                    Ok(()) => println!("Saved {}", path.display()),
+                    Err(err) => println!("Failed to save {}: {}", path.display(), err),
+                });
+        }
+    }
+
+    pub fn show_previous_texture(&self) -> bool {
+        self.show_previous_texture
+    }
+
+    /// Debug: shows the texture the last blit read from instead of the one it wrote.
+    pub fn set_show_previous_texture(&mut self, show_previous_texture: bool) {
+        self.show_previous_texture = show_previous_texture;
+    }
+
+    pub fn resize_window(&mut self, window_size: UVec2) {
+        self.window_size = window_size;
+    }
+
+    pub fn set_fractal(&mut self, fractal: Fractal) {
+        self.fractal = fractal;
+    }
+
+    /// The part of the plane shown in the window, as passed to the last `update`.
+    pub fn frame_rect(&self) -> DRect {
+        self.frame_rect
+    }
+
+    /// The part of the plane the texture covers, the frame rect grown to the texture's size.
+    pub fn fractal_rect(&self) -> DRect {
+        self.fractal_rect
+    }
+
+    pub fn texture_size(&self) -> UVec2 {
+        self.texture_size
+    }
+
+    /// The texels the window shows, row 0 at the bottom of the plane like the texture's.
+    pub fn frame_texel_rect(&self) -> DRect {
+        texel_rect(self.fractal_rect, self.texture_size, self.frame_rect)
+    }
+
+    pub fn max_iters(&self) -> u32 {
+        if let Some(iter_lock) = self.iter_lock {
+            return iter_lock;
+        }
+
+        let max_iters = self.iter_override.unwrap_or_else(|| {
+            let doublings = detail_split(self.detail, self.frame_rect).0;
+            (calc_max_iters(self.fractal_rect, self.iter_cap) << doublings).min(self.iter_cap)
+        });
+
+        let max_iters = if self.quality_boost {
+            (max_iters * QUALITY_BOOST_ITERS).min(self.iter_cap)
+        } else {
+            max_iters
+        };
+
+        match self.warmup_iters {
+            Some(warmup_iters) => max_iters.min(warmup_iters),
+            None => max_iters,
+        }
+    }
+
+    /// The plane point at the center of the window's busiest region, where the counts change
+    /// the most within `radius` cells of `SAMPLE_STRIDE` texels. `None` while nothing in view
+    /// has data or the view is a single count.
+    pub fn detail_peak(&self, radius: u32) -> Option<DVec2> {
+        let stride = SAMPLE_STRIDE as u32;
+        let cell_count = self.texture_size / stride;
+        let frame = self.frame_texel_rect();
+        let first = (frame.pos / stride as f64)
+            .floor()
+            .max(DVec2::ZERO)
+            .as_uvec2()
+            .min(cell_count);
+        let last = ((frame.pos + frame.size) / stride as f64)
+            .ceil()
+            .max(DVec2::ZERO)
+            .as_uvec2()
+            .min(cell_count);
+        let size = last.saturating_sub(first);
+        if size.min_element() == 0 {
+            return None;
+        }
+
+        let mut gradients = vec![0u32; (size.x * size.y) as usize];
+        let tile_cells = TILE_SIZE / stride;
+        for tile in &self.tiles {
+            let tile_first = tile.tex_rect.pos / stride;
+            for (index, &gradient) in tile.gradients.iter().enumerate() {
+                let index = index as u32;
+                let cell = tile_first + UVec2::new(index % tile_cells, index / tile_cells);
+                if cell.cmpge(first).all() && cell.cmplt(last).all() {
+                    let cell = cell - first;
+                    gradients[(cell.y * size.x + cell.x) as usize] = gradient;
+                }
+            }
+        }
+
+        let cell = busiest_cell(&gradients, size.x as usize, radius as usize)?;
+        let texel = ((first + cell).as_dvec2() + 0.5) * stride as f64;
+        Some(self.fractal_rect.pos + texel / self.texture_size.as_dvec2() * self.fractal_rect.size)
+    }
+
+    /// True once the view is complete and practically a single color, e.g. entirely interior.
+    pub fn uniform_view(&self) -> bool {
+        self.uniform_view
+    }
+
+    /// True once no tile is computing or waiting for its upload, out of view tiles are cancelled.
+    pub fn is_view_complete(&self) -> bool {
+        self.tiles
+            .iter()
+            .all(|tile| matches!(*tile.state.lock(), TileState::Idle | TileState::Failed))
+    }
+
+    pub fn compute_stats(&self) -> ComputeStats {
+        let pending = self
+            .tiles
+            .iter()
+            .filter(|tile| tile.state.lock().is_computing())
+            .count();
+        // every tile holding a permit is running
+        let running = self.compute_permits - self.semaphore.available_permits()
+            + (1 - self.slow_semaphore.available_permits());
+        let computing = running.min(pending);
+
+        ComputeStats {
+            computing,
+            queued: pending - computing,
+            buffers_taken: self.buf_pool.taken_buffer_count(),
+        }
+    }
+
+    /// Tiles whose last compute failed, they stay blank until the next update.
+    pub fn failed_tile_count(&self) -> usize {
+        self.tiles
+            .iter()
+            .filter(|tile| matches!(*tile.state.lock(), TileState::Failed))
+            .count()
+    }
+
+    pub fn quality_boost(&self) -> bool {
+        self.quality_boost
+    }
+
+    pub fn set_quality_boost(&mut self, quality_boost: bool) {
+        self.quality_boost = quality_boost;
+    }
+
+    pub fn is_warming_up(&self) -> bool {
+        self.warmup_iters.is_some()
+    }
+
+    pub fn set_warmup_iters(&mut self, warmup_iters: Option<u32>) {
+        self.warmup_iters = warmup_iters;
+    }
+
+    pub fn iter_override(&self) -> Option<u32> {
+        self.iter_override
+    }
+
+    pub fn set_iter_override(&mut self, iter_override: Option<u32>) {
+        self.iter_override = iter_override.map(|iters| iters.clamp(MIN_ITER, self.iter_cap));
+    }
+
+    pub fn iters_locked(&self) -> bool {
+        self.iter_lock.is_some()
+    }
+
+    /// Pins the iteration count to the current one, zooming and the idle boost leave it alone.
+    pub fn set_iters_locked(&mut self, locked: bool) {
+        self.iter_lock = None;
+        if locked {
+            self.iter_lock = Some(self.max_iters());
+        }
+    }
+
+    pub fn iter_cap(&self) -> u32 {
+        self.iter_cap
+    }
+
+    /// Limits adaptive and manual iteration counts, at most what the iteration texture can store.
+    pub fn set_iter_cap(&mut self, iter_cap: u32) {
+        self.iter_cap = iter_cap.clamp(MIN_ITER, self.iter_storage.iter_limit());
+        self.set_iter_override(self.iter_override);
+        self.iter_lock = self.iter_lock.map(|iters| iters.min(self.iter_cap));
+    }
+
+    pub fn iter_storage(&self) -> IterStorage {
+        self.iter_storage
+    }
+
+    /// Changes how counts are stored, tiles computed before keep the old encoding until redone.
+    pub fn set_iter_storage(&mut self, iter_storage: IterStorage) {
+        self.iter_storage = iter_storage;
+        self.set_iter_cap(self.iter_cap);
+    }
+
+    pub fn set_mip_blend(&mut self, mip_blend: MipBlend) {
+        self.mip_blend = mip_blend;
+        self.textures[0].mips_dirty = true;
+    }
+
+    pub fn detect_glitches(&self) -> bool {
+        self.detect_glitches
+    }
+
+    pub fn set_detect_glitches(&mut self, detect_glitches: bool) {
+        self.detect_glitches = detect_glitches;
+    }
+
+    pub fn set_tile_time_budget(&mut self, tile_time_budget: Option<Duration>) {
+        self.tile_time_budget = tile_time_budget;
+    }
+
+    /// Computes tiles in square pieces of this side, rounded to a power of two. Smaller pieces
+    /// show up and stop sooner, the texture's tiles stay the same. The time budget is per piece.
+    pub fn set_sub_tile_size(&mut self, sub_tile_size: u32) {
+        self.sub_tile_size = sub_tile_size
+            .clamp(MIN_SUB_TILE_SIZE, MAX_SUB_TILE_SIZE)
+            .next_power_of_two();
+    }
+
+    /// Computes tiles that come back at least half interior again with `interior_boost` times the
+    /// iterations, up to the cap, so the bulbs are confirmed without the higher count everywhere.
+    pub fn set_interior_boost(&mut self, interior_boost: u32) {
+        self.interior_boost = interior_boost.clamp(1, MAX_INTERIOR_BOOST);
+    }
+
+    // a locked count and the warmup pass stay as they are
+    fn interior_boost_iters(&self, max_iters: u32) -> u32 {
+        if self.iter_lock.is_some() || self.warmup_iters.is_some() {
+            return max_iters;
+        }
+
+        (max_iters * self.interior_boost)
+            .min(self.iter_cap)
+            .max(max_iters)
+    }
+
+    /// Until when the last presented frame should stay on screen, after a zoom too large to carry
+    /// it over. `None` once that passed or the tiles in view are all in, or without such a zoom.
+    pub fn frame_hold(&self) -> Option<Instant> {
+        let hold_until = self.hold_until.filter(|&until| Instant::now() < until)?;
+        self.tiles
+            .iter()
+            .any(|tile| tile.state.lock().is_computing())
+            .then_some(hold_until)
+    }
+
+    /// Largest zoom in one frame that still carries the previous frame over, magnified.
+    pub fn set_max_blit_zoom(&mut self, max_blit_zoom: f64) {
+        self.max_blit_zoom = max_blit_zoom;
+    }
+
+    pub fn set_supersample(&mut self, supersample: Option<SamplePattern>) {
+        self.supersample = supersample;
+    }
+
+    pub fn texture_count(&self) -> usize {
+        self.texture_count
+    }
+
+    /// Blits into the oldest of `texture_count` textures, more than two keep a texture the
+    /// previous frames read from out of the next blit's way.
+    pub fn set_texture_count(&mut self, texture_count: usize) {
+        self.texture_count = texture_count.clamp(MIN_TEXTURE_COUNT, MAX_TEXTURE_COUNT);
+    }
+
+    /// Supersamples with `settle_supersample` once the idle boost is on, `--supersample` wins.
+    pub fn set_settle_supersample(&mut self, settle_supersample: Option<SamplePattern>) {
+        self.settle_supersample = settle_supersample;
+    }
+
+    pub fn detail(&self) -> u32 {
+        self.detail
+    }
+
+    /// Clamped to `MAX_DETAIL`, manual iterations and `--supersample` still take precedence.
+    pub fn set_detail(&mut self, detail: u32) {
+        self.detail = detail.min(MAX_DETAIL);
+    }
+
+    pub fn kernel(&self) -> Kernel {
+        self.kernel
+    }
+
+    /// Takes effect for tiles scheduled from now on.
+    pub fn set_kernel(&mut self, kernel: Kernel) {
+        self.kernel = kernel;
+    }
+
+    pub fn set_measure_tile_timings(&mut self, measure: bool) {
+        self.tile_timings = measure.then(Default::default);
+    }
+
+    /// Summarizes the tile compute times recorded since the previous call.
+    pub fn take_tile_timings(&mut self) -> Option<TimingSummary> {
+        let durations = std::mem::take(&mut *self.tile_timings.as_ref()?.lock());
+        TimingSummary::from_durations(&durations)
+    }
+
+    pub fn show_legend(&self) -> bool {
+        self.show_legend
+    }
+
+    pub fn set_show_legend(&mut self, show_legend: bool) {
+        self.show_legend = show_legend;
+    }
+
+    pub fn show_boundary(&self) -> bool {
+        self.show_boundary
+    }
+
+    pub fn set_show_boundary(&mut self, show_boundary: bool) {
+        self.show_boundary = show_boundary;
+    }
+
+    pub fn set_boundary_color(&mut self, boundary_color: Vec4) {
+        self.boundary_color = boundary_color;
+    }
+
+    pub fn show_crosshair(&self) -> bool {
+        self.show_crosshair
+    }
+
+    pub fn set_show_crosshair(&mut self, show_crosshair: bool) {
+        self.show_crosshair = show_crosshair;
+    }
+
+    pub fn set_crosshair_color(&mut self, crosshair_color: Vec4) {
+        self.crosshair_color = crosshair_color;
+    }
+
+    /// The `z` values of the shown orbit, see `Fractal::orbit`.
+    pub fn orbit(&self) -> Option<&[DVec2]> {
+        self.orbit.as_ref().map(|orbit| orbit.points.as_slice())
+    }
+
+    /// Draws `points` as connected segments, `point` is where the orbit was clicked.
+    pub fn set_orbit(&mut self, point: DVec2, points: Vec<DVec2>) {
+        self.orbit = Some(Polyline {
+            origin: point,
+            points,
+            vertex_buf: None,
+        });
+    }
+
+    pub fn clear_orbit(&mut self) {
+        self.orbit = None;
+    }
+
+    /// Draws the measured segment from `start` to `end`, `None` hides it.
+    pub fn set_measure(&mut self, segment: Option<(DVec2, DVec2)>) {
+        self.measure = segment.map(|(start, end)| Polyline {
+            origin: start,
+            points: vec![start, end],
+            vertex_buf: None,
+        });
+    }
+
+    pub fn interior_coloring(&self) -> InteriorColoring {
+        self.interior_coloring
+    }
+
+    /// Shader-only, the texture always holds the interior phase.
+    pub fn set_interior_coloring(&mut self, interior_coloring: InteriorColoring) {
+        self.interior_coloring = interior_coloring;
+    }
+
+    pub fn interior_color(&self) -> Vec4 {
+        self.interior_color
+    }
+
+    /// The solid interior's color, the phase coloring still uses the palette.
+    pub fn set_interior_color(&mut self, interior_color: Vec4) {
+        self.interior_color = interior_color;
+    }
+
+    pub fn color_offset(&self) -> f32 {
+        self.color_offset
+    }
+
+    /// Shifts the escapes along the palette, a gap between the interior and the first bands.
+    pub fn set_color_offset(&mut self, color_offset: f32) {
+        self.color_offset = color_offset;
+    }
+
+    pub fn coloring_mode(&self) -> ColoringMode {
+        self.coloring_mode
+    }
+
+    /// Shader-only as well, every escape keeps the |z| smooth coloring needs.
+    pub fn set_coloring_mode(&mut self, coloring_mode: ColoringMode) {
+        self.coloring_mode = coloring_mode;
+    }
+
+    pub fn palette_period(&self) -> u32 {
+        self.palette_period
+    }
+
+    /// Iterations the palette spans before it repeats, how tight the bands are at any zoom.
+    /// Shader-only like the color offset.
+    pub fn set_palette_period(&mut self, palette_period: u32) {
+        self.palette_period = palette_period.clamp(MIN_PALETTE_PERIOD, MAX_PALETTE_PERIOD);
+    }
+
+    pub fn band_aa(&self) -> bool {
+        self.band_aa
+    }
+
+    /// Softens band edges by blurring each pixel's palette lookup over how much the iterations
+    /// change across it, much cheaper than supersampling. Off keeps the bands crisp.
+    pub fn set_band_aa(&mut self, band_aa: bool) {
+        self.band_aa = band_aa;
+    }
+
+    pub fn render_scale(&self) -> f64 {
+        self.render_scale
+    }
+
+    /// Resizes the texture and the tile grid to `render_scale` compute texels per window pixel,
+    /// the GPU textures follow on the next render.
+    pub fn set_render_scale(&mut self, render_scale: f64) {
+        self.render_scale = render_scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+
+        let texture_size = scaled_texture_size(
+            self.base_texture_size,
+            self.render_scale,
+            self.max_texture_dimension,
+        );
+        if texture_size != self.texture_size {
+            self.cancel_tiles();
+            self.tiles = create_tiles(texture_size);
+            self.texture_size = texture_size;
+            // the next update recomputes every tile, even if the covered area stays the same
+            self.fractal_rect = DRect::zeroed();
+        }
+    }
+
+    pub fn msaa_samples(&self) -> u32 {
+        self.msaa_samples
+    }
+
+    pub fn set_msaa_samples(&mut self, msaa_samples: u32) {
+        self.msaa_samples = msaa_samples;
+        self.msaa_view = None;
+    }
+
+    /// The format of the views drawn into, the pipelines and the MSAA target follow next frame.
+    pub fn set_surface_format(&mut self, surface_format: wgpu::TextureFormat) {
+        self.surface_format = surface_format;
+        self.msaa_view = None;
+    }
+
+    // recreates the iteration textures after a render scale change, nothing is carried over,
+    // a new ring length only adds or drops textures behind the drawn one
+    fn prepare_textures(&mut self, render_info: &RenderContext) {
+        let size = UVec2::new(
+            self.textures[0].texture.width(),
+            self.textures[0].texture.height(),
+        );
+        if size == self.texture_size && self.textures.len() == self.texture_count {
+            return;
+        }
+
+        let palette_views =
+            PaletteViews::new(&self.palette_texture, &self.previous_palette_texture);
+        let create_texture = || {
+            TileTexture::new(
+                render_info.device,
+                self.texture_size,
+                &self.bind_group_layout,
+                &self.mip_bind_group_layout,
+                &self.sampler,
+                &palette_views,
+            )
+        };
+        if size == self.texture_size {
+            self.textures.truncate(self.texture_count);
+            while self.textures.len() < self.texture_count {
+                self.textures.push(create_texture());
+            }
+            return;
+        }
+
+        self.textures = (0..self.texture_count).map(|_| create_texture()).collect();
+        self.screen_rect_buf =
+            render_info
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    contents: ScreenRect::with_texture_size(self.texture_size).as_bytes(),
+                    usage: wgpu::BufferUsages::VERTEX,
+                    label: None,
+                });
+
+        // the new textures start empty, there is nothing to blit
+        self.frame_changed = false;
+        self.fractal_rect_prev = self.fractal_rect;
+    }
+
+    fn prepare_polylines(&mut self, render_info: &RenderContext) {
+        let polylines = [self.orbit.as_mut(), self.measure.as_mut()];
+        for polyline in polylines.into_iter().flatten() {
+            if polyline.vertex_buf.is_some() {
+                continue;
+            }
+
+            let vertices: Vec<Vec2> = polyline
+                .points
+                .iter()
+                .map(|point| (*point - polyline.origin).as_vec2())
+                .collect();
+            polyline.vertex_buf = Some(render_info.device.create_buffer_init(
+                &wgpu::util::BufferInitDescriptor {
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                    label: None,
+                },
+            ));
+        }
+    }
+
+    fn prepare_msaa(&mut self, render_info: &RenderContext) {
+        if self.surface_pipelines_for != (self.surface_format, self.msaa_samples) {
+            self.surface_pipelines = SurfacePipelines::new(
+                render_info.device,
+                &self.screen_pipeline_layout,
+                &self.screen_shader,
+                self.surface_format,
+                self.msaa_samples,
+            );
+            self.surface_pipelines_for = (self.surface_format, self.msaa_samples);
+        }
+
+        let size_changed = self
+            .msaa_view
+            .as_ref()
+            .is_some_and(|(size, _)| *size != render_info.view_size);
+        if size_changed {
+            self.msaa_view = None;
+        }
+        if self.msaa_samples > 1 && self.msaa_view.is_none() {
+            let msaa_texture = render_info.device.create_texture(&wgpu::TextureDescriptor {
+                size: wgpu::Extent3d {
+                    width: render_info.view_size.x,
+                    height: render_info.view_size.y,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: self.msaa_samples,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.surface_format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+                label: None,
+            });
+            self.msaa_view = Some((
+                render_info.view_size,
+                msaa_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            ));
+        }
+    }
+}
+
+impl PaletteViews {
+    fn new(current: &wgpu::Texture, previous: &wgpu::Texture) -> Self {
+        Self {
+            current: current.create_view(&wgpu::TextureViewDescriptor::default()),
+            previous: previous.create_view(&wgpu::TextureViewDescriptor::default()),
+        }
+    }
+}
+
+impl TileTexture {
+    fn new(
+        device: &wgpu::Device,
+        texture_size: UVec2,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        mip_bind_group_layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        palette_views: &PaletteViews,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: texture_size.x,
+                height: texture_size.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: MIP_LEVEL_COUNT,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rg16Uint,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+            label: None,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&palette_views.current),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&palette_views.previous),
+                },
+            ],
+            label: None,
+        });
+
+        let mip_views: Vec<wgpu::TextureView> = (0..MIP_LEVEL_COUNT)
+            .map(|level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    base_mip_level: level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        let mip_bind_groups = mip_views[..mip_views.len() - 1]
+            .iter()
+            .map(|mip_view| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: mip_bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(mip_view),
+                    }],
+                    label: None,
+                })
+            })
+            .collect();
+
+        Self {
+            texture,
+            bind_group,
+            mip_views,
+            mip_bind_groups,
+            mips_dirty: true,
+        }
+    }
+}
+
+// places the previous texture's quad where its rect lies within the current one, scaled per axis
+// so a resize that changes the aspect lines up as exactly as a zoom
+fn blit_transform(prev: DRect, cur: DRect) -> Mat4 {
+    let offset = 2.0 * (prev.center() - cur.center()) / cur.size;
+    let scale = prev.size / cur.size;
+
+    // texture rows go up the plane, NDC y down the target's rows
+    Mat4::from_translation(Vec3::new(offset.x as f32, -offset.y as f32, 0.0))
+        * Mat4::from_scale(Vec3::new(scale.x as f32, scale.y as f32, 1.0))
+}
+
+// from the plane relative to `origin` to NDC of `frame_rect`, the plane's y up like NDC's
+fn orbit_transform(origin: DVec2, frame_rect: DRect) -> Mat4 {
+    let offset = 2.0 * (origin - frame_rect.center()) / frame_rect.size;
+    let scale = 2.0 / frame_rect.size;
+
+    Mat4::from_translation(Vec3::new(offset.x as f32, offset.y as f32, 0.0))
+        * Mat4::from_scale(Vec3::new(scale.x as f32, scale.y as f32, 1.0))
+}
+
+// `mix` of the way from `from` to `to`, what the shader's `sample_palette` shows
+fn blend_palettes(from: &[[u8; 4]], to: &[[u8; 4]], mix: f32) -> Vec<[u8; 4]> {
+    from.iter()
+        .zip(to)
+        .map(|(from, to)| {
+            std::array::from_fn(|channel| {
+                let (from, to) = (from[channel] as f32, to[channel] as f32);
+                (from + (to - from) * mix).round() as u8
+            })
+        })
+        .collect()
+}
+
+fn write_palette(queue: &wgpu::Queue, texture: &wgpu::Texture, colors: &[[u8; 4]]) {
+    queue.write_texture(
+        texture.as_image_copy(),
+        bytemuck::cast_slice(colors),
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(PALETTE_SIZE as u32 * 4),
+            rows_per_image: Some(1),
+        },
+        wgpu::Extent3d {
+            width: PALETTE_SIZE as u32,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+fn vertex_buffer_layouts() -> [wgpu::VertexBufferLayout<'static>; 1] {
+    [wgpu::VertexBufferLayout {
+        array_stride: ScreenRect::vert_size() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &VERTEX_ATTRIBUTES,
+    }]
+}
+
+fn polyline_buffer_layouts() -> [wgpu::VertexBufferLayout<'static>; 1] {
+    [wgpu::VertexBufferLayout {
+        array_stride: size_of::<Vec2>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &ORBIT_VERTEX_ATTRIBUTES,
+    }]
+}
+
+impl SurfacePipelines {
+    fn new(
+        device: &wgpu::Device,
+        pipeline_layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        surface_format: wgpu::TextureFormat,
+        msaa_samples: u32,
+    ) -> SurfacePipelines {
+        let create = |entry_points, vertex_buffers: &[wgpu::VertexBufferLayout], topology| {
+            create_surface_pipeline(
+                device,
+                pipeline_layout,
+                shader,
+                entry_points,
+                vertex_buffers,
+                topology,
+                (surface_format, msaa_samples),
+            )
+        };
+        let strip = wgpu::PrimitiveTopology::TriangleStrip;
+
+        SurfacePipelines {
+            screen: create(("vs_main", "fs_main"), &vertex_buffer_layouts(), strip),
+            boundary: create(
+                ("vs_boundary", "fs_boundary"),
+                &vertex_buffer_layouts(),
+                strip,
+            ),
+            legend: create(("vs_legend", "fs_legend"), &[], strip),
+            crosshair: create(("vs_crosshair", "fs_crosshair"), &[], strip),
+            orbit: create(
+                ("vs_orbit", "fs_orbit"),
+                &polyline_buffer_layouts(),
+                wgpu::PrimitiveTopology::LineStrip,
+            ),
+            measure: create(
+                ("vs_orbit", "fs_measure"),
+                &polyline_buffer_layouts(),
+                wgpu::PrimitiveTopology::LineStrip,
+            ),
+        }
+    }
+}
+
+fn create_surface_pipeline(
+    device: &wgpu::Device,
+    pipeline_layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    (vs_entry, fs_entry): (&str, &str),
+    vertex_buffers: &[wgpu::VertexBufferLayout],
+    topology: wgpu::PrimitiveTopology,
+    (surface_format, msaa_samples): (wgpu::TextureFormat, u32),
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: None,
+        layout: Some(pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some(vs_entry),
+            compilation_options: Default::default(),
+            buffers: vertex_buffers,
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some(fs_entry),
+            compilation_options: Default::default(),
+            targets: &[Some(surface_format.into())],
+        }),
+        primitive: wgpu::PrimitiveState {
+            cull_mode: None,
+            front_face: wgpu::FrontFace::Cw,
+            topology,
+
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: msaa_samples,
+            ..Default::default()
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
+impl Tile {
+    pub(crate) fn fractal_rect(&self, tex_size: UVec2, fractal_rect: DRect) -> DRect {
+        let abs_frame_size = DVec2::from(tex_size);
+        let abs_tile_pos = DVec2::from(self.tex_rect.pos);
+        let abs_tile_size = DVec2::from(self.tex_rect.size);
+
+        let tile_size = fractal_rect.size * abs_tile_size / abs_frame_size;
+        let tile_pos = fractal_rect.pos + fractal_rect.size * abs_tile_pos / abs_frame_size;
+
+        DRect::from_pos_size(tile_pos, tile_size)
+    }
+}
+
+impl TimingSummary {
+    pub fn from_durations(durations: &[Duration]) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+
+        let mut sorted = durations.to_vec();
+        sorted.sort_unstable();
+        let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+
+        Some(Self {
+            count: sorted.len(),
+            min: sorted[0],
+            median: percentile(0.5),
+            p99: percentile(0.99),
+            max: sorted[sorted.len() - 1],
+            total: sorted.iter().sum(),
+        })
+    }
+}
+
+impl std::fmt::Display for TimingSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "tiles: {}, min: {:.2?}, median: {:.2?}, p99: {:.2?}, max: {:.2?}, total: {:.2?}",
+            self.count, self.min, self.median, self.p99, self.max, self.total
+        )
+    }
+}
+
+impl TileState {
+    fn cancel(&mut self) {
+        if let TileState::Computing {
+            task_handle,
+            cancel_token,
+            ..
+        } = self
+        {
+            cancel_token.store(true, std::sync::atomic::Ordering::Relaxed);
+            task_handle.abort();
+        }
+
+        *self = TileState::Idle;
+    }
+
+    fn is_computing(&self) -> bool {
+        matches!(self, TileState::Computing { .. })
+    }
+
+    // when panning within the texture, a tile already in progress computes the same texels
+    fn needs_spawn(&self, frame_changed: bool) -> bool {
+        frame_changed || !self.is_computing()
+    }
+}
+
+// the plane rect of a texture centered on `frame_rect` at `render_scale` texels per window pixel
+fn texture_fractal_rect(
+    frame_rect: DRect,
+    texture_size: UVec2,
+    window_size: UVec2,
+    render_scale: f64,
+) -> DRect {
+    let texels = texture_size.as_dvec2() / (window_size.as_dvec2() * render_scale);
+    DRect::from_center_size(frame_rect.center(), frame_rect.size * texels)
+}
+
+// the rect the texture's content is blitted from when the view changes away from `fractal_rect`,
+// `held` is the rect of the last blit, which the texture keeps until the pending one
+fn blit_source(held: DRect, fractal_rect: DRect, blit_pending: bool) -> DRect {
+    match blit_pending {
+        true => held,
+        false => fractal_rect,
+    }
+}
+
+// whether the texture at `fractal_rect` no longer covers `frame_rect` at the same scale,
+// `new_fractal_rect` is where it would go, and the tiles start over
+fn is_frame_changed(fractal_rect: DRect, new_fractal_rect: DRect, frame_rect: DRect) -> bool {
+    !fractal_rect.contains(&frame_rect) || fractal_rect.size != new_fractal_rect.size
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn texture_size_errors_explain_the_constraint() {
+        assert!(validate_texture_size(TEXTURE_SIZE, 8192).is_ok());
+        assert!(validate_texture_size(UVec2::new(2048, 1024), 8192).is_ok());
+
+        let error = validate_texture_size(UVec2::new(4096, 512), 8192).unwrap_err();
+        assert!(error.to_string().contains("at least 1024"), "{}", error);
+
+        let error = validate_texture_size(UVec2::new(4096, 2000), 8192).unwrap_err();
+        assert!(
+            error.to_string().contains("multiple of the 128"),
+            "{}",
+            error
+        );
+
+        let error = validate_texture_size(UVec2::new(16384, 4096), 8192).unwrap_err();
+        assert!(
+            error.to_string().contains("device limit of 8192"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn detail_goes_to_edges_when_shallow_and_iterations_when_deep() {
+        let shallow = DRect::from_center_size(DVec2::new(-0.5, 0.0), DVec2::new(3.0, 2.0));
+        let deep = DRect::from_center_size(DVec2::new(-0.5, 0.0), DVec2::new(3e-6, 2e-6));
+
+        assert_eq!(detail_split(0, shallow), (0, false));
+        assert_eq!(detail_split(0, deep), (0, false));
+        assert_eq!(detail_split(1, shallow), (0, true));
+        assert_eq!(detail_split(1, deep), (1, false));
+        assert_eq!(detail_split(MAX_DETAIL, shallow), (MAX_DETAIL - 1, true));
+        assert_eq!(detail_split(MAX_DETAIL, deep), (MAX_DETAIL - 1, true));
+        assert_eq!(detail_split(99, deep), detail_split(MAX_DETAIL, deep));
+    }
+
+    #[test]
+    fn frame_maps_to_the_middle_texels() {
+        // a 1024x512 window at render scale 1 in a 4096 texture, as `update` sizes the rects
+        let frame_rect = DRect::from_center_size(DVec2::new(-0.5, 0.25), DVec2::new(2.0, 1.0));
+        let fractal_rect =
+            DRect::from_center_size(frame_rect.center(), DVec2::new(2.0 * 4.0, 1.0 * 8.0));
+
+        let texels = texel_rect(fractal_rect, TEXTURE_SIZE, frame_rect);
+        assert_eq!(texels.pos, DVec2::new(1536.0, 1792.0));
+        assert_eq!(texels.size, DVec2::new(1024.0, 512.0));
+
+        // the bottom left of the plane is texel 0, the top right the far corner
+        assert_eq!(
+            plane_to_texel(fractal_rect, TEXTURE_SIZE, fractal_rect.pos),
+            DVec2::ZERO
+        );
+        assert_eq!(
+            plane_to_texel(fractal_rect, TEXTURE_SIZE, fractal_rect.upper_right()),
+            DVec2::from(TEXTURE_SIZE)
+        );
+    }
+
+    #[test]
+    fn large_textures_keep_their_upload_layout() {
+        let texture_size = UVec2::splat(65536);
+        assert!(validate_texture_size(texture_size, 65536).is_ok());
+
+        // the last tile sits at the far corner, not wrapped back to the origin
+        let tiles = create_tiles(texture_size);
+        assert_eq!(tiles.len(), 512 * 512);
+        let last = tiles.last().unwrap();
+        assert_eq!(last.tex_rect.pos, texture_size - TILE_SIZE);
+        let layout = pixel_copy_layout(last.tex_rect.size);
+        assert_eq!(
+            layout.bytes_per_row,
+            Some(TILE_SIZE * size_of::<Pixel>() as u32)
+        );
+
+        // a full width row of a still past 4 GiB in total
+        let layout = pixel_copy_layout(texture_size);
+        assert_eq!(
+            layout.bytes_per_row,
+            Some(65536 * size_of::<Pixel>() as u32)
+        );
+        assert_eq!(
+            layout.bytes_per_row.unwrap() as u64 * layout.rows_per_image.unwrap() as u64,
+            1 << 34
+        );
+
+        let wide = UVec2::new(u32::MAX / 2, 1);
+        assert!(std::panic::catch_unwind(|| pixel_copy_layout(wide)).is_err());
+    }
+
+    #[test]
+    fn failing_compute_marks_the_tile_for_retry() {
+        let tile_state = Mutex::new(TileState::Idle);
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+
+        let status = catch_panic(|| panic!("injected kernel failure"));
+        assert!(status.is_err());
+        assert!(finish_tile(
+            &tile_state,
+            0,
+            status,
+            buffer.clone(),
+            Vec::new()
+        ));
+        assert!(matches!(*tile_state.lock(), TileState::Failed));
+        // not computing, so the next update schedules it again
+        assert!(!tile_state.lock().is_computing());
+
+        let status = catch_panic(|| Err(anyhow!("out of buffers")));
+        assert!(finish_tile(
+            &tile_state,
+            0,
+            status,
+            buffer.clone(),
+            Vec::new()
+        ));
+        assert!(matches!(*tile_state.lock(), TileState::Failed));
+
+        *tile_state.lock() = TileState::Idle;
+        assert!(!finish_tile(
+            &tile_state,
+            0,
+            Ok(TileStatus::Cancelled),
+            buffer.clone(),
+            Vec::new()
+        ));
+        assert!(matches!(*tile_state.lock(), TileState::Idle));
+
+        assert!(finish_tile(
+            &tile_state,
+            0,
+            Ok(TileStatus::Complete),
+            buffer,
+            Vec::new()
+        ));
+        assert!(matches!(
+            *tile_state.lock(),
+            TileState::WaitForUpload { .. }
+        ));
+    }
+
+    #[test]
+    fn unchanged_frame_keeps_the_tiles_in_progress() {
+        let fractal_rect = DRect::from_center_size(DVec2::ZERO, DVec2::splat(4.0));
+        let frame_rect = DRect::from_center_size(DVec2::ZERO, DVec2::splat(2.0));
+        let panned = DRect::from_center_size(DVec2::new(0.5, -0.5), DVec2::splat(2.0));
+        let recentered = DRect::from_center_size(panned.center(), fractal_rect.size);
+        assert!(!is_frame_changed(fractal_rect, fractal_rect, frame_rect));
+        assert!(!is_frame_changed(fractal_rect, recentered, panned));
+
+        // past the texture's edge, or zoomed, it's recomputed around the new frame
+        let beyond = DRect::from_center_size(DVec2::new(1.5, 0.0), DVec2::splat(2.0));
+        assert!(is_frame_changed(fractal_rect, recentered, beyond));
+        let zoomed = DRect::from_center_size(DVec2::ZERO, DVec2::splat(2.0));
+        assert!(is_frame_changed(fractal_rect, zoomed, frame_rect));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let computing = TileState::Computing {
+            task_handle: runtime.spawn(async {}),
+            cancel_token: Arc::new(AtomicBool::new(false)),
+            parts: Vec::new(),
+        };
+        assert!(!computing.needs_spawn(false));
+        assert!(computing.needs_spawn(true));
+        for state in [TileState::Idle, TileState::Failed] {
+            assert!(state.needs_spawn(false));
+        }
+    }
+
+    #[test]
+    fn palette_switch_mid_fade_starts_from_the_blend() {
+        let from = [[0, 100, 200, 255]];
+        let to = [[200, 100, 0, 255]];
+        assert_eq!(blend_palettes(&from, &to, 0.0), from);
+        assert_eq!(blend_palettes(&from, &to, 0.25), [[50, 100, 150, 255]]);
+        assert_eq!(blend_palettes(&from, &to, 1.0), to);
+    }
+
+    #[test]
+    fn sampled_counts_skip_glitches() {
+        let width = 2 * SAMPLE_STRIDE as u32;
+        // count and phase per texel, as uploaded
+        let mut texels = vec![[0u16; 2]; (width * width) as usize];
+        texels[0] = [u16::MAX, 0];
+        texels[SAMPLE_STRIDE] = [7, 0];
+        texels[SAMPLE_STRIDE + 1] = [9, 0];
+
+        // four sample points, one of them a glitch, the off-stride texel is never looked at
+        let pixels: &[Pixel] = bytemuck::cast_slice(&texels);
+        assert_eq!(sample_counts(pixels, width), vec![7, 0, 0]);
+    }
+
+    #[test]
+    fn busiest_cell_follows_the_edge() {
+        let width = 4 * SAMPLE_STRIDE as u32;
+        // counts change across the column at the start of the third cell, and in one texel of
+        // the first cell, which the wide window outweighs
+        let pixels: Vec<Pixel> = (0..width * width)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                let count = if x >= 2 * SAMPLE_STRIDE as u32 {
+                    40
+                } else {
+                    10
+                };
+                let count = if (x, y) == (0, 0) { 200 } else { count };
+                bytemuck::cast([count, 0u16])
+            })
+            .collect();
+
+        let gradients = cell_gradients(&pixels, width);
+        assert_eq!(gradients.len(), 16);
+        assert_eq!(gradients[1], 30 * SAMPLE_STRIDE as u32);
+
+        assert_eq!(busiest_cell(&gradients, 4, 0), Some(UVec2::new(0, 0)));
+        assert_eq!(busiest_cell(&gradients, 4, 1).map(|cell| cell.x), Some(1));
+        assert_eq!(busiest_cell(&[0; 16], 4, 1), None);
+    }
+
+    #[test]
+    fn scaled_texture_size_stays_within_limits() {
+        assert_eq!(scaled_texture_size(TEXTURE_SIZE, 1.0, 8192), TEXTURE_SIZE);
+        assert_eq!(
+            scaled_texture_size(TEXTURE_SIZE, 0.5, 8192),
+            UVec2::splat(2048)
+        );
+        // rounded up to whole tiles
+        assert_eq!(
+            scaled_texture_size(UVec2::new(2048, 1024), 1.3, 8192),
+            UVec2::new(2688, 1408)
+        );
+        assert_eq!(
+            scaled_texture_size(TEXTURE_SIZE, 2.0, 6000),
+            UVec2::splat(5888)
+        );
+        assert_eq!(
+            scaled_texture_size(UVec2::splat(1024), 0.5, 8192),
+            UVec2::splat(1024)
+        );
+    }
+
+    #[test]
+    fn zoom_then_resize_blits_from_the_rect_the_texture_holds() {
+        let window = UVec2::new(1280, 800);
+        let frame = DRect::from_center_size(DVec2::new(-0.74, 0.0), DVec2::new(4.0, 2.5));
+        // right after a blit, the texture holds what it's placed at
+        let held = texture_fractal_rect(frame, TEXTURE_SIZE, window, 1.0);
+        let (mut fractal_rect, mut source, mut blit_pending) = (held, held, false);
+        let mut change_view = |frame: DRect, window: UVec2| {
+            let new_fractal_rect = texture_fractal_rect(frame, TEXTURE_SIZE, window, 1.0);
+            assert!(is_frame_changed(fractal_rect, new_fractal_rect, frame));
+            source = blit_source(source, fractal_rect, blit_pending);
+            blit_pending = true;
+            fractal_rect = new_fractal_rect;
+        };
+
+        // a wheel step, then the window gets wider and shorter, both before a render
+        let zoomed = DRect::from_center_size(DVec2::new(-0.6, 0.1), frame.size / 2.0);
+        change_view(zoomed, window);
+        let resized = DRect::from_center_size(
+            zoomed.center(),
+            DVec2::new(zoomed.size.y * 1600.0 / 700.0, zoomed.size.y),
+        );
+        change_view(resized, UVec2::new(1600, 700));
+        assert_eq!(source, held);
+
+        let ndc = |rect: DRect, point: DVec2| {
+            let ndc = 2.0 * (point - rect.center()) / rect.size;
+            Vec3::new(ndc.x as f32, -ndc.y as f32, 0.0)
+        };
+        let transform = blit_transform(source, fractal_rect);
+        for point in [held.center(), held.pos, DVec2::new(-0.55, 0.15)] {
+            let blitted = transform.transform_point3(ndc(held, point));
+            assert!(
+                (blitted - ndc(fractal_rect, point)).length() < 1e-5,
+                "{:?}",
+                point
+            );
+        }
+    }
+
+    #[test]
+    fn blit_lines_up_for_per_axis_scale() {
+        // the point's position in a quad covering `rect`, as the blit and screen passes draw it
+        let ndc = |rect: DRect, point: DVec2| {
+            let ndc = 2.0 * (point - rect.center()) / rect.size;
+            Vec3::new(ndc.x as f32, -ndc.y as f32, 0.0)
+        };
+
+        let prev = DRect::from_center_size(DVec2::new(-0.74, 0.1), DVec2::new(3.2, 2.0));
+        // zoomed in while the window got wider and shorter
+        let cur = DRect::from_center_size(DVec2::new(-0.6, 0.2), DVec2::new(2.4, 0.8));
+        let transform = blit_transform(prev, cur);
+
+        for point in [
+            prev.center(),
+            prev.pos,
+            prev.pos + prev.size,
+            DVec2::new(-0.5, 0.25),
+        ] {
+            let blitted = transform.transform_point3(ndc(prev, point));
+            assert!((blitted - ndc(cur, point)).length() < 1e-5, "{:?}", point);
+        }
+    }
+
+    #[test]
+    fn sub_tiles_cover_the_tile() {
+        let tex_rect = URect::from_pos_size(UVec2::new(256, 128), UVec2::splat(TILE_SIZE));
+        let parts: Vec<URect> = sub_rects(tex_rect, 64).collect();
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[1].pos, UVec2::new(320, 128));
+        assert_eq!(parts[2].pos, UVec2::new(256, 192));
+
+        // each part's pixels land in its own quarter of the tile's buffer
+        let pixel = |count: u16| bytemuck::cast::<[u16; 2], Pixel>([count, 0]);
+        let mut buffer = vec![Pixel::zeroed(); (TILE_SIZE * TILE_SIZE) as usize];
+        for (index, part) in parts.iter().enumerate() {
+            copy_part(
+                tex_rect,
+                *part,
+                &[pixel(index as u16); 64 * 64],
+                &mut buffer,
+            );
+        }
+        let at = |x: usize, y: usize| buffer[y * TILE_SIZE as usize + x];
+        assert_eq!(at(0, 0), pixel(0));
+        assert_eq!(at(127, 0), pixel(1));
+        assert_eq!(at(0, 127), pixel(2));
+        assert_eq!(at(64, 64), pixel(3));
     }
 }
@@ -1,30 +1,283 @@
 #![allow(non_camel_case_types)]
 
 use std::simd::prelude::*;
+use std::str::FromStr;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
-use std::time::Instant;
-use std::usize;
+use std::time::{Duration, Instant};
 
 use anyhow::anyhow;
 use bytemuck::{Pod, Zeroable};
-use glam::DVec2;
+use glam::{DVec2, UVec2};
 
+use crate::double_double::{DoubleDouble, DoubleDoubleComplex};
 use crate::env::is_test_build;
+use crate::fractal::{
+    is_in_main_cardioid, BurningShip, EscapeFractal, Fractal, FractalKind, Julia, Mandelbrot,
+    Multibrot, Tricorn, ESCAPE_RADIUS_SQUARED,
+};
 use crate::math::{DRect, URect};
 
-const SIMD_LANE_COUNT: usize = 8;
-pub const MAX_ITER: u32 = 4500;
+pub(crate) const SIMD_LANE_COUNT: usize = 8;
+// default ceiling for adaptive and manual iteration counts, raise with `MandelTexture::set_iter_cap`
+pub const DEFAULT_ITER_CAP: u32 = 4500;
+pub const MIN_ITER: u32 = 32;
+// largest count `Pixel` holds without wrapping: 0 marks the interior, `1 + iters` an escape
+// and u16::MAX is `Pixel::GLITCH`
+pub const ITER_STORAGE_LIMIT: u32 = u16::MAX as u32 - 2;
+const _: () = assert!(DEFAULT_ITER_CAP <= ITER_STORAGE_LIMIT);
+// largest count `IterStorage::Log` holds, its top code stays below `Pixel::GLITCH`
+pub const LOG_ITER_LIMIT: u32 = 10_000_000;
+// counts well below it are stored exactly, above it one code covers `iters / LOG_SCALE` iterations,
+// must match LOG_SCALE in screen_shader.wgsl and mip_shader.wgsl
+const LOG_SCALE: f64 = 8192.0;
+
+// steps per unit of the escape's piecewise linear log2 |z|², must match NORM_LOG_SCALE
+// in screen_shader.wgsl and mip_shader.wgsl, the u16 range reaches past the 16th power Multibrot's largest
+const NORM_LOG_SCALE: f64 = 1024.0;
+
+// a pixel step this many ulps of the coordinate or less is below what f64 can resolve
+const GLITCH_ULPS: f64 = 4.0;
+// more iterations between neighboring escapes than a view that still resolves its pixels has,
+// checked only where the pixel step is within `GLITCH_ULPS`
+const GLITCH_JUMP: u32 = 8;
 
-type f64simd = Simd<f64, SIMD_LANE_COUNT>;
+pub(crate) type f64simd = Simd<f64, SIMD_LANE_COUNT>;
 type i64simd = Simd<i64, SIMD_LANE_COUNT>;
 type mask64simd = Mask<i64, SIMD_LANE_COUNT>;
 type CountSimd = [Pixel; SIMD_LANE_COUNT];
 
 #[repr(C)]
-#[derive(Debug, Clone, Copy, Pod, Zeroable, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, Default)]
 pub(crate) struct Pixel {
     r: u16,
+    // interior: angle of the final `z`, a full turn mapped onto the u16 range,
+    // escape: |z|² at the escape as exponent plus mantissa, see `Pixel::escaped`
+    g: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KernelParams {
+    pub max_iterations: u32,
+    pub fractal: Fractal,
+    pub detect_glitches: bool,
+    // wall-clock limit for one call, checked once per row like the cancel token
+    pub time_budget: Option<Duration>,
+    // `None` takes a single sample per pixel
+    pub supersample: Option<SamplePattern>,
+    pub iter_storage: IterStorage,
+    pub kernel: Kernel,
+}
+
+/// Which implementation iterates the points, both fill the same `Pixel` buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Kernel {
+    #[default]
+    Simd,
+    /// One point at a time in plain f64, a slow reference to check the SIMD kernel against.
+    Scalar,
+    /// One point at a time in double-double, for pixels too small for f64 to tell apart.
+    /// `MandelTexture` switches to it from `Simd` on its own once the zoom needs it.
+    DoubleDouble,
+}
+
+impl Kernel {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kernel::Simd => "simd",
+            Kernel::Scalar => "scalar",
+            Kernel::DoubleDouble => "double-double",
+        }
+    }
+
+    /// Relative precision of the kernel's coordinates, what glitch detection measures against.
+    fn epsilon(self) -> f64 {
+        match self {
+            Kernel::Simd | Kernel::Scalar => f64::EPSILON,
+            Kernel::DoubleDouble => DoubleDouble::EPSILON,
+        }
+    }
+}
+
+impl FromStr for Kernel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "simd" => Ok(Kernel::Simd),
+            "scalar" => Ok(Kernel::Scalar),
+            "double-double" => Ok(Kernel::DoubleDouble),
+            _ => Err(anyhow!("Unknown kernel: {}", s)),
+        }
+    }
+}
+
+/// How escape counts are packed into the 16-bit `Pixel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IterStorage {
+    /// The exact count, up to `ITER_STORAGE_LIMIT` iterations.
+    #[default]
+    Linear = 0,
+    /// Logarithmically compressed: exact at low counts, coarser up to `LOG_ITER_LIMIT`.
+    Log = 1,
+}
+
+impl IterStorage {
+    pub fn iter_limit(self) -> u32 {
+        match self {
+            IterStorage::Linear => ITER_STORAGE_LIMIT,
+            IterStorage::Log => LOG_ITER_LIMIT,
+        }
+    }
+
+    /// Stored value of an escape after `iters` iterations, never 0 or `Pixel::GLITCH`.
+    pub fn encode(self, iters: u32) -> u16 {
+        match self {
+            IterStorage::Linear => 1 + iters as u16,
+            IterStorage::Log => 1 + (LOG_SCALE * (iters as f64 / LOG_SCALE).ln_1p()).round() as u16,
+        }
+    }
+
+    /// Iterations of a stored escape value, the inverse of `encode`.
+    pub fn decode(self, count: u16) -> u32 {
+        let code = count as u32 - 1;
+        match self {
+            IterStorage::Linear => code,
+            IterStorage::Log => (LOG_SCALE * (code as f64 / LOG_SCALE).exp_m1()).round() as u32,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IterStorage::Linear => "linear",
+            IterStorage::Log => "log",
+        }
+    }
+}
+
+impl FromStr for IterStorage {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(IterStorage::Linear),
+            "log" => Ok(IterStorage::Log),
+            _ => Err(anyhow!("Unknown iteration storage: {}", s)),
+        }
+    }
+}
+
+// most samples per axis of `SamplePattern::Grid`
+pub const MAX_GRID_SAMPLES: u32 = 4;
+const MAX_SAMPLES: usize = (MAX_GRID_SAMPLES * MAX_GRID_SAMPLES) as usize;
+
+/// Placement of the sub-pixel samples when supersampling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SamplePattern {
+    /// Axis-aligned grid of `x` by `y` samples at the centers of its cells, `x` alone
+    /// smooths edges across rows like vertical filaments.
+    Grid(UVec2),
+    /// 2x2 grid rotated so every sample has its own row and column.
+    RotatedGrid,
+}
+
+impl Default for SamplePattern {
+    fn default() -> Self {
+        SamplePattern::Grid(UVec2::splat(2))
+    }
+}
+
+impl SamplePattern {
+    /// Sample offsets in pixels from the pixel's sample point, rows of the grid one after another.
+    pub fn sample_offsets(self) -> Vec<DVec2> {
+        match self {
+            SamplePattern::Grid(counts) => (0..counts.y)
+                .flat_map(|y| (0..counts.x).map(move |x| UVec2::new(x, y)))
+                .map(|cell| (cell.as_dvec2() + 0.5) / counts.as_dvec2() - 0.5)
+                .collect(),
+            SamplePattern::RotatedGrid => vec![
+                DVec2::new(0.125, 0.375),
+                DVec2::new(0.375, -0.125),
+                DVec2::new(-0.125, -0.375),
+                DVec2::new(-0.375, 0.125),
+            ],
+        }
+    }
+}
+
+impl FromStr for SamplePattern {
+    type Err = anyhow::Error;
+
+    /// `grid` is 2x2, `NxM` a grid of N columns and M rows.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grid" => return Ok(SamplePattern::default()),
+            "rotated" => return Ok(SamplePattern::RotatedGrid),
+            _ => {}
+        }
+
+        let counts = s
+            .split_once('x')
+            .and_then(|(x, y)| Some(UVec2::new(x.parse().ok()?, y.parse().ok()?)))
+            .ok_or_else(|| anyhow!("Unknown sample pattern: {}", s))?;
+        if counts.min_element() == 0 || counts.max_element() > MAX_GRID_SAMPLES {
+            return Err(anyhow!(
+                "Sample grids take 1 to {} samples per axis, not {}",
+                MAX_GRID_SAMPLES,
+                s
+            ));
+        }
+
+        Ok(SamplePattern::Grid(counts))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileStatus {
+    Complete,
+    // the budget ran out, the buffer is only partially written
+    TimedOut,
+    // the cancel token was set, the result is no longer wanted
+    Cancelled,
+}
+
+impl Pixel {
+    // never produced by a regular escape count, see `pixel`
+    pub const GLITCH: Pixel = Pixel { r: u16::MAX, g: 0 };
+
+    fn interior(zx: f64, zy: f64) -> Pixel {
+        let turn = zy.atan2(zx) / std::f64::consts::TAU + 0.5;
+        Pixel {
+            r: 0,
+            g: (turn * u16::MAX as f64) as u16,
+        }
+    }
+
+    /// An escape after `iters` iterations, `norm` is |z|² at the escape.
+    /// The norm is kept as its binary exponent plus the mantissa's fraction, a continuous
+    /// stand-in for log2 without a logarithm per pixel, the shader undoes it for smooth coloring.
+    fn escaped(iters: u32, norm: f64, iter_storage: IterStorage) -> Pixel {
+        let bits = norm.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as f64 - 1023.0;
+        let fraction = (bits & ((1 << 52) - 1)) as f64 / (1u64 << 52) as f64;
+        Pixel {
+            r: iter_storage.encode(iters),
+            g: ((exponent + fraction) * NORM_LOG_SCALE)
+                .round()
+                .clamp(0.0, u16::MAX as f64) as u16,
+        }
+    }
+
+    /// log2 of the |z|² stored by `escaped`, the CPU copy of `escape_log2_norm` in the shader.
+    pub fn escape_log2_norm(self) -> f64 {
+        let stored = self.g as f64 / NORM_LOG_SCALE;
+        stored.floor() + stored.fract().ln_1p() / std::f64::consts::LN_2
+    }
+
+    /// Raw stored value: 0 for the interior, `IterStorage::encode` of the iterations for an escape.
+    pub fn count(self) -> u16 {
+        self.r
+    }
 }
 
 const CX_INIT: [f64; SIMD_LANE_COUNT] = {
@@ -39,46 +292,64 @@ const CX_INIT: [f64; SIMD_LANE_COUNT] = {
 
 //noinspection RsConstantConditionIf
 pub fn mandelbrot_simd(
-    image_size: u32,
+    image_size: UVec2,
     tex_rect: URect,
-    fractal_offset: DVec2,
+    fractal_center: DVec2,
     fractal_scale: f64,
-    max_iterations: u32,
+    params: &KernelParams,
     cancel_token: Arc<AtomicBool>,
     buffer: &mut [Pixel],
-) -> anyhow::Result<()> {
+) -> anyhow::Result<TileStatus> {
     assert_eq!(buffer.len(), (tex_rect.size.x * tex_rect.size.y) as usize);
+    assert!(params.max_iterations <= params.iter_storage.iter_limit());
 
     let now = Instant::now();
-    let buffer_frame = {
-        let image_size = image_size as f64;
-        let fractal_offset = DVec2::new(fractal_offset.x, fractal_offset.y);
-
-        DRect::from_pos_size(
-            (DVec2::from(tex_rect.pos) / image_size - 0.5) / fractal_scale - fractal_offset,
-            (DVec2::from(tex_rect.size) / image_size) / fractal_scale,
-        )
+    let mapping = PixelMapping {
+        image_size,
+        fractal_center,
+        fractal_scale,
     };
 
-    for y in 0..tex_rect.size.y {
-        if cancel_token.load(std::sync::atomic::Ordering::Relaxed) {
-            return Err(anyhow!("Cancelled"));
-        }
-        for x in 0..tex_rect.size.x / SIMD_LANE_COUNT as u32 {
-            let cx = f64simd::from_slice(CX_INIT.as_slice())
-                + f64simd::splat((x * SIMD_LANE_COUNT as u32) as f64);
-            let cx = cx * f64simd::splat(buffer_frame.size.x / tex_rect.size.x as f64);
-            let cx = cx + f64simd::splat(buffer_frame.pos.x);
-
-            let cy = f64simd::splat(
-                buffer_frame.pos.y + buffer_frame.size.y * (y as f64 / tex_rect.size.y as f64),
-            );
-
-            let values_simd = pixel(max_iterations, cx, cy);
-            let idx = (y * tex_rect.size.x + x * SIMD_LANE_COUNT as u32) as usize;
-            buffer[idx..idx + SIMD_LANE_COUNT].copy_from_slice(values_simd.as_slice());
+    let fractal = params.fractal;
+    let status = match fractal.kind {
+        FractalKind::Mandelbrot => escape_time(
+            &Mandelbrot,
+            tex_rect,
+            &mapping,
+            params,
+            &cancel_token,
+            buffer,
+        ),
+        FractalKind::Julia => escape_time(
+            &Julia { c: fractal.julia_c },
+            tex_rect,
+            &mapping,
+            params,
+            &cancel_token,
+            buffer,
+        ),
+        FractalKind::BurningShip => escape_time(
+            &BurningShip,
+            tex_rect,
+            &mapping,
+            params,
+            &cancel_token,
+            buffer,
+        ),
+        FractalKind::Multibrot => escape_time(
+            &Multibrot {
+                power: fractal.multibrot_power,
+            },
+            tex_rect,
+            &mapping,
+            params,
+            &cancel_token,
+            buffer,
+        ),
+        FractalKind::Tricorn => {
+            escape_time(&Tricorn, tex_rect, &mapping, params, &cancel_token, buffer)
         }
-    }
+    }?;
 
     if is_test_build() {
         let elapsed = now.elapsed();
@@ -92,41 +363,391 @@ pub fn mandelbrot_simd(
         // }
     }
 
-    Ok(())
+    Ok(status)
+}
+
+/// Writes a Mandelbrot tile `fractal::is_rect_interior` accepted without iterating,
+/// taking the phase from the cycle the kernel's `z` would have settled on.
+pub fn fill_interior(
+    image_size: UVec2,
+    tex_rect: URect,
+    fractal_center: DVec2,
+    fractal_scale: f64,
+    max_iterations: u32,
+    buffer: &mut [Pixel],
+) {
+    assert_eq!(buffer.len(), (tex_rect.size.x * tex_rect.size.y) as usize);
+
+    let mapping = PixelMapping {
+        image_size,
+        fractal_center,
+        fractal_scale,
+    };
+    for y in 0..tex_rect.size.y {
+        for x in 0..tex_rect.size.x {
+            let c = mapping.to_fractal((tex_rect.pos + UVec2::new(x, y)).as_dvec2());
+            let z = interior_attractor(c, max_iterations);
+            buffer[(y * tex_rect.size.x + x) as usize] = Pixel::interior(z.x, z.y);
+        }
+    }
+}
+
+// the fixed point (1 - sqrt(1 - 4c)) / 2 in the cardioid, in the bulb one point of the 2-cycle
+// (-1 ± sqrt(-3 - 4c)) / 2, the orbit of 0 is at the `+` one after an even number of iterations
+fn interior_attractor(c: DVec2, max_iterations: u32) -> DVec2 {
+    if is_in_main_cardioid(c) {
+        return (DVec2::X - complex_sqrt(DVec2::X - 4.0 * c)) / 2.0;
+    }
+
+    let root = complex_sqrt(DVec2::new(-3.0, 0.0) - 4.0 * c);
+    let root = if max_iterations.is_multiple_of(2) {
+        root
+    } else {
+        -root
+    };
+    (root - DVec2::X) / 2.0
 }
 
-fn pixel(max_iterations: u32, cx: f64simd, cy: f64simd) -> CountSimd {
-    let mut zx = f64simd::splat(0.0);
-    let mut zy = f64simd::splat(0.0);
-    let mut cnt = i64simd::splat(0);
-    let mut escaped = mask64simd::splat(false);
+// principal square root
+fn complex_sqrt(w: DVec2) -> DVec2 {
+    let length = w.length();
+    let re = ((length + w.x) / 2.0).sqrt();
+    let im = ((length - w.x) / 2.0).sqrt();
+    DVec2::new(re, if w.y < 0.0 { -im } else { im })
+}
 
-    let f64_4_0 = f64simd::splat(5.0);
-    let i64_0 = i64simd::splat(0);
-    let i64_1 = i64simd::splat(1);
+// Places image pixels in fractal space, pixels are square and `fractal_scale` is per image height.
+// Coordinates depend on the absolute pixel only, so how the image is split into tiles or bands
+// never changes the result.
+struct PixelMapping {
+    image_size: UVec2,
+    fractal_center: DVec2,
+    fractal_scale: f64,
+}
 
-    for _ in 0..max_iterations {
-        (zx, zy) = (zx * zx - zy * zy + cx, zx * zy + zx * zy + cy);
-        escaped |= (zx * zx + zy * zy).simd_ge(f64_4_0);
+impl PixelMapping {
+    fn pixel_size(&self) -> f64 {
+        1.0 / self.image_size.y as f64 / self.fractal_scale
+    }
 
-        if escaped.all() {
-            break;
+    /// Fractal coordinate of the corner of image pixel `pixel`.
+    fn to_fractal(&self, pixel: DVec2) -> DVec2 {
+        self.to_offset(pixel) + self.fractal_center
+    }
+
+    // from the center, small enough near it to keep what adding the center rounds away
+    fn to_offset(&self, pixel: DVec2) -> DVec2 {
+        let image_size = DVec2::from(self.image_size);
+        (pixel - image_size / 2.0) / image_size.y / self.fractal_scale
+    }
+
+    // `to_fractal` without rounding the sum, the center itself stays an f64
+    fn to_fractal_double_double(&self, pixel: DVec2) -> DoubleDoubleComplex {
+        let offset = self.to_offset(pixel);
+        (
+            DoubleDouble::from(self.fractal_center.x) + offset.x.into(),
+            DoubleDouble::from(self.fractal_center.y) + offset.y.into(),
+        )
+    }
+
+    // `to_fractal` for `SIMD_LANE_COUNT` pixels of one row, with the same operations in the same order
+    fn to_fractal_simd(&self, first_pixel: UVec2) -> (f64simd, f64simd) {
+        let image_size = DVec2::from(self.image_size);
+        let column = f64simd::from_slice(CX_INIT.as_slice()) + f64simd::splat(first_pixel.x as f64);
+        let cx = (column - f64simd::splat(image_size.x / 2.0))
+            / f64simd::splat(image_size.y)
+            / f64simd::splat(self.fractal_scale)
+            + f64simd::splat(self.fractal_center.x);
+        let cy = self.to_fractal(DVec2::new(0.0, first_pixel.y as f64)).y;
+
+        (cx, f64simd::splat(cy))
+    }
+}
+
+fn escape_time<F: EscapeFractal>(
+    fractal: &F,
+    tex_rect: URect,
+    mapping: &PixelMapping,
+    params: &KernelParams,
+    cancel_token: &AtomicBool,
+    buffer: &mut [Pixel],
+) -> anyhow::Result<TileStatus> {
+    let pixel_step = DVec2::splat(mapping.pixel_size());
+    let deadline = params.time_budget.map(|budget| Instant::now() + budget);
+    // the same for every pixel of the tile
+    let offsets = params.supersample.map(SamplePattern::sample_offsets);
+    let offsets = offsets.as_deref();
+
+    for y in 0..tex_rect.size.y {
+        if cancel_token.load(std::sync::atomic::Ordering::Relaxed) {
+            return Ok(TileStatus::Cancelled);
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Ok(TileStatus::TimedOut);
         }
+        // the last group of a width that isn't a multiple of the lanes computes past the tile's
+        // edge, those lanes are dropped
+        for x in 0..tex_rect.size.x.div_ceil(SIMD_LANE_COUNT as u32) {
+            let first_pixel = tex_rect.pos + UVec2::new(x * SIMD_LANE_COUNT as u32, y);
+            let (cx, cy) = mapping.to_fractal_simd(first_pixel);
+
+            let values_simd = match params.kernel {
+                Kernel::Simd => sample_pixels(
+                    |cx, cy| pixel(fractal, params.max_iterations, params.iter_storage, cx, cy),
+                    cx,
+                    cy,
+                    pixel_step,
+                    offsets,
+                ),
+                Kernel::Scalar => sample_pixels(
+                    |cx, cy| scalar_pixels(&params.fractal, params, cx, cy),
+                    cx,
+                    cy,
+                    pixel_step,
+                    offsets,
+                ),
+                // takes its coordinates from the mapping, the f64 ones lose the offsets
+                Kernel::DoubleDouble => double_double_pixels(params, mapping, first_pixel, offsets),
+            };
 
-        cnt += escaped.select(i64_0, i64_1);
+            let lanes =
+                SIMD_LANE_COUNT.min((tex_rect.size.x - x * SIMD_LANE_COUNT as u32) as usize);
+            let idx = (y * tex_rect.size.x + x * SIMD_LANE_COUNT as u32) as usize;
+            buffer[idx..idx + lanes].copy_from_slice(&values_simd[..lanes]);
+        }
     }
 
-    cnt.as_array().map(|iters| {
-        if iters as u32 == max_iterations {
-            Pixel { r: 0 }
+    if params.detect_glitches {
+        mark_glitches(tex_rect, mapping, params, buffer);
+    }
+
+    Ok(TileStatus::Complete)
+}
+
+/// Marks the pixels where the kernel runs out of precision and the count jumps from a neighbor's
+/// by more than `GLITCH_JUMP`. The coordinates round to a coarser grid there, the view breaks
+/// up into flat blocks and their seams are where the counts stop making sense.
+fn mark_glitches(
+    tex_rect: URect,
+    mapping: &PixelMapping,
+    params: &KernelParams,
+    buffer: &mut [Pixel],
+) {
+    let pixel_step = DVec2::splat(mapping.pixel_size());
+    let jumps = neighbor_jumps(buffer, tex_rect.size, params.iter_storage);
+
+    for y in 0..tex_rect.size.y {
+        for x in 0..tex_rect.size.x.div_ceil(SIMD_LANE_COUNT as u32) {
+            let first_pixel = tex_rect.pos + UVec2::new(x * SIMD_LANE_COUNT as u32, y);
+            let (cx, cy) = mapping.to_fractal_simd(first_pixel);
+            let beyond = glitch_mask(cx, cy, pixel_step, params.kernel.epsilon());
+
+            let lanes =
+                SIMD_LANE_COUNT.min((tex_rect.size.x - x * SIMD_LANE_COUNT as u32) as usize);
+            let idx = (y * tex_rect.size.x + x * SIMD_LANE_COUNT as u32) as usize;
+            for lane in (0..lanes).filter(|&lane| beyond.test(lane) && jumps[idx + lane]) {
+                buffer[idx + lane] = Pixel::GLITCH;
+            }
+        }
+    }
+}
+
+// per pixel of a `size` buffer, whether its escape count is more than `GLITCH_JUMP` iterations
+// from a horizontal or vertical neighbor's escape count
+fn neighbor_jumps(buffer: &[Pixel], size: UVec2, iter_storage: IterStorage) -> Vec<bool> {
+    let iters = |index: usize| {
+        let count = buffer[index].count();
+        (count != 0).then(|| iter_storage.decode(count))
+    };
+    let jumps = |a: usize, b: usize| match (iters(a), iters(b)) {
+        (Some(a), Some(b)) => a.abs_diff(b) > GLITCH_JUMP,
+        _ => false,
+    };
+
+    (0..buffer.len())
+        .map(|index| {
+            let (x, y) = (index as u32 % size.x, index as u32 / size.x);
+            (x > 0 && jumps(index, index - 1))
+                || (x + 1 < size.x && jumps(index, index + 1))
+                || (y > 0 && jumps(index, index - size.x as usize))
+                || (y + 1 < size.y && jumps(index, index + size.x as usize))
+        })
+        .collect()
+}
+
+fn pixel<F: EscapeFractal>(
+    fractal: &F,
+    max_iterations: u32,
+    iter_storage: IterStorage,
+    cx: f64simd,
+    cy: f64simd,
+) -> CountSimd {
+    let ((mut zx, mut zy), c) = fractal.initial((cx, cy));
+    // iterations before each lane escaped and |z|² then, lanes that never do keep `max_iterations`
+    let mut cnt = [max_iterations; SIMD_LANE_COUNT];
+    let mut escape_norm = [0.0; SIMD_LANE_COUNT];
+    // a bit per lane, escaped lanes keep iterating with the rest
+    let mut escaped = 0u64;
+    let all_escaped = (1u64 << SIMD_LANE_COUNT) - 1;
+
+    let escape_radius_squared = f64simd::splat(ESCAPE_RADIUS_SQUARED);
+
+    for iteration in 0..max_iterations {
+        (zx, zy) = fractal.iterate((zx, zy), c);
+        let norm = zx * zx + zy * zy;
+        let over = norm.simd_ge(escape_radius_squared).to_bitmask() | escaped;
+
+        // at most once per lane, so the common path is a single compare
+        if over != escaped {
+            let mut escaping = over & !escaped;
+            while escaping != 0 {
+                let lane = escaping.trailing_zeros() as usize;
+                cnt[lane] = iteration;
+                escape_norm[lane] = norm[lane];
+                escaping &= escaping - 1;
+            }
+            escaped = over;
+
+            if escaped == all_escaped {
+                break;
+            }
+        }
+    }
+
+    let (zx, zy) = (zx.to_array(), zy.to_array());
+    std::array::from_fn(|lane| {
+        let iters = cnt[lane];
+        if iters == max_iterations {
+            Pixel::interior(zx[lane], zy[lane])
         } else {
-            Pixel {
-                r: 1 + (iters % u16::MAX as i64) as u16,
+            // `max_iterations` is checked against the storage's limit
+            Pixel::escaped(iters, escape_norm[lane], iter_storage)
+        }
+    })
+}
+
+// the lanes of `pixel` one at a time, without the shared early exit
+fn scalar_pixels(fractal: &Fractal, params: &KernelParams, cx: f64simd, cy: f64simd) -> CountSimd {
+    std::array::from_fn(|lane| {
+        let (mut z, c) = fractal.scalar_initial(DVec2::new(cx[lane], cy[lane]));
+        for iters in 0..params.max_iterations {
+            z = fractal.scalar_iterate(z, c);
+            let norm = z.x * z.x + z.y * z.y;
+            if norm >= ESCAPE_RADIUS_SQUARED {
+                return Pixel::escaped(iters, norm, params.iter_storage);
             }
         }
+
+        Pixel::interior(z.x, z.y)
+    })
+}
+
+// `scalar_pixels` in double-double, each lane's point the f64 center plus its exact offset,
+// the supersampling offsets are added before the center too
+fn double_double_pixels(
+    params: &KernelParams,
+    mapping: &PixelMapping,
+    first_pixel: UVec2,
+    offsets: Option<&[DVec2]>,
+) -> CountSimd {
+    let fractal = &params.fractal;
+    let sample = |pixel: DVec2| {
+        let (mut z, c) = fractal.double_double_initial(mapping.to_fractal_double_double(pixel));
+        for iters in 0..params.max_iterations {
+            z = fractal.double_double_iterate(z, c);
+            let norm = z.0.hi * z.0.hi + z.1.hi * z.1.hi;
+            if norm >= ESCAPE_RADIUS_SQUARED {
+                return Pixel::escaped(iters, norm, params.iter_storage);
+            }
+        }
+
+        Pixel::interior(z.0.hi, z.1.hi)
+    };
+
+    std::array::from_fn(|lane| {
+        let pixel = (first_pixel + UVec2::new(lane as u32, 0)).as_dvec2();
+        match offsets {
+            None => sample(pixel),
+            Some(offsets) => {
+                let mut samples = [Pixel::default(); MAX_SAMPLES];
+                for (sample_pixel, offset) in samples.iter_mut().zip(offsets) {
+                    *sample_pixel = sample(pixel + *offset);
+                }
+                combine_samples(&samples[..offsets.len()])
+            }
+        }
+    })
+}
+
+// one sample per pixel, or one per offset of the supersampling pattern
+fn sample_pixels(
+    sample: impl Fn(f64simd, f64simd) -> CountSimd,
+    cx: f64simd,
+    cy: f64simd,
+    pixel_step: DVec2,
+    offsets: Option<&[DVec2]>,
+) -> CountSimd {
+    match offsets {
+        None => sample(cx, cy),
+        Some(offsets) => supersampled_pixel(sample, cx, cy, pixel_step, offsets),
+    }
+}
+
+fn supersampled_pixel(
+    sample: impl Fn(f64simd, f64simd) -> CountSimd,
+    cx: f64simd,
+    cy: f64simd,
+    pixel_step: DVec2,
+    offsets: &[DVec2],
+) -> CountSimd {
+    let mut samples = [[Pixel::default(); SIMD_LANE_COUNT]; MAX_SAMPLES];
+    for (samples, offset) in samples.iter_mut().zip(offsets) {
+        let offset = *offset * pixel_step;
+        *samples = sample(cx + f64simd::splat(offset.x), cy + f64simd::splat(offset.y));
+    }
+
+    std::array::from_fn(|lane| {
+        let mut lane_samples = [Pixel::default(); MAX_SAMPLES];
+        for (lane_sample, samples) in lane_samples.iter_mut().zip(&samples[..offsets.len()]) {
+            *lane_sample = samples[lane];
+        }
+        combine_samples(&lane_samples[..offsets.len()])
     })
 }
 
+// same rule as the mip shader: interior if at least half the samples are, keeping the first
+// interior sample's phase, else the mean escape count and magnitude
+fn combine_samples(samples: &[Pixel]) -> Pixel {
+    let escaped = samples.iter().filter(|sample| sample.r != 0);
+    let count = escaped.clone().count() as u32;
+    if count as usize * 2 <= samples.len() {
+        return *samples.iter().find(|sample| sample.r == 0).unwrap();
+    }
+
+    let sum: u32 = escaped.clone().map(|sample| sample.r as u32).sum();
+    let magnitude_sum: u32 = escaped.map(|sample| sample.g as u32).sum();
+    Pixel {
+        r: (sum / count) as u16,
+        g: (magnitude_sum / count) as u16,
+    }
+}
+
+/// Scalar `glitch_mask` for a whole frame: true once its pixels are too small for f64
+/// to tell apart anywhere in `frame`.
+pub(crate) fn is_beyond_precision(frame: DRect, pixel_step: DVec2) -> bool {
+    let farthest = frame.center().abs() + frame.size / 2.0;
+    let limit = farthest * f64::EPSILON * GLITCH_ULPS;
+
+    pixel_step.x <= limit.x || pixel_step.y <= limit.y
+}
+
+fn glitch_mask(cx: f64simd, cy: f64simd, pixel_step: DVec2, epsilon: f64) -> mask64simd {
+    let limit = f64simd::splat(epsilon * GLITCH_ULPS);
+
+    f64simd::splat(pixel_step.x).simd_le(cx.abs() * limit)
+        | f64simd::splat(pixel_step.y).simd_le(cy.abs() * limit)
+}
+
 #[cfg(test)]
 mod test {
     use std::sync::Arc;
@@ -139,7 +760,7 @@ mod test {
     fn draw_mandelbrot() {
         let image_size = 2048;
         let tile_rect = URect::from_pos_size(UVec2::new(0, 0), UVec2::new(image_size, image_size));
-        let fractal_offset = DVec2::new(0.10486747136388758, 0.9244368813525663);
+        let fractal_center = DVec2::new(-0.10486747136388758, -0.9244368813525663);
         let fractal_scale = 32.0;
         let max_iterations = 1024;
         let cancel_token = Arc::new(AtomicBool::new(false));
@@ -150,11 +771,19 @@ mod test {
 
         for _ in 0..retry {
             mandelbrot_simd(
-                image_size,
+                UVec2::splat(image_size),
                 tile_rect,
-                fractal_offset,
+                fractal_center,
                 fractal_scale,
-                max_iterations,
+                &KernelParams {
+                    max_iterations,
+                    fractal: Fractal::default(),
+                    detect_glitches: false,
+                    time_budget: None,
+                    supersample: None,
+                    iter_storage: IterStorage::Linear,
+                    kernel: Kernel::Simd,
+                },
                 cancel_token.clone(),
                 &mut buffer,
             )
@@ -175,4 +804,477 @@ mod test {
         }
         image.save("test_output/mandelbrot.png").unwrap();
     }
+
+    // fraction of the pixel's samples below the line `y = slope * x + intercept`
+    fn coverage(pattern: SamplePattern, pixel: DVec2, slope: f64, intercept: f64) -> f64 {
+        let offsets = pattern.sample_offsets();
+        let inside = offsets
+            .iter()
+            .filter(|offset| {
+                let p = pixel + **offset;
+                p.y < slope * p.x + intercept
+            })
+            .count();
+        inside as f64 / offsets.len() as f64
+    }
+
+    // area of the unit pixel centered at `pixel` below the same line, integrated over thin columns
+    fn exact_coverage(pixel: DVec2, slope: f64, intercept: f64) -> f64 {
+        let columns = 1000;
+        let area: f64 = (0..columns)
+            .map(|i| {
+                let x = pixel.x - 0.5 + (i as f64 + 0.5) / columns as f64;
+                (slope * x + intercept - (pixel.y - 0.5)).clamp(0.0, 1.0)
+            })
+            .sum();
+        area / columns as f64
+    }
+
+    fn edge_error(pattern: SamplePattern, slope: f64) -> f64 {
+        let mut error = 0.0;
+        // sweep the line across a pixel so the result doesn't hinge on one lucky placement
+        for k in 0..20 {
+            let intercept = k as f64 / 20.0;
+            for x in -3..3 {
+                for y in -4..5 {
+                    let pixel = DVec2::new(x as f64, y as f64);
+                    error += (coverage(pattern, pixel, slope, intercept)
+                        - exact_coverage(pixel, slope, intercept))
+                    .abs();
+                }
+            }
+        }
+        error
+    }
+
+    #[test]
+    fn rotated_grid_reduces_diagonal_edge_error() {
+        // a near-horizontal edge and a 45 degree one
+        for slope in [0.1, 1.0] {
+            let grid_error = edge_error(SamplePattern::default(), slope);
+            let rotated_error = edge_error(SamplePattern::RotatedGrid, slope);
+
+            assert!(
+                rotated_error < grid_error,
+                "slope {}: rotated {} vs grid {}",
+                slope,
+                rotated_error,
+                grid_error
+            );
+        }
+    }
+
+    #[test]
+    fn wide_grid_favors_steep_edges() {
+        let single = SamplePattern::Grid(UVec2::ONE);
+        let wide = SamplePattern::Grid(UVec2::new(2, 1));
+        // error removed by the second column on a near-vertical and a near-horizontal edge
+        let gain = |slope| edge_error(single, slope) - edge_error(wide, slope);
+
+        assert!(gain(8.0) > gain(0.1), "{} vs {}", gain(8.0), gain(0.1));
+        assert_eq!(
+            "3x2".parse::<SamplePattern>().unwrap(),
+            SamplePattern::Grid(UVec2::new(3, 2))
+        );
+        assert!("0x2".parse::<SamplePattern>().is_err());
+        assert!("5x1".parse::<SamplePattern>().is_err());
+    }
+
+    #[test]
+    fn interior_fill_matches_the_kernel() {
+        let image_size = UVec2::new(1024, 1024);
+        let fractal_scale = 1.0 / 4.0;
+        // one tile deep in the cardioid, one in the bulb
+        let tiles = [
+            URect::from_pos_size(UVec2::new(512, 448), UVec2::new(64, 64)),
+            URect::from_pos_size(UVec2::new(384, 496), UVec2::new(32, 32)),
+        ];
+
+        // the bulb's phase depends on which point of the 2-cycle the last iteration lands on
+        let cases = [1000, 1001]
+            .into_iter()
+            .flat_map(|max_iterations| tiles.map(|tex_rect| (tex_rect, max_iterations)));
+        for (tex_rect, max_iterations) in cases {
+            let len = (tex_rect.size.x * tex_rect.size.y) as usize;
+            let mut expected = vec![Pixel::default(); len];
+            let mut filled = vec![Pixel::default(); len];
+            mandelbrot_simd(
+                image_size,
+                tex_rect,
+                DVec2::new(-0.5, 0.0),
+                fractal_scale,
+                &KernelParams {
+                    max_iterations,
+                    fractal: Fractal::default(),
+                    detect_glitches: false,
+                    time_budget: None,
+                    supersample: None,
+                    iter_storage: IterStorage::Linear,
+                    kernel: Kernel::Simd,
+                },
+                Arc::new(AtomicBool::new(false)),
+                &mut expected,
+            )
+            .unwrap();
+            fill_interior(
+                image_size,
+                tex_rect,
+                DVec2::new(-0.5, 0.0),
+                fractal_scale,
+                max_iterations,
+                &mut filled,
+            );
+
+            for (expected, filled) in expected.iter().zip(&filled) {
+                assert_eq!(expected.r, 0);
+                assert_eq!(filled.r, 0);
+                let turns = (expected.g as f64 - filled.g as f64) / u16::MAX as f64;
+                let turns = turns - turns.round();
+                assert!(turns.abs() < 1e-3, "{:?} vs {:?}", expected, filled);
+            }
+        }
+    }
+
+    #[test]
+    fn scalar_kernel_agrees_with_simd() {
+        let image_size = UVec2::new(256, 192);
+        let tex_rect = URect::from_pos_size(UVec2::new(64, 64), UVec2::new(64, 32));
+        let render = |fractal: Fractal, supersample, kernel| {
+            let mut buffer = vec![Pixel::default(); (tex_rect.size.x * tex_rect.size.y) as usize];
+            let (center, height) = fractal.kind.default_view();
+            mandelbrot_simd(
+                image_size,
+                tex_rect,
+                center,
+                1.0 / height,
+                &KernelParams {
+                    max_iterations: 300,
+                    fractal,
+                    detect_glitches: false,
+                    time_budget: None,
+                    supersample,
+                    iter_storage: IterStorage::Linear,
+                    kernel,
+                },
+                Arc::new(AtomicBool::new(false)),
+                &mut buffer,
+            )
+            .unwrap();
+            buffer
+        };
+
+        for kind in FractalKind::ALL {
+            let fractal = Fractal {
+                kind,
+                ..Fractal::default()
+            };
+            for supersample in [None, Some(SamplePattern::RotatedGrid)] {
+                let simd = render(fractal, supersample, Kernel::Simd);
+                let scalar = render(fractal, supersample, Kernel::Scalar);
+                assert!(simd.iter().any(|pixel| pixel.r == 0), "{:?}", kind);
+                assert!(simd.iter().any(|pixel| pixel.r != 0), "{:?}", kind);
+                for (simd, scalar) in simd.iter().zip(&scalar) {
+                    assert_eq!((simd.r, simd.g), (scalar.r, scalar.g), "{:?}", kind);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn double_double_resolves_what_f64_rounds_away() {
+        // a row of pixels 1e-20 apart on the antenna, far below an f64 ulp of the center
+        let image_size = UVec2::new(8, 1);
+        let render = |kernel| {
+            let mut buffer = vec![Pixel::default(); SIMD_LANE_COUNT];
+            mandelbrot_simd(
+                image_size,
+                URect::from_pos_size(UVec2::ZERO, image_size),
+                DVec2::new(-1.7497219818, 0.0),
+                1e20,
+                &KernelParams {
+                    max_iterations: 3000,
+                    fractal: Fractal::default(),
+                    detect_glitches: false,
+                    time_budget: None,
+                    supersample: None,
+                    iter_storage: IterStorage::Linear,
+                    kernel,
+                },
+                Arc::new(AtomicBool::new(false)),
+                &mut buffer,
+            )
+            .unwrap();
+            buffer.iter().map(|pixel| pixel.count()).collect::<Vec<_>>()
+        };
+
+        // the same points iterated with 60 significant digits
+        let reference = [360, 360, 360, 361, 362, 361, 362, 364];
+        assert_eq!(render(Kernel::DoubleDouble), reference);
+        let f64_counts = render(Kernel::Simd);
+        assert!(f64_counts.iter().all(|&count| count == f64_counts[0]));
+    }
+
+    #[test]
+    fn non_square_texture_keeps_pixels_square() {
+        let image_size = UVec2::new(2048, 1024);
+        let tex_rect = URect::from_pos_size(UVec2::ZERO, image_size);
+        let fractal_center = DVec2::new(-0.5, 0.0);
+        let fractal_scale = 1.0 / 2.5;
+        let mut buffer = vec![Pixel::default(); (image_size.x * image_size.y) as usize];
+
+        let mapping = PixelMapping {
+            image_size,
+            fractal_center,
+            fractal_scale,
+        };
+        let frame = DRect::from_pos_size(
+            mapping.to_fractal(DVec2::ZERO),
+            mapping.to_fractal(DVec2::from(image_size)) - mapping.to_fractal(DVec2::ZERO),
+        );
+        assert!((frame.size - DVec2::new(5.0, 2.5)).length() < 1e-12);
+        assert!((frame.center() - fractal_center).length() < 1e-12);
+
+        mandelbrot_simd(
+            image_size,
+            tex_rect,
+            fractal_center,
+            fractal_scale,
+            &KernelParams {
+                max_iterations: 256,
+                fractal: Fractal::default(),
+                detect_glitches: false,
+                time_budget: None,
+                supersample: None,
+                iter_storage: IterStorage::Linear,
+                kernel: Kernel::Simd,
+            },
+            Arc::new(AtomicBool::new(false)),
+            &mut buffer,
+        )
+        .unwrap();
+
+        // the middle row is the real axis, the set covers exactly [-2, 0.25] of it
+        let row = image_size.y / 2;
+        let pixels_per_unit = image_size.y as f64 * fractal_scale;
+        let texel = |re: f64| {
+            let column = (re - fractal_center.x) * pixels_per_unit + image_size.x as f64 / 2.0;
+            buffer[(row * image_size.x + column.round() as u32) as usize].r
+        };
+
+        assert_eq!(texel(-1.9), 0);
+        assert_eq!(texel(-1.0), 0);
+        assert_eq!(texel(0.2), 0);
+        assert_ne!(texel(-2.1), 0);
+        assert_ne!(texel(0.3), 0);
+    }
+
+    #[test]
+    fn tile_width_needs_no_whole_lane_groups() {
+        let image_size = UVec2::new(136, 4);
+        let params = KernelParams {
+            max_iterations: 256,
+            fractal: Fractal::default(),
+            detect_glitches: false,
+            time_budget: None,
+            supersample: None,
+            iter_storage: IterStorage::Linear,
+            kernel: Kernel::Simd,
+        };
+        let render = |tex_rect: URect| {
+            // never written by the kernel with glitch detection off
+            let mut buffer = vec![Pixel::GLITCH; (tex_rect.size.x * tex_rect.size.y) as usize];
+            mandelbrot_simd(
+                image_size,
+                tex_rect,
+                DVec2::new(-0.5, 0.0),
+                1.0 / 2.5,
+                &params,
+                Arc::new(AtomicBool::new(false)),
+                &mut buffer,
+            )
+            .unwrap();
+            buffer
+        };
+
+        let whole = render(URect::from_pos_size(UVec2::ZERO, image_size));
+        let narrow = render(URect::from_pos_size(UVec2::ZERO, UVec2::new(130, 4)));
+        for (y, row) in narrow.chunks(130).enumerate() {
+            assert_eq!(row, &whole[y * 136..y * 136 + 130]);
+        }
+    }
+
+    #[test]
+    fn glitches_are_the_seams_between_counts() {
+        let escape = |iters| Pixel {
+            r: IterStorage::Linear.encode(iters),
+            g: 0,
+        };
+        // two flat blocks and the interior, only the seam between the blocks jumps
+        let row = [
+            escape(100),
+            escape(104),
+            escape(200),
+            escape(200),
+            Pixel::default(),
+        ];
+        let buffer = [row, row].concat();
+
+        let jumps = neighbor_jumps(&buffer, UVec2::new(5, 2), IterStorage::Linear);
+        assert_eq!(jumps[..5], [false, true, true, false, false]);
+        assert_eq!(jumps[..5], jumps[5..]);
+
+        // a view f64 resolves keeps its seams, however steep
+        let params = KernelParams {
+            max_iterations: 256,
+            fractal: Fractal::default(),
+            detect_glitches: true,
+            time_budget: None,
+            supersample: None,
+            iter_storage: IterStorage::Linear,
+            kernel: Kernel::Simd,
+        };
+        let tex_rect = URect::from_pos_size(UVec2::ZERO, UVec2::new(64, 64));
+        let mut buffer = vec![Pixel::default(); 64 * 64];
+        mandelbrot_simd(
+            tex_rect.size,
+            tex_rect,
+            DVec2::new(-0.5, 0.0),
+            1.0 / 2.5,
+            &params,
+            Arc::new(AtomicBool::new(false)),
+            &mut buffer,
+        )
+        .unwrap();
+        assert!(!buffer.contains(&Pixel::GLITCH));
+    }
+
+    #[test]
+    fn interior_phase_is_the_attracting_fixed_point_angle() {
+        // inside the main cardioid z settles on z* = (1 - sqrt(1 - 4c)) / 2
+        let c = DVec2::new(0.1, 0.3);
+        let d = DVec2::new(1.0 - 4.0 * c.x, -4.0 * c.y);
+        let (r, angle) = (d.length().sqrt(), d.y.atan2(d.x) / 2.0);
+        let fixed_point = (DVec2::ONE.with_y(0.0) - DVec2::from_angle(angle) * r) / 2.0;
+        let expected = fixed_point.y.atan2(fixed_point.x) / std::f64::consts::TAU + 0.5;
+
+        let pixels = pixel(
+            &Mandelbrot,
+            1000,
+            IterStorage::Linear,
+            f64simd::splat(c.x),
+            f64simd::splat(c.y),
+        );
+
+        assert_eq!(pixels[0].r, 0);
+        let turn = pixels[0].g as f64 / u16::MAX as f64;
+        assert!((turn - expected).abs() < 1e-3, "{} vs {}", turn, expected);
+    }
+
+    // plain f64 reference for one point, in the same `Pixel` encoding as the kernel
+    fn scalar_count(c: DVec2, max_iterations: u32) -> u16 {
+        let mut z = DVec2::ZERO;
+        for iters in 0..max_iterations {
+            z = DVec2::new(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+            if z.length_squared() >= ESCAPE_RADIUS_SQUARED {
+                return 1 + iters as u16;
+            }
+        }
+        0
+    }
+
+    #[test]
+    fn kernel_matches_scalar_iteration() {
+        let max_iterations = 500;
+        let points = [
+            DVec2::new(0.0, 0.0),
+            DVec2::new(-1.0, 0.0),
+            DVec2::new(0.3, 0.0),
+            DVec2::new(0.25, 0.5),
+            DVec2::new(-0.75, 0.1),
+            DVec2::new(-1.9, 0.5),
+            DVec2::new(-0.1011, 0.9563),
+            DVec2::new(2.0, 2.0),
+        ];
+
+        let pixels = pixel(
+            &Mandelbrot,
+            max_iterations,
+            IterStorage::Linear,
+            f64simd::from_array(points.map(|point| point.x)),
+            f64simd::from_array(points.map(|point| point.y)),
+        );
+
+        for (point, pixel) in points.iter().zip(pixels) {
+            assert_eq!(
+                pixel.count(),
+                scalar_count(*point, max_iterations),
+                "{:?}",
+                point
+            );
+        }
+    }
+
+    #[test]
+    fn escape_norm_smooths_the_bands() {
+        for norm in [4.0, 4.5, 7.3, 1e3, 1e18] {
+            let stored = Pixel::escaped(3, norm, IterStorage::Linear).escape_log2_norm();
+            assert!(
+                (stored - norm.log2()).abs() < 2e-3,
+                "{} vs {}",
+                stored,
+                norm
+            );
+        }
+
+        // along the real axis past the cusp the counts step, the smooth ones barely move
+        let smooth = |x: f64| {
+            let pixel = pixel(
+                &Mandelbrot,
+                1000,
+                IterStorage::Linear,
+                f64simd::splat(x),
+                f64simd::splat(0.0),
+            )[0];
+            let ratio = pixel.escape_log2_norm() / ESCAPE_RADIUS_SQUARED.log2();
+            (pixel.count(), pixel.count() as f64 - ratio.log2())
+        };
+        let mut steps = 0;
+        for x in (0..1000).map(|step| 0.5 + step as f64 * 1e-3) {
+            let ((count, value), (next_count, next_value)) = (smooth(x), smooth(x + 1e-3));
+            steps += (count != next_count) as u32;
+            assert!(
+                (value - next_value).abs() < 0.25,
+                "{} {} {}",
+                x,
+                value,
+                next_value
+            );
+        }
+        assert!(steps > 0);
+    }
+
+    #[test]
+    fn log_storage_round_trips() {
+        let storage = IterStorage::Log;
+        assert!(storage.encode(LOG_ITER_LIMIT) < Pixel::GLITCH.count());
+
+        let mut previous = 0;
+        for iters in (0..64).chain((64..LOG_ITER_LIMIT).step_by(997)) {
+            let count = storage.encode(iters);
+            assert!(count > 0 && count >= previous);
+            previous = count;
+
+            // exact at low counts, within half a code step above
+            let error = storage.decode(count).abs_diff(iters) as f64;
+            if iters < 64 {
+                assert_eq!(error, 0.0, "{}", iters);
+            } else {
+                assert!(
+                    error <= 0.5 * (1.0 + iters as f64 / LOG_SCALE) + 1.0,
+                    "{}",
+                    iters
+                );
+            }
+        }
+    }
 }
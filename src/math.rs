@@ -92,6 +92,16 @@ impl DRect {
     pub fn upper_right(&self) -> DVec2 {
         self.pos + self.size
     }
+    /// The rect `t` of the way to `target`, the size changing geometrically. The center keeps
+    /// pace with the size, so it zooms about one point that stays put in the rect.
+    pub fn zoom_towards(&self, target: &Self, t: f64) -> Self {
+        let size = self.size * (target.size / self.size).powf(t);
+        let zoomed = (self.size.x - size.x) / (self.size.x - target.size.x);
+        let zoomed = if zoomed.is_finite() { zoomed } else { t };
+        let center = self.center().lerp(target.center(), zoomed);
+
+        Self::from_center_size(center, size)
+    }
 }
 
 impl std::fmt::Debug for DRect {
@@ -0,0 +1,93 @@
+// Downsamples one mip level of the iteration texture into the next.
+// Iteration counts and escape magnitudes are averaged, as stored or with `MipBlend::Linear` as the
+// iterations and |z|² they decode to. A texel becomes interior if at least half of its sources
+// are, keeping the interior phase of the first interior source.
+
+const GLITCH: u32 = 65535u;
+// must match LOG_SCALE and NORM_LOG_SCALE in mandelbrot_simd.rs
+const LOG_SCALE: f32 = 8192.0;
+const NORM_LOG_SCALE: f32 = 1024.0;
+
+struct PushConstant {
+    // `MipBlend`, nonzero averages the values the codes stand for instead of the codes
+    linear: u32,
+    // `IterStorage`, nonzero for log compressed counts
+    iter_storage: u32,
+};
+var<push_constant> pc: PushConstant;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+
+@group(0)
+@binding(0)
+var source: texture_2d<u32>;
+
+// iterations of a stored escape, `IterStorage::decode`
+fn decode_iters(count: u32) -> f32 {
+    let code = f32(count - 1u);
+    if (pc.iter_storage == 0u) {
+        return code;
+    }
+    return LOG_SCALE * (exp(code / LOG_SCALE) - 1.0);
+}
+
+fn encode_iters(iters: f32) -> u32 {
+    if (pc.iter_storage == 0u) {
+        return 1u + min(u32(round(iters)), GLITCH - 2u);
+    }
+    return 1u + min(u32(round(LOG_SCALE * log(1.0 + iters / LOG_SCALE))), GLITCH - 2u);
+}
+
+// |z|² of a stored escape magnitude, binary exponent plus the mantissa's fraction, see `Pixel::escaped`
+fn decode_norm(magnitude: u32) -> f32 {
+    let stored = f32(magnitude) / NORM_LOG_SCALE;
+    return exp2(floor(stored)) * (1.0 + fract(stored));
+}
+
+fn encode_norm(norm: f32) -> u32 {
+    let exponent = floor(log2(norm));
+    let fraction = clamp(norm / exp2(exponent) - 1.0, 0.0, 1.0);
+    return u32(clamp(round((exponent + fraction) * NORM_LOG_SCALE), 0.0, 65535.0));
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec2<u32> {
+    let base = vec2<u32>(position.xy) * 2u;
+
+    var sum = 0u;
+    var magnitude_sum = 0u;
+    var iters_sum = 0.0;
+    var norm_sum = 0.0;
+    var count = 0u;
+    var interior = 0u;
+    var phase = 0u;
+    for (var i = 0u; i < 4u; i++) {
+        let texel = textureLoad(source, base + vec2<u32>(i & 1u, i >> 1u), 0);
+        let iters = texel.r;
+        if (iters == 0u) {
+            if (interior == 0u) {
+                phase = texel.g;
+            }
+            interior += 1u;
+        } else if (iters != GLITCH) {
+            sum += iters;
+            magnitude_sum += texel.g;
+            iters_sum += decode_iters(iters);
+            norm_sum += decode_norm(texel.g);
+            count += 1u;
+        }
+    }
+
+    if (interior >= 2u || count == 0u) {
+        return vec2<u32>(0u, phase);
+    }
+    if (pc.linear != 0u) {
+        return vec2<u32>(encode_iters(iters_sum / f32(count)), encode_norm(norm_sum / f32(count)));
+    }
+    return vec2<u32>(sum / count, magnitude_sum / count);
+}
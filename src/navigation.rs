@@ -0,0 +1,275 @@
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::anyhow;
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::headless::Frames;
+use crate::math::DRect;
+use crate::view_state::ViewState;
+
+// least time between keyframes while the view keeps moving
+const KEYFRAME_INTERVAL: f64 = 0.05;
+// a gap between changes this long is a pause, the view holds still through it on replay
+const PAUSE: f64 = 0.25;
+// frame rate of `--replay-out`, in recorded seconds times the speed
+const REPLAY_FPS: f64 = 30.0;
+
+/// A flight through the fractal recorded with Shift+T, played back with `--replay`.
+/// There is always at least one keyframe, sorted by time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Navigation {
+    keyframes: Vec<Keyframe>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Keyframe {
+    // seconds since the recording started
+    time: f64,
+    view: ViewState,
+}
+
+/// `--replay`, the navigation and how to play it back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub navigation: Navigation,
+    /// Playback rate, 2 plays twice as fast as recorded.
+    pub speed: f64,
+    /// Renders the frames here without opening a window, `--replay-out`.
+    pub dir: Option<PathBuf>,
+}
+
+impl Replay {
+    pub const DEFAULT_SPEED: f64 = 1.0;
+}
+
+impl Navigation {
+    /// Seconds from the first keyframe to the last.
+    pub fn duration(&self) -> f64 {
+        self.keyframes.last().unwrap().time
+    }
+
+    /// The view `time` seconds in, held at either end. Between keyframes it zooms about
+    /// a fixed point like an autofocus glide, a change of fractal waits for the next keyframe.
+    pub fn view_at(&self, time: f64) -> ViewState {
+        let next = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time);
+        let Some(before) = next.checked_sub(1).map(|index| self.keyframes[index]) else {
+            return self.keyframes[0].view;
+        };
+        let Some(after) = self.keyframes.get(next) else {
+            return before.view;
+        };
+        if after.view.fractal != before.view.fractal {
+            return before.view;
+        }
+
+        let rect =
+            |view: &ViewState| DRect::from_center_size(view.center, DVec2::splat(view.height));
+        let progress = (time - before.time) / (after.time - before.time);
+        let frame = rect(&before.view).zoom_towards(&rect(&after.view), progress);
+
+        ViewState {
+            center: frame.center(),
+            height: frame.size.y,
+            ..before.view
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        // the views serialize like `ViewState::to_json`, nothing that can fail
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Parses JSON from `to_json`, the views are clamped like `ViewState::from_json` does.
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let navigation: Self = serde_json::from_str(json)?;
+
+        if navigation.keyframes.is_empty() {
+            return Err(anyhow!("Navigation has no keyframes"));
+        }
+        let in_order = navigation
+            .keyframes
+            .windows(2)
+            .all(|pair| pair[0].time <= pair[1].time);
+        let first = navigation.keyframes[0].time;
+        if !first.is_finite() || first < 0.0 || !in_order || !navigation.duration().is_finite() {
+            return Err(anyhow!("Navigation keyframes are out of order"));
+        }
+
+        Ok(navigation)
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|err| anyhow!("Failed to read {}: {}", path.display(), err))?;
+        Self::from_json(&json)
+            .map_err(|err| anyhow!("Invalid navigation {}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, self.to_json())?;
+
+        Ok(())
+    }
+}
+
+/// Collects keyframes while exploring, one per `KEYFRAME_INTERVAL` while the view moves.
+pub struct Recorder {
+    start: Instant,
+    keyframes: Vec<Keyframe>,
+    // the last change and when it was, not yet a keyframe if it came too soon after one
+    latest: Keyframe,
+}
+
+impl Recorder {
+    pub fn new(view: ViewState) -> Self {
+        let start = Keyframe { time: 0.0, view };
+
+        Self {
+            start: Instant::now(),
+            keyframes: vec![start],
+            latest: start,
+        }
+    }
+
+    /// Notes a change of the view, called after every pan, zoom or switch.
+    pub fn record(&mut self, view: ViewState) {
+        let keyframe = Keyframe {
+            time: self.start.elapsed().as_secs_f64(),
+            view,
+        };
+
+        if keyframe.time - self.latest.time >= PAUSE {
+            // the view stood still since the latest change, the replay holds it until now
+            self.settle();
+            self.keyframes.push(Keyframe {
+                time: keyframe.time,
+                view: self.latest.view,
+            });
+            self.keyframes.push(keyframe);
+        } else if keyframe.time - self.last_time() >= KEYFRAME_INTERVAL {
+            self.keyframes.push(keyframe);
+        }
+        self.latest = keyframe;
+    }
+
+    /// The navigation up to now, ending on `view`.
+    pub fn finish(mut self, view: ViewState) -> Navigation {
+        self.settle();
+        self.keyframes.push(Keyframe {
+            time: self.start.elapsed().as_secs_f64(),
+            view,
+        });
+
+        Navigation {
+            keyframes: self.keyframes,
+        }
+    }
+
+    // keeps the latest change when it didn't make a keyframe
+    fn settle(&mut self) {
+        if self.latest.time > self.last_time() {
+            self.keyframes.push(self.latest);
+        }
+    }
+
+    fn last_time(&self) -> f64 {
+        self.keyframes.last().unwrap().time
+    }
+}
+
+/// Plays a navigation back in the window, the clock starts with the first frame.
+pub struct Playback {
+    navigation: Navigation,
+    speed: f64,
+    start: Option<Instant>,
+}
+
+impl Playback {
+    pub fn new(replay: &Replay) -> Self {
+        Self {
+            navigation: replay.navigation.clone(),
+            speed: replay.speed,
+            start: None,
+        }
+    }
+
+    /// The view for this frame.
+    pub fn step(&mut self) -> ViewState {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        self.navigation
+            .view_at(start.elapsed().as_secs_f64() * self.speed)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.start.is_some_and(|start| {
+            start.elapsed().as_secs_f64() * self.speed >= self.navigation.duration()
+        })
+    }
+}
+
+/// Renders the navigation at `REPLAY_FPS` without opening a window, saving every frame to the
+/// replay's directory. The view's coloring was applied to `config` when it was loaded.
+pub fn run(config: &Config, replay: &Replay, dir: &Path) -> anyhow::Result<()> {
+    let writer = Frames::new(config, dir)?;
+    let frames = (replay.navigation.duration() / replay.speed * REPLAY_FPS).ceil() as u32 + 1;
+
+    for index in 0..frames {
+        let time = index as f64 / REPLAY_FPS * replay.speed;
+        let view = replay.navigation.view_at(time);
+        let path = writer.save(index, &view)?;
+        println!("Saved {}", path.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn keyframe(time: f64, center: DVec2, height: f64) -> Keyframe {
+        Keyframe {
+            time,
+            view: ViewState {
+                center,
+                height,
+                ..ViewState::default()
+            },
+        }
+    }
+
+    #[test]
+    fn replay_holds_the_ends_and_zooms_between_keyframes() {
+        let navigation = Navigation {
+            keyframes: vec![
+                keyframe(0.0, DVec2::ZERO, 2.0),
+                keyframe(1.0, DVec2::new(1.0, 0.5), 0.5),
+                keyframe(3.0, DVec2::new(1.0, 0.5), 0.5),
+            ],
+        };
+        let navigation = Navigation::from_json(&navigation.to_json()).unwrap();
+
+        assert_eq!(navigation.view_at(-1.0), navigation.keyframes[0].view);
+        assert_eq!(navigation.view_at(1.0), navigation.keyframes[1].view);
+        assert_eq!(navigation.view_at(2.0), navigation.keyframes[1].view);
+        assert_eq!(navigation.view_at(5.0), navigation.keyframes[2].view);
+
+        // halfway in time is halfway in zoom, not in height
+        let middle = navigation.view_at(0.5);
+        assert!((middle.height - 1.0).abs() < 1e-12);
+        assert!(middle.center.distance(DVec2::new(2.0 / 3.0, 1.0 / 3.0)) < 1e-12);
+
+        for json in [
+            r#"{"keyframes": []}"#,
+            r#"{"keyframes": [{"time": 1, "view": {}}, {"time": 0, "view": {}}]}"#,
+            r#"{"keyframes": [{"time": 0, "view": {"height": 0}}]}"#,
+        ] {
+            assert!(Navigation::from_json(json).is_err(), "{}", json);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use crate::gradient::load_gradient;
+
+/// Entries in the palette texture, one period of the coloring.
+pub const PALETTE_SIZE: usize = 256;
+
+/// The palettes cycled with O, all but `Classic` and `Gradient` built in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaletteKind {
+    /// palette.png next to the executable.
+    #[default]
+    Classic,
+    Viridis,
+    Cividis,
+    Inferno,
+    /// The file given with `--gradient`.
+    Gradient,
+}
+
+// sRGB stops of the matplotlib colormaps, interpolated into the full palette
+const VIRIDIS: [u32; 6] = [0x440154, 0x414487, 0x2a788e, 0x22a884, 0x7ad151, 0xfde725];
+const CIVIDIS: [u32; 6] = [0x00204d, 0x31446b, 0x666970, 0x958f78, 0xcbba69, 0xffea46];
+const INFERNO: [u32; 6] = [0x000004, 0x420a68, 0x932667, 0xdd513a, 0xfca50a, 0xfcffa4];
+
+impl PaletteKind {
+    pub fn next(self) -> Self {
+        match self {
+            PaletteKind::Classic => PaletteKind::Viridis,
+            PaletteKind::Viridis => PaletteKind::Cividis,
+            PaletteKind::Cividis => PaletteKind::Inferno,
+            PaletteKind::Inferno => PaletteKind::Gradient,
+            PaletteKind::Gradient => PaletteKind::Classic,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PaletteKind::Classic => "classic",
+            PaletteKind::Viridis => "viridis",
+            PaletteKind::Cividis => "cividis",
+            PaletteKind::Inferno => "inferno",
+            PaletteKind::Gradient => "gradient",
+        }
+    }
+
+    /// Which color vision deficiencies the palette stays readable under.
+    pub fn vision_note(self) -> &'static str {
+        match self {
+            PaletteKind::Classic | PaletteKind::Gradient => "not color-blind safe",
+            // lightness rises monotonically, so every deficiency still sees the ramp
+            PaletteKind::Viridis | PaletteKind::Inferno => {
+                "deuteranopia, protanopia and tritanopia safe"
+            }
+            // blue to yellow only, the axis red-green deficiencies keep
+            PaletteKind::Cividis => "deuteranopia and protanopia safe",
+        }
+    }
+
+    /// The `PALETTE_SIZE` RGBA entries, linear like palette.png is read.
+    pub fn colors(self, gradient: Option<&Path>) -> anyhow::Result<Vec<[u8; 4]>> {
+        let stops = match self {
+            PaletteKind::Classic => return load_classic(),
+            PaletteKind::Gradient => {
+                let gradient = gradient.ok_or_else(|| anyhow!("No gradient file given"))?;
+                return load_gradient(gradient);
+            }
+            PaletteKind::Viridis => &VIRIDIS,
+            PaletteKind::Cividis => &CIVIDIS,
+            PaletteKind::Inferno => &INFERNO,
+        };
+
+        Ok(rasterize(|position| {
+            let t = position * (stops.len() - 1) as f32;
+            let stop = (t as usize).min(stops.len() - 2);
+            lerp_color(
+                srgb_stop(stops[stop]),
+                srgb_stop(stops[stop + 1]),
+                t - stop as f32,
+            )
+        }))
+    }
+}
+
+impl FromStr for PaletteKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classic" => Ok(PaletteKind::Classic),
+            "viridis" => Ok(PaletteKind::Viridis),
+            "cividis" => Ok(PaletteKind::Cividis),
+            "inferno" => Ok(PaletteKind::Inferno),
+            "gradient" => Ok(PaletteKind::Gradient),
+            _ => Err(anyhow!("Unknown palette: {}", s)),
+        }
+    }
+}
+
+fn load_classic() -> anyhow::Result<Vec<[u8; 4]>> {
+    let image = image::open("palette.png")?.into_rgba8();
+    if image.height() != 1 || image.width() as usize != PALETTE_SIZE {
+        return Err(anyhow!(
+            "palette.png must be a single row of {} pixels",
+            PALETTE_SIZE
+        ));
+    }
+
+    Ok(image.pixels().map(|pixel| pixel.0).collect())
+}
+
+/// Samples an sRGB color function of the position in 0..=1 into the palette entries.
+pub fn rasterize(color_at: impl Fn(f32) -> [f32; 3]) -> Vec<[u8; 4]> {
+    (0..PALETTE_SIZE)
+        .map(|index| {
+            let srgb = color_at(index as f32 / (PALETTE_SIZE - 1) as f32);
+            // the surface encodes to sRGB, so the texture has to hold linear values
+            let [r, g, b] =
+                srgb.map(|channel| (srgb_to_linear(channel.clamp(0.0, 1.0)) * 255.0).round() as u8);
+            [r, g, b, 255]
+        })
+        .collect()
+}
+
+pub fn lerp_color(from: [f32; 3], to: [f32; 3], t: f32) -> [f32; 3] {
+    std::array::from_fn(|channel| from[channel] + (to[channel] - from[channel]) * t)
+}
+
+fn srgb_stop(rgb: u32) -> [f32; 3] {
+    [rgb >> 16, rgb >> 8, rgb].map(|channel| (channel & 0xff) as f32 / 255.0)
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn built_in_palettes_run_dark_to_light() {
+        let luminance = |color: [u8; 4]| {
+            0.2126 * color[0] as f32 + 0.7152 * color[1] as f32 + 0.0722 * color[2] as f32
+        };
+
+        for kind in [
+            PaletteKind::Viridis,
+            PaletteKind::Cividis,
+            PaletteKind::Inferno,
+        ] {
+            let colors = kind.colors(None).unwrap();
+            assert_eq!(colors.len(), PALETTE_SIZE);
+            // readable without hue: each stop is at least as bright as the one before
+            assert!(colors
+                .windows(2)
+                .all(|pair| luminance(pair[1]) + 0.5 >= luminance(pair[0])));
+            assert_eq!(kind.name().parse::<PaletteKind>().unwrap(), kind);
+        }
+    }
+}
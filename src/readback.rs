@@ -0,0 +1,170 @@
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::anyhow;
+use glam::UVec2;
+
+use crate::math::DRect;
+
+/// A screenshot asked for by the user, rendered on the next `render`.
+#[derive(Debug)]
+pub struct ScreenshotRequest {
+    pub path: PathBuf,
+    // captured at request time, the view may move before the next frame is rendered
+    pub frame_rect: DRect,
+    pub size: UVec2,
+}
+
+/// A GPU-to-CPU copy waiting for its buffer to be mapped, written to `path` once done.
+#[derive(Debug)]
+pub struct PendingReadback {
+    path: PathBuf,
+    size: UVec2,
+    format: wgpu::TextureFormat,
+    padded_bytes_per_row: u32,
+    buffer: wgpu::Buffer,
+    mapped: MapResult,
+}
+
+impl PendingReadback {
+    /// Row pitch of a `width` wide copy of `format`, padded to what buffer copies require.
+    pub fn padded_bytes_per_row(width: u32, format: wgpu::TextureFormat) -> u32 {
+        let bytes_per_row = width as u64 * format.block_copy_size(None).unwrap() as u64;
+        let padded = bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64);
+        u32::try_from(padded)
+            .unwrap_or_else(|_| panic!("{} texel rows exceed u32 byte counts", width))
+    }
+
+    /// Starts mapping `buffer`, which must already hold the copied texture.
+    pub fn new(
+        path: PathBuf,
+        size: UVec2,
+        format: wgpu::TextureFormat,
+        buffer: wgpu::Buffer,
+    ) -> PendingReadback {
+        PendingReadback {
+            path,
+            size,
+            format,
+            padded_bytes_per_row: Self::padded_bytes_per_row(size.x, format),
+            mapped: map_read(&buffer),
+            buffer,
+        }
+    }
+
+    /// Whether mapping finished, `take_image` reports when it failed.
+    pub fn is_mapped(&self) -> bool {
+        self.mapped.get().is_some()
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Copies the mapped rows into an image and releases the buffer.
+    pub fn take_image(self) -> anyhow::Result<image::DynamicImage> {
+        if let Some(Err(err)) = self.mapped.get() {
+            return Err(anyhow!("Failed to map the buffer: {}", err));
+        }
+
+        let row_len = self.size.x as usize * self.format.block_copy_size(None).unwrap() as usize;
+        let mut data = Vec::with_capacity(row_len * self.size.y as usize);
+        {
+            let mapped = self.buffer.slice(..).get_mapped_range();
+            for row in mapped.chunks(self.padded_bytes_per_row as usize) {
+                data.extend_from_slice(&row[..row_len]);
+            }
+        }
+        self.buffer.unmap();
+
+        let (width, height) = (self.size.x, self.size.y);
+        let image = match self.format {
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+                data.chunks_exact_mut(4).for_each(|pixel| pixel.swap(0, 2));
+                opaque_rgba(width, height, data)
+            }
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => {
+                opaque_rgba(width, height, data)
+            }
+            // raw iteration counts
+            wgpu::TextureFormat::R16Uint => {
+                let data = bytemuck::pod_collect_to_vec::<u8, u16>(&data);
+                image::DynamicImage::ImageLuma16(
+                    image::ImageBuffer::from_raw(width, height, data).unwrap(),
+                )
+            }
+            // counts and interior phases, only the counts are kept
+            wgpu::TextureFormat::Rg16Uint => {
+                let data: Vec<u16> = bytemuck::pod_collect_to_vec::<u8, u16>(&data)
+                    .into_iter()
+                    .step_by(2)
+                    .collect();
+                image::DynamicImage::ImageLuma16(
+                    image::ImageBuffer::from_raw(width, height, data).unwrap(),
+                )
+            }
+            format => return Err(anyhow!("Unsupported readback format: {:?}", format)),
+        };
+
+        Ok(image)
+    }
+}
+
+/// One iteration texel copied back for the hover readout.
+#[derive(Debug)]
+pub struct TexelProbe {
+    buffer: wgpu::Buffer,
+    mapped: MapResult,
+}
+
+impl TexelProbe {
+    /// Starts mapping `buffer`, which must already hold the copied texel.
+    pub fn new(buffer: wgpu::Buffer) -> TexelProbe {
+        TexelProbe {
+            mapped: map_read(&buffer),
+            buffer,
+        }
+    }
+
+    /// Whether mapping finished, `take_count` reports when it failed.
+    pub fn is_mapped(&self) -> bool {
+        self.mapped.get().is_some()
+    }
+
+    /// The texel's raw count channel, releasing the buffer.
+    pub fn take_count(self) -> anyhow::Result<u16> {
+        if let Some(Err(err)) = self.mapped.get() {
+            return Err(anyhow!("Failed to map the buffer: {}", err));
+        }
+
+        let count = {
+            let mapped = self.buffer.slice(..).get_mapped_range();
+            u16::from_le_bytes([mapped[0], mapped[1]])
+        };
+        self.buffer.unmap();
+
+        Ok(count)
+    }
+}
+
+// set once mapping finishes, with the error when it failed
+type MapResult = Arc<OnceLock<Result<(), wgpu::BufferAsyncError>>>;
+
+fn map_read(buffer: &wgpu::Buffer) -> MapResult {
+    let mapped = MapResult::default();
+    let mapped_clone = mapped.clone();
+    buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, move |result| {
+            let _ = mapped_clone.set(result);
+        });
+
+    mapped
+}
+
+// the frame is opaque, drop whatever alpha the surface format left behind
+fn opaque_rgba(width: u32, height: u32, mut data: Vec<u8>) -> image::DynamicImage {
+    data.chunks_exact_mut(4)
+        .for_each(|pixel| pixel[3] = u8::MAX);
+    image::DynamicImage::ImageRgba8(image::RgbaImage::from_raw(width, height, data).unwrap())
+}
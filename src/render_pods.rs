@@ -1,7 +1,9 @@
 use std::mem::size_of;
 
 use bytemuck::{Pod, Zeroable};
-use glam::{Mat4, UVec2, Vec2};
+use glam::{Mat4, UVec2, Vec2, Vec4};
+
+use crate::mandel_texture::DEFAULT_PALETTE_PERIOD;
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
@@ -19,7 +21,29 @@ pub struct ScreenRect([Vert; 4]);
 pub struct PushConst {
     pub proj_mat: Mat4,
     pub texture_size: Vec2,
-    _padding: Vec2,
+    pub mip_level: u32,
+    pub interior_coloring: u32,
+    pub boundary_color: Vec4,
+    pub iter_storage: u32,
+    // 0 shows the previous palette, 1 the current one
+    pub palette_mix: f32,
+    pub target_size: Vec2,
+    pub crosshair_color: Vec4,
+    // degree of the iteration for smooth coloring
+    pub smooth_degree: f32,
+    // `ColoringMode`
+    pub coloring_mode: u32,
+    // top left of the drawn area in the window, nonzero when letterboxed
+    pub viewport_offset: Vec2,
+    // solid interior, independent of the palette
+    pub interior_color: Vec4,
+    // iterations added to every escape before the palette lookup
+    pub color_offset: f32,
+    // nonzero blurs band edges in the fragment shader, see `MandelTexture::set_band_aa`
+    pub band_aa: u32,
+    // iterations of one pass through the palette, see `MandelTexture::set_palette_period`
+    pub palette_period: f32,
+    pub _padding2: f32,
 }
 
 impl Default for ScreenRect {
@@ -89,7 +113,21 @@ impl PushConst {
         Self {
             proj_mat: Mat4::default(),
             texture_size: Vec2::default(),
-            _padding: Vec2::default(),
+            mip_level: 0,
+            interior_coloring: 0,
+            boundary_color: Vec4::ONE,
+            iter_storage: 0,
+            palette_mix: 1.0,
+            target_size: Vec2::default(),
+            crosshair_color: Vec4::ONE,
+            smooth_degree: 0.0,
+            coloring_mode: 0,
+            viewport_offset: Vec2::ZERO,
+            interior_color: Vec4::W,
+            color_offset: 0.0,
+            band_aa: 0,
+            palette_period: DEFAULT_PALETTE_PERIOD as f32,
+            _padding2: 0.0,
         }
     }
     pub fn as_bytes(&self) -> &[u8] {
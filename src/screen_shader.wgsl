@@ -1,11 +1,43 @@
 struct VertexOutput {
     @location(0) tex_coord: vec2<f32> ,
+    // push constants are only visible to the vertex stage
+    @location(1) @interpolate(flat) interior_coloring: u32,
+    @location(2) @interpolate(flat) iter_storage: u32,
+    @location(3) @interpolate(flat) palette_mix: f32,
+    @location(4) @interpolate(flat) smooth_degree: f32,
+    @location(5) @interpolate(flat) color_offset: f32,
+    @location(6) @interpolate(flat) interior_color: vec3<f32>,
+    @location(7) @interpolate(flat) coloring_mode: u32,
+    @location(8) @interpolate(flat) band_aa: u32,
+    @location(9) @interpolate(flat) palette_period: f32,
     @builtin(position) position: vec4<f32>,
 };
 
 
 struct PushConstant {
     proj_mat: mat4x4<f32>,
+    texture_size: vec2<f32>,
+    mip_level: u32,
+    interior_coloring: u32,
+    boundary_color: vec4<f32>,
+    iter_storage: u32,
+    // 0 shows the previous palette, 1 the current one
+    palette_mix: f32,
+    target_size: vec2<f32>,
+    crosshair_color: vec4<f32>,
+    // degree of the iteration for smooth coloring
+    smooth_degree: f32,
+    coloring_mode: u32,
+    // top left of the drawn area in the window, nonzero when letterboxed
+    viewport_offset: vec2<f32>,
+    // solid interior, independent of the palette
+    interior_color: vec4<f32>,
+    // iterations added to every escape before the palette lookup
+    color_offset: f32,
+    // nonzero blurs the palette lookup over the pixel's iteration change, see `band_width`
+    band_aa: u32,
+    // iterations of one pass through the palette, shorter makes tighter bands
+    palette_period: f32,
 };
 var<push_constant> pc: PushConstant;
 
@@ -18,11 +50,24 @@ fn vs_main(
     var result: VertexOutput;
     result.position = pc.proj_mat * position;
     result.tex_coord = tex_coord;
+    result.interior_coloring = pc.interior_coloring;
+    result.iter_storage = pc.iter_storage;
+    result.palette_mix = pc.palette_mix;
+    result.smooth_degree = pc.smooth_degree;
+    result.color_offset = pc.color_offset;
+    result.interior_color = pc.interior_color.rgb;
+    result.coloring_mode = pc.coloring_mode;
+    result.band_aa = pc.band_aa;
+    result.palette_period = pc.palette_period;
 
     return result;
 }
 
 
+// marks pixels beyond the kernel's precision that break off from a neighbor, only written with
+// glitch detection enabled
+const GLITCH: u32 = 65535u;
+
 @group(0)
 @binding(0)
 var the_sampler: sampler;
@@ -32,16 +77,311 @@ var color: texture_2d<u32>;
 @group(0)
 @binding(2)
 var palette: texture_1d<f32>;
+// the palette before the last switch, faded out over `palette_mix`
+@group(0)
+@binding(3)
+var previous_palette: texture_1d<f32>;
+
+// must match `InteriorColoring` in mandel_texture.rs
+const INTERIOR_SOLID: u32 = 0u;
+const INTERIOR_PHASE: u32 = 1u;
+
+// must match `ColoringMode` in mandel_texture.rs
+const COLORING_BANDS: u32 = 0u;
+const COLORING_SMOOTH: u32 = 1u;
+
+// must match `IterStorage` and LOG_SCALE in mandelbrot_simd.rs
+const STORAGE_LOG: u32 = 1u;
+const LOG_SCALE: f32 = 8192.0;
+// must match NORM_LOG_SCALE in mandelbrot_simd.rs and ESCAPE_RADIUS_SQUARED in fractal.rs
+const NORM_LOG_SCALE: f32 = 1024.0;
+const ESCAPE_RADIUS_SQUARED: f32 = 5.0;
+
+// the stored value of an escape as `1 + iters` however it was compressed, the interior stays 0
+fn escape_count(stored: u32, iter_storage: u32) -> u32 {
+    if (iter_storage != STORAGE_LOG || stored == 0u) {
+        return stored;
+    }
+
+    let code = f32(stored - 1u);
+    return 1u + u32(round(LOG_SCALE * (exp(code / LOG_SCALE) - 1.0)));
+}
+
+// log2 |z|² at the escape, undoing the exponent plus mantissa fraction `Pixel::escaped` stores
+fn escape_log2_norm(stored: u32) -> f32 {
+    let value = f32(stored) / NORM_LOG_SCALE;
+    let exponent = floor(value);
+    return exponent + log2(1.0 + value - exponent);
+}
+
+// the stored escape count plus the fraction between bands from how far past the radius z went,
+// count + 1 right at the radius down to count a full iteration past it
+fn smooth_count(count: u32, stored_norm: u32, degree: f32) -> f32 {
+    let ratio = max(escape_log2_norm(stored_norm) / log2(ESCAPE_RADIUS_SQUARED), 1.0);
+    return f32(count) + 1.0 - clamp(log2(ratio) / log2(degree), 0.0, 1.0);
+}
+
+fn sample_palette(u: f32, palette_mix: f32) -> vec3<f32> {
+    let previous = textureSample(previous_palette, the_sampler, u).rgb;
+    return mix(previous, textureSample(palette, the_sampler, u).rgb, palette_mix);
+}
+
+fn palette_color(iters: f32, period: f32, palette_mix: f32) -> vec3<f32> {
+    let norm = (max(iters - 1.0, 0.0) % period) / period;
+    let b = clamp(iters, 0.0, 1.0) * clamp(iters - 1.0, 0.0, 16.0) / 16.0;
+
+    let u = pow(norm, 0.4);
+    return sample_palette(u, palette_mix) * b;
+}
+
+// `palette_color` averaged over `width` iterations around `iters`, a box filter along the palette
+fn filtered_palette_color(iters: f32, width: f32, period: f32, palette_mix: f32) -> vec3<f32> {
+    if (width == 0.0) {
+        return palette_color(iters, period, palette_mix);
+    }
+
+    var rgb = vec3<f32>(0.0);
+    for (var i = 0; i < 8; i++) {
+        let offset = (f32(i) - 3.5) / 8.0 * width;
+        rgb += palette_color(iters + offset, period, palette_mix);
+    }
+    return rgb / 8.0;
+}
+
+// the coloring settings, from the flat varyings
+struct Coloring {
+    interior_coloring: u32,
+    iter_storage: u32,
+    palette_mix: f32,
+    smooth_degree: f32,
+    color_offset: f32,
+    interior_color: vec3<f32>,
+    coloring_mode: u32,
+    palette_period: f32,
+    // iterations the palette lookup is blurred over, 0 without band anti-aliasing
+    band_width: f32,
+};
+
+// the escape count as colored, with the fraction between bands in smooth coloring
+fn escape_iterations(texel: vec4<u32>, coloring: Coloring) -> f32 {
+    let count = escape_count(texel.r, coloring.iter_storage);
+    if (coloring.coloring_mode == COLORING_SMOOTH) {
+        return smooth_count(count, texel.g, coloring.smooth_degree);
+    }
+    return f32(count);
+}
+
+// how much the iterations change across the pixel, from the screen-space derivatives.
+// The palette only blurs as far as the counts vary smoothly, the stepped counts of bands
+// barely soften, smooth coloring gives the best results.
+// Must be called from uniform control flow, the toggle only selects the result.
+fn band_width(coord: vec2<f32>, coloring: Coloring, band_aa: u32) -> f32 {
+    let texel = textureLoad(color, vec2<u32>(coord), 0);
+    let escaped = texel.r != 0u && texel.r != GLITCH;
+    let iterations = select(0.0, escape_iterations(texel, coloring), escaped);
+    let width = min(fwidth(iterations), coloring.palette_period);
+    // next to the interior or a glitch the change says nothing about the escapes, left sharp
+    let edge = fwidth(select(0.0, 1.0, escaped)) != 0.0;
+
+    return select(0.0, width, band_aa != 0u && escaped && !edge);
+}
+
+fn texel_color(coord: vec2<f32>, coloring: Coloring) -> vec3<f32> {
+    let texel = textureLoad(color, vec2<u32>(coord), 0);
+    let iters = texel.r;
+    if (iters == GLITCH) {
+        return vec3<f32>(1.0, 0.0, 1.0);
+    }
+    if (iters == 0u && coloring.interior_coloring == INTERIOR_PHASE) {
+        // dimmed so the interior still reads as interior
+        let u = f32(texel.g) / 65535.0;
+        return sample_palette(u, coloring.palette_mix) * 0.35;
+    }
+
+    // 0 is only ever the interior, its color needn't continue the escapes' palette
+    if (iters == 0u) {
+        return coloring.interior_color;
+    }
+    let iterations = escape_iterations(texel, coloring) + coloring.color_offset;
+    return filtered_palette_color(
+        iterations,
+        coloring.band_width,
+        coloring.palette_period,
+        coloring.palette_mix,
+    );
+}
 
 @fragment
 fn fs_main(vertex: VertexOutput) -> @location(0) vec4<f32> {
-    let iters = textureLoad(color, vec2<u32>(vertex.tex_coord), 0).r;
-    let norm = f32((iters - 1) % 768) / 768.0;
-    let b = clamp(f32(iters), 0.0, 1.0) * clamp(f32(iters - 1), 0.0, 16.0) / 16.0;
+    // texels per screen pixel, above 1 with a render scale above 1
+    let footprint = fwidth(vertex.tex_coord);
+    var coloring = Coloring(
+        vertex.interior_coloring,
+        vertex.iter_storage,
+        vertex.palette_mix,
+        vertex.smooth_degree,
+        vertex.color_offset,
+        vertex.interior_color,
+        vertex.coloring_mode,
+        vertex.palette_period,
+        0.0,
+    );
+    coloring.band_width = band_width(vertex.tex_coord, coloring, vertex.band_aa);
+    if (max(footprint.x, footprint.y) <= 1.0) {
+        return vec4<f32>(texel_color(vertex.tex_coord, coloring), 1.0);
+    }
 
-    let u = pow(norm, 0.4);
-    let rgb = textureSample(palette, the_sampler, u).rgb;
-    return vec4<f32>(rgb * b, 1.0);
+    // supersampling, the colors of the texels under the pixel are averaged rather than their counts
+    let offset = footprint * 0.25;
+    let rgb = texel_color(vertex.tex_coord + vec2<f32>(-offset.x, -offset.y), coloring)
+        + texel_color(vertex.tex_coord + vec2<f32>(offset.x, -offset.y), coloring)
+        + texel_color(vertex.tex_coord + vec2<f32>(-offset.x, offset.y), coloring)
+        + texel_color(vertex.tex_coord + vec2<f32>(offset.x, offset.y), coloring);
+    return vec4<f32>(rgb * 0.25, 1.0);
+}
+
+
+// Boundary overlay: interior texels touching an escaped neighbour, drawn over the fractal.
+
+struct BoundaryOutput {
+    @location(0) tex_coord: vec2<f32>,
+    @location(1) @interpolate(flat) color: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_boundary(
+    @location(0) position: vec4<f32>,
+    @location(1) tex_coord: vec2<f32>,
+) -> BoundaryOutput {
+    var result: BoundaryOutput;
+    result.position = pc.proj_mat * position;
+    result.tex_coord = tex_coord;
+    result.color = pc.boundary_color;
+
+    return result;
+}
+
+fn is_escaped(coord: vec2<i32>) -> bool {
+    let size = vec2<i32>(textureDimensions(color));
+    let iters = textureLoad(color, clamp(coord, vec2<i32>(0), size - 1), 0).r;
+    return iters != 0u && iters != GLITCH;
+}
 
+@fragment
+fn fs_boundary(vertex: BoundaryOutput) -> @location(0) vec4<f32> {
+    let coord = vec2<i32>(vertex.tex_coord);
+    if (textureLoad(color, coord, 0).r != 0u) {
+        discard;
+    }
+
+    let edge = is_escaped(coord + vec2<i32>(1, 0))
+        || is_escaped(coord - vec2<i32>(1, 0))
+        || is_escaped(coord + vec2<i32>(0, 1))
+        || is_escaped(coord - vec2<i32>(0, 1));
+    if (!edge) {
+        discard;
+    }
+
+    return vertex.color;
 }
 
+
+// Legend: a strip along the bottom edge showing one palette period, iterations growing to the right.
+
+const LEGEND_TICKS: f32 = 6.0;
+
+struct LegendOutput {
+    @location(0) u: f32,
+    @location(1) @interpolate(flat) palette_mix: f32,
+    @location(2) @interpolate(flat) color_offset: f32,
+    @location(3) @interpolate(flat) palette_period: f32,
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_legend(@builtin(vertex_index) index: u32) -> LegendOutput {
+    let corner = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+
+    var result: LegendOutput;
+    result.position = vec4<f32>(mix(-0.9, 0.9, corner.x), mix(-0.97, -0.91, corner.y), 0.0, 1.0);
+    result.u = corner.x;
+    result.palette_mix = pc.palette_mix;
+    result.color_offset = pc.color_offset;
+    result.palette_period = pc.palette_period;
+
+    return result;
+}
+
+@fragment
+fn fs_legend(vertex: LegendOutput) -> @location(0) vec4<f32> {
+    let ticks = vertex.u * LEGEND_TICKS;
+    if (fract(ticks) < 0.004 * LEGEND_TICKS) {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+
+    let iters = 1.0 + vertex.u * (vertex.palette_period - 1.0);
+    let rgb = palette_color(iters + vertex.color_offset, vertex.palette_period, vertex.palette_mix);
+    return vec4<f32>(rgb, 1.0);
+}
+
+
+// Crosshair: short lines through the view center, with a gap so the target itself stays visible.
+
+const CROSSHAIR_GAP: f32 = 4.0;
+const CROSSHAIR_ARM: f32 = 16.0;
+
+struct CrosshairOutput {
+    @location(0) @interpolate(flat) center: vec2<f32>,
+    @location(1) @interpolate(flat) color: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_crosshair(@builtin(vertex_index) index: u32) -> CrosshairOutput {
+    let corner = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+
+    var result: CrosshairOutput;
+    result.position = vec4<f32>(corner * 2.0 - 1.0, 0.0, 1.0);
+    result.center = pc.viewport_offset + pc.target_size * 0.5;
+    result.color = pc.crosshair_color;
+
+    return result;
+}
+
+@fragment
+fn fs_crosshair(vertex: CrosshairOutput) -> @location(0) vec4<f32> {
+    // fragment positions are pixel centers, in pixels from the top left
+    let offset = abs(vertex.position.xy - vertex.center);
+    let along = max(offset.x, offset.y);
+    if (min(offset.x, offset.y) >= 1.0 || along < CROSSHAIR_GAP || along > CROSSHAIR_ARM) {
+        discard;
+    }
+
+    return vertex.color;
+}
+
+
+// Orbit: the clicked point's iteration as a line strip, vertices in the plane relative to it.
+
+const ORBIT_COLOR: vec4<f32> = vec4<f32>(1.0, 0.85, 0.2, 1.0);
+
+@vertex
+fn vs_orbit(@location(0) position: vec2<f32>) -> @builtin(position) vec4<f32> {
+    return pc.proj_mat * vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_orbit() -> @location(0) vec4<f32> {
+    return ORBIT_COLOR;
+}
+
+
+// Measurement: the segment between two clicked points, drawn with `vs_orbit`.
+
+const MEASURE_COLOR: vec4<f32> = vec4<f32>(0.3, 0.9, 1.0, 1.0);
+
+@fragment
+fn fs_measure() -> @location(0) vec4<f32> {
+    return MEASURE_COLOR;
+}
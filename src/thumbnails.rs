@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use glam::UVec2;
+
+use crate::config::Config;
+use crate::headless::{render_image, Palette};
+use crate::view_state::ViewState;
+
+// the width must be a multiple of the kernel's SIMD lane count
+pub const THUMBNAIL_SIZE: UVec2 = UVec2::new(256, 160);
+const GAP: u32 = 4;
+
+/// Renders every view link in `links` into one contact sheet saved to `output`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thumbnails {
+    pub links: PathBuf,
+    pub output: PathBuf,
+}
+
+impl Thumbnails {
+    pub const DEFAULT_OUTPUT: &'static str = "thumbnails.png";
+}
+
+/// Reads one view link per line, as printed by the L key; blank lines and `#` comments are skipped.
+fn read_links(thumbnails: &Thumbnails) -> anyhow::Result<Vec<ViewState>> {
+    let text = std::fs::read_to_string(&thumbnails.links)?;
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ViewState::from_link)
+        .collect()
+}
+
+/// Renders the thumbnails without opening a window, one thread per core across the views.
+pub fn run(config: &Config, thumbnails: &Thumbnails) -> anyhow::Result<()> {
+    let views = read_links(thumbnails)?;
+    if views.is_empty() {
+        return Err(anyhow!("No view links in {}", thumbnails.links.display()));
+    }
+    let palette = Palette::from_config(config)?;
+
+    let thread_count = std::thread::available_parallelism()?.get();
+    let chunk_size = views.len().div_ceil(thread_count);
+    let images = std::thread::scope(|scope| {
+        let handles: Vec<_> = views
+            .chunks(chunk_size)
+            .map(|views| {
+                let palette = &palette;
+                scope.spawn(move || {
+                    views
+                        .iter()
+                        // the views are already spread across cores
+                        .map(|view| render_image(view, THUMBNAIL_SIZE, config, palette, 1))
+                        .collect::<anyhow::Result<Vec<_>>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<anyhow::Result<Vec<_>>>()
+    })?;
+    let images: Vec<_> = images.into_iter().flatten().collect();
+
+    let columns = (images.len() as f64).sqrt().ceil() as u32;
+    let rows = (images.len() as u32).div_ceil(columns);
+    let cell = THUMBNAIL_SIZE + GAP;
+    let mut sheet = image::RgbImage::new(columns * cell.x - GAP, rows * cell.y - GAP);
+    for (index, image) in images.iter().enumerate() {
+        let index = index as u32;
+        let pos = UVec2::new(index % columns, index / columns) * cell;
+        image::imageops::replace(&mut sheet, image, pos.x as i64, pos.y as i64);
+    }
+
+    sheet.save(&thumbnails.output)?;
+    println!(
+        "Saved {} thumbnails to {}",
+        images.len(),
+        thumbnails.output.display()
+    );
+
+    Ok(())
+}
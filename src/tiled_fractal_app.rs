@@ -1,115 +1,436 @@
 #![allow(unused_parens)]
 
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use anyhow::anyhow;
 use bytemuck::Zeroable;
 use glam::{DVec2, IVec2, UVec2};
 use parking_lot::Mutex;
 use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
 use winit::event_loop::EventLoopProxy;
+use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
 
+use crate::config::Config;
 use crate::env::is_debug_build;
-use crate::event::{ElementState, Event, EventResult, MouseButtons};
-use crate::mandel_texture::MandelTexture;
-use crate::math::DRect;
+use crate::event::{ElementState, Event, EventResult, MouseButtons, TouchGesture, Touches};
+use crate::fractal::{Fractal, FractalKind, ESCAPE_RADIUS_SQUARED};
+use crate::iter_dump::IterDump;
+use crate::key_bindings::{action_for, help_text, Action};
+use crate::locations::LOCATIONS;
+use crate::mandel_texture::{ColoringMode, InteriorColoring, MandelTexture, LEGEND_TICKS};
+use crate::mandelbrot_simd::Kernel;
+use crate::math::{DRect, URect};
+use crate::navigation::{Playback, Recorder};
+use crate::palette::PaletteKind;
+use crate::view_state::{Coloring, ViewState};
 use crate::{RenderContext, WindowContext};
 
+const ITER_STEP: f64 = 1.5;
+// palette period change per Shift+[ and Shift+] press
+const PALETTE_PERIOD_STEP: f64 = 1.5;
+// Julia constant change per Shift+arrow press, small enough to watch the set morph
+const JULIA_NUDGE: f64 = 0.002;
+// frame size change per wheel notch at the default zoom speed
+const ZOOM_PER_WHEEL_STEP: f64 = 1.0875;
+// wheel notches per second while the right mouse button is held
+const HOLD_ZOOM_STEPS_PER_SECOND: f32 = 8.0;
+// frame size change of an autofocus glide, `--autofocus-zoom`
+pub const DEFAULT_AUTOFOCUS_ZOOM: f64 = 4.0;
+pub const MAX_AUTOFOCUS_ZOOM: f64 = 64.0;
+// cells of 8 texels around a point whose detail counts for it, larger favors wide busy regions
+// over a single sharp edge, `--autofocus-radius`
+pub const DEFAULT_AUTOFOCUS_RADIUS: u32 = 4;
+pub const MAX_AUTOFOCUS_RADIUS: u32 = 32;
+const AUTOFOCUS_GLIDE: Duration = Duration::from_millis(1200);
+pub const DEFAULT_IDLE_DELAY: Duration = Duration::from_millis(1000);
+// time constant of the drag focus filter, see `smooth_focus`
+const FOCUS_SMOOTHING: Duration = Duration::from_millis(100);
+// a pause this long between moves starts the next drag's focus at the cursor
+const FOCUS_RESET: Duration = Duration::from_millis(300);
+// how long the title names the coloring mode after a switch
+const COLORING_LABEL: Duration = Duration::from_millis(2000);
+// frame rate caps cycled with F, `None` renders as fast as frames are requested
+const FPS_CAPS: [Option<u32>; 3] = [None, Some(60), Some(30)];
+// render scales stepped through with , and .
+const RENDER_SCALES: [f64; 5] = [0.5, 0.75, 1.0, 1.5, 2.0];
+// smallest `--render-region`, and the regions stepped through with < and >
+pub const MIN_RENDER_REGION: f64 = 0.25;
+const RENDER_REGIONS: [f64; 4] = [0.5, 0.7, 0.85, 1.0];
+
 enum ManipulateState {
     Idle,
     Drag,
 }
 
+/// The point wheel and held zooms keep in place, pans always follow the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZoomAnchor {
+    #[default]
+    Cursor,
+    /// The view center, steady for keyboard zooms and recordings.
+    Center,
+    /// A fractal point pinned with Shift+C, the view center until one is.
+    Pinned,
+}
+
+impl ZoomAnchor {
+    pub fn next(self) -> Self {
+        match self {
+            ZoomAnchor::Cursor => ZoomAnchor::Center,
+            ZoomAnchor::Center => ZoomAnchor::Pinned,
+            ZoomAnchor::Pinned => ZoomAnchor::Cursor,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ZoomAnchor::Cursor => "cursor",
+            ZoomAnchor::Center => "center",
+            ZoomAnchor::Pinned => "pinned",
+        }
+    }
+}
+
+impl FromStr for ZoomAnchor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cursor" => Ok(ZoomAnchor::Cursor),
+            "center" => Ok(ZoomAnchor::Center),
+            "pinned" => Ok(ZoomAnchor::Pinned),
+            _ => Err(anyhow!("Unknown zoom anchor: {}", s)),
+        }
+    }
+}
+
 pub struct TiledFractalApp {
+    // the view's part of the window, all of it unless letterboxed
     window_size: UVec2,
+    surface_size: UVec2,
+    // "fit" keeps the frame at this aspect and letterboxes the window, `None` fills it
+    fit_aspect: Option<f64>,
+    // share of the window's width and height the view is drawn in, centered with bars around it
+    render_region: f64,
+    // physical pixels per logical pixel, and the factor `frame_rect` was last sized for
+    scale_factor: f64,
+    frame_scale_factor: f64,
     event_loop_proxy: Arc<Mutex<EventLoopProxy<UserEvent>>>,
     runtime: Runtime,
 
+    minimized: bool,
     manipulate_state: ManipulateState,
+    mouse_position: UVec2,
+    // where the left button went down, a release at the same spot is a click
+    press_position: Option<UVec2>,
+    // clicks pick the point whose orbit is drawn, see `Fractal::orbit`
+    orbit_mode: bool,
+    orbit_point: Option<DVec2>,
+    // point, fractal and iterations the drawn orbit was computed for
+    orbit_inputs: Option<(DVec2, Fractal, u32)>,
+    // clicks pick the points measured, a third click starts over
+    measure_mode: bool,
+    measure_points: Vec<DVec2>,
+    // zoom direction in wheel notches and the time of the last step, while the right button is held
+    hold_zoom: Option<(f32, Instant)>,
+    // frame rects an autofocus glides between and when it started, any pan or zoom stops it
+    glide: Option<(DRect, DRect, Instant)>,
+    // `--replay` in the window, any pan or zoom stops it
+    playback: Option<Playback>,
+    // collects the navigation between two presses of Shift+T
+    recorder: Option<Recorder>,
+    autofocus_zoom: f64,
+    autofocus_radius: u32,
+    // the filtered cursor of the last move relative to the frame, and when it was
+    drag_focus: Option<(DVec2, Instant)>,
+    touches: Touches,
+    // held Shift/Ctrl/Alt/Super, current as of the event being handled
+    modifiers: ModifiersState,
+    // false: the content follows the cursor, true: the cursor moves the camera
+    invert_pan: bool,
+    pan_sensitivity: f64,
+    // multiplies the wheel notches of every zoom event
+    zoom_speed: f64,
+    zoom_anchor: ZoomAnchor,
+    // the fractal point `ZoomAnchor::Pinned` zooms about
+    zoom_pin: Option<DVec2>,
+    fps_cap: Option<u32>,
+    // the key list was printed, the next key press only dismisses it
+    show_help: bool,
+    show_compute_stats: bool,
+    // the GPU and backend in the title, for bug reports
+    show_adapter_info: bool,
+    adapter_info: wgpu::AdapterInfo,
+    // created by the first copy and kept, on X11 the copied text goes with it when dropped
+    clipboard: Option<arboard::Clipboard>,
+    // when the coloring mode was last switched, its name stays in the title for `COLORING_LABEL`
+    coloring_switch: Option<Instant>,
+    // CPU time of the last `render`
+    frame_time: Duration,
+    // creation time, until the first full quality view is complete
+    startup: Option<Instant>,
+    // print how long the startup passes took, with `--tile-timings`
+    measure_startup: bool,
 
     frame_rect: DRect,
     aspect: DVec2,
+    fractal: Fractal,
+    // entry of `LOCATIONS` on screen, cleared once the view moves away from it
+    location_index: Option<usize>,
 
     mandel_texture: MandelTexture,
+    msaa_sample_counts: Vec<u32>,
+
+    idle_generation: u64,
+    // how long the view has to stay still for the idle boost
+    idle_delay: Duration,
+    idle_timer: Option<JoinHandle<()>>,
 }
 
 #[derive(Debug)]
 pub enum UserEvent {
     Redraw,
     TileReady { tile_index: usize },
+    Idle { generation: u64 },
 }
 
 impl TiledFractalApp {
     pub fn new(
         window_state: &WindowContext,
+        config: &Config,
+        iter_dump: Option<IterDump>,
         event_loop_proxy: EventLoopProxy<UserEvent>,
-    ) -> TiledFractalApp {
+    ) -> anyhow::Result<TiledFractalApp> {
         let window_size = UVec2::new(
             window_state.surface_config.width,
             window_state.surface_config.height,
         );
 
-        let mandel_texture = MandelTexture::new(
+        let msaa_sample_counts = supported_msaa_sample_counts(window_state);
+        let msaa_samples = if msaa_sample_counts.contains(&config.msaa_samples) {
+            config.msaa_samples
+        } else {
+            println!(
+                "MSAA x{} is not supported, supported sample counts: {:?}",
+                config.msaa_samples, msaa_sample_counts
+            );
+            1
+        };
+
+        let mut mandel_texture = MandelTexture::new(
             &window_state.device,
             &window_state.queue,
             &window_state.surface_config,
             window_size,
-        );
+            msaa_samples,
+            config.texture_size,
+            config.max_in_flight,
+        )?;
+        mandel_texture.set_measure_tile_timings(config.tile_timings);
+        mandel_texture.set_boundary_color(config.boundary_color);
+        mandel_texture.set_crosshair_color(config.crosshair_color);
+        mandel_texture.set_interior_coloring(config.interior_coloring);
+        mandel_texture.set_interior_color(config.interior_color);
+        mandel_texture.set_color_offset(config.color_offset);
+        mandel_texture.set_coloring_mode(config.coloring_mode);
+        mandel_texture.set_band_aa(config.band_aa);
+        mandel_texture.set_palette_period(config.palette_period);
+        mandel_texture.set_gradient(config.gradient.clone());
+        mandel_texture.set_palette(config.palette)?;
+        // after the first palette, which should not fade in from the default one
+        mandel_texture.set_palette_fade(config.palette_fade);
+        mandel_texture.set_tile_time_budget(config.tile_time_budget);
+        mandel_texture.set_max_blit_zoom(config.max_blit_zoom);
+        mandel_texture.set_supersample(config.supersample);
+        mandel_texture.set_settle_supersample(config.settle_supersample);
+        mandel_texture.set_detail(config.detail);
+        mandel_texture.set_iter_storage(config.iter_storage);
+        mandel_texture.set_mip_blend(config.mip_blend);
+        mandel_texture.set_kernel(config.kernel);
+        mandel_texture.set_iter_cap(config.iter_cap);
+        mandel_texture.set_warmup_iters(config.warmup_iters);
+        mandel_texture.set_render_scale(config.render_scale);
+        mandel_texture.set_texture_count(config.texture_count);
+        mandel_texture.set_sub_tile_size(config.sub_tile_size);
+        mandel_texture.set_interior_boost(config.interior_boost);
+
+        // a dump's count is pinned so the idle boost doesn't replace its pixels
+        if let Some(iter_dump) = &iter_dump {
+            mandel_texture.set_iter_storage(iter_dump.iter_storage);
+            mandel_texture.set_warmup_iters(None);
+        }
+
+        let view_state = iter_dump.as_ref().map(IterDump::view_state);
+        let view_state = view_state.or(config.view).unwrap_or(ViewState {
+            fractal: Fractal {
+                kind: config.fractal_kind,
+                ..Fractal::default()
+            },
+            ..ViewState::default()
+        });
 
         let aspect = DVec2::new(window_size.x as f64 / window_size.y as f64, 1.0);
-        let frame_rect = DRect::from_center_size(DVec2::new(-0.74, 0.0), aspect * 2.5);
+
+        let scale_factor = window_state.window.scale_factor();
 
         let mut result = Self {
             window_size,
+            surface_size: window_size,
+            fit_aspect: None,
+            render_region: config.render_region,
+            scale_factor,
+            frame_scale_factor: scale_factor,
             event_loop_proxy: Arc::new(Mutex::new(event_loop_proxy)),
             runtime: Runtime::new().unwrap(),
 
+            minimized: false,
             manipulate_state: ManipulateState::Idle,
+            mouse_position: window_size / 2,
+            press_position: None,
+            orbit_mode: false,
+            orbit_point: None,
+            orbit_inputs: None,
+            measure_mode: false,
+            measure_points: Vec::new(),
+            hold_zoom: None,
+            glide: None,
+            playback: None,
+            recorder: None,
+            autofocus_zoom: config.autofocus_zoom,
+            autofocus_radius: config.autofocus_radius,
+            drag_focus: None,
+            touches: Touches::default(),
+            modifiers: ModifiersState::empty(),
+            invert_pan: config.invert_pan,
+            pan_sensitivity: config.pan_sensitivity,
+            zoom_speed: config.zoom_speed,
+            zoom_anchor: config.zoom_anchor,
+            zoom_pin: None,
+            fps_cap: config.fps_cap,
+            show_help: false,
+            show_compute_stats: false,
+            show_adapter_info: false,
+            coloring_switch: None,
+            adapter_info: window_state.adapter.get_info(),
+            clipboard: None,
+            frame_time: Duration::ZERO,
+            startup: Some(Instant::now()),
+            measure_startup: config.tile_timings,
 
-            frame_rect,
+            frame_rect: DRect::zeroed(),
             aspect,
+            fractal: view_state.fractal,
+            location_index: None,
 
             mandel_texture,
+            msaa_sample_counts,
+
+            idle_generation: 0,
+            idle_delay: config.idle_delay,
+            idle_timer: None,
         };
-        result.update_fractal(result.frame_rect.center());
-        return result;
+        result.apply_view_state(&view_state);
+        result.playback = config.replay.as_ref().map(Playback::new);
+        if config.fit {
+            result.fit_aspect = Some(window_size.x as f64 / window_size.y as f64);
+        }
+        if result.render_region < 1.0 {
+            result.resize(window_size);
+        }
+        if let Some(iter_dump) = iter_dump {
+            result.mandel_texture.set_iters_locked(true);
+            if let Err(err) = result.mandel_texture.show_iter_dump(iter_dump) {
+                println!("Computing the dumped view instead: {}", err);
+            }
+        }
+        Ok(result)
     }
 
     pub fn update(&mut self, event: Event<UserEvent>) -> EventResult {
-        match event {
+        match self.to_view(event) {
             Event::WindowClose => EventResult::Exit,
-            Event::Resized(window_size) => {
-                if self.window_size == window_size {
+            Event::Resized(surface_size) => {
+                // minimized, keep the last frame and stop computing until restored
+                if surface_size.min_element() == 0 {
+                    self.minimized = true;
+                    self.mandel_texture.cancel_tiles();
                     return EventResult::Continue;
                 }
+                if self.surface_size == surface_size && self.frame_scale_factor == self.scale_factor
+                {
+                    if !self.minimized {
+                        return EventResult::Continue;
+                    }
 
-                self.frame_rect = DRect::from_center_size(
-                    self.frame_rect.center(),
-                    self.frame_rect.size * DVec2::from(window_size) / DVec2::from(self.window_size),
-                );
-                self.window_size = window_size;
-                self.mandel_texture.resize_window(window_size);
+                    self.minimized = false;
+                    self.update_fractal(self.frame_rect.center());
+                    return EventResult::Redraw;
+                }
+                self.minimized = false;
 
-                self.update_fractal(self.frame_rect.center());
+                self.resize(surface_size);
 
                 EventResult::Redraw
             }
 
             Event::MouseWheel(position, delta) => {
-                self.move_scale(position, IVec2::zeroed(), 3.0 * delta);
+                self.move_scale(position, IVec2::zeroed(), delta * self.zoom_speed as f32);
 
                 EventResult::Redraw
             }
-            Event::MouseMove { position, delta } => match self.manipulate_state {
-                ManipulateState::Idle => EventResult::Continue,
-                ManipulateState::Drag => {
-                    self.move_scale(position, delta, 0.0);
+            Event::MouseMove { position, delta } => {
+                self.mouse_position = position;
+                if self.mandel_texture.is_probing() {
+                    let point = self.screen_to_fractal(position);
+                    self.mandel_texture.set_probe_point(Some(point));
+                }
 
-                    EventResult::Redraw
+                match self.manipulate_state {
+                    ManipulateState::Idle => EventResult::Continue,
+                    ManipulateState::Drag => {
+                        self.move_scale(position, delta, 0.0);
+
+                        EventResult::Redraw
+                    }
                 }
-            },
-            Event::MouseButton(btn, state, _position) => match (btn, state) {
+            }
+            Event::MouseButton(btn, state, position) => match (btn, state) {
                 (MouseButtons::Left, ElementState::Pressed) => {
                     self.manipulate_state = ManipulateState::Drag;
+                    self.press_position = Some(position);
+                    EventResult::Continue
+                }
+                (MouseButtons::Left, ElementState::Released) => {
+                    self.manipulate_state = ManipulateState::Idle;
+                    let clicked = self.press_position.take() == Some(position);
+                    if clicked && self.measure_mode {
+                        self.add_measure_point(self.screen_to_fractal(position));
+                        return EventResult::Redraw;
+                    }
+                    if !(clicked && self.orbit_mode) {
+                        return EventResult::Continue;
+                    }
+
+                    self.orbit_point = Some(self.screen_to_fractal(position));
+                    self.update_orbit();
+                    EventResult::Redraw
+                }
+                // zooms in toward the cursor until released, out with Shift held
+                (MouseButtons::Right, ElementState::Pressed) => {
+                    let direction = if self.modifiers.shift_key() {
+                        -1.0
+                    } else {
+                        1.0
+                    };
+                    self.hold_zoom = Some((direction, Instant::now()));
+                    EventResult::Redraw
+                }
+                (MouseButtons::Right, ElementState::Released) => {
+                    self.hold_zoom = None;
                     EventResult::Continue
                 }
                 _ => {
@@ -117,32 +438,47 @@ impl TiledFractalApp {
                     EventResult::Continue
                 }
             },
+            Event::Touch {
+                id,
+                phase,
+                position,
+            } => match self.touches.update(id, phase, position) {
+                Some(gesture) => {
+                    self.touch_pan_zoom(gesture);
+                    EventResult::Redraw
+                }
+                None => EventResult::Continue,
+            },
             Event::KeyboardInput(key) => {
-                if !is_debug_build() {
+                if key.state != winit::event::ElementState::Released {
                     return EventResult::Continue;
                 }
-
-                if key.state != winit::event::ElementState::Released {
+                // any key dismisses the help and does nothing else
+                if self.show_help {
+                    self.show_help = false;
                     return EventResult::Continue;
                 }
 
-                match key.physical_key {
-                    winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::KeyA) => {
-                        let count = self.mandel_texture.buf_pool.taken_buffer_count();
-                        println!("Taken buffer count: {}", count);
-                        EventResult::Continue
-                    }
-                    winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::KeyS) => {
-                        EventResult::Redraw
-                    }
-                    winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::KeyD) => {
-                        self.update_fractal(self.frame_rect.center());
-                        EventResult::Redraw
-                    }
-                    _ => EventResult::Continue,
+                let PhysicalKey::Code(key_code) = key.physical_key else {
+                    return EventResult::Continue;
+                };
+                match action_for(key_code, self.modifiers.shift_key()) {
+                    Some(action) => self.perform(action),
+                    None if is_debug_build() => self.update_debug_key(key.physical_key),
+                    None => EventResult::Continue,
                 }
             }
 
+            // the matching Resized follows, it rescales the frame
+            Event::ScaleFactorChanged(scale_factor) => {
+                self.scale_factor = scale_factor;
+                EventResult::Continue
+            }
+            Event::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+                EventResult::Continue
+            }
+
             Event::Custom(event) => self.update_user_event(event),
 
             _ => EventResult::Continue,
@@ -150,7 +486,477 @@ impl TiledFractalApp {
     }
 
     pub fn render(&mut self, render_info: &RenderContext) {
-        self.mandel_texture.render(render_info);
+        self.render_to(
+            render_info,
+            URect::from_pos_size(UVec2::ZERO, self.surface_size),
+        );
+    }
+
+    /// Draws into `viewport` of `render_info.view`, for embedding in a larger frame.
+    /// The view follows the viewport's size, events are expected in its pixels.
+    pub fn render_to(&mut self, render_info: &RenderContext, viewport: URect) {
+        if viewport.size != self.surface_size && viewport.size.min_element() > 0 {
+            self.resize(viewport.size);
+        }
+        self.step_hold_zoom();
+        self.step_glide();
+        self.step_playback();
+
+        let start = Instant::now();
+        let view_viewport = URect::from_pos_size(
+            viewport.pos + (self.surface_size - self.window_size) / 2,
+            self.window_size,
+        );
+        self.mandel_texture.render_to(render_info, view_viewport);
+        self.frame_time = start.elapsed();
+
+        self.finish_startup_pass();
+    }
+
+    /// Returns true while a palette switch is fading, a held zoom, a glide or a replay is
+    /// running, and the next frame should follow right away.
+    pub fn is_animating(&self) -> bool {
+        self.hold_zoom.is_some()
+            || self.glide.is_some()
+            || self.playback.is_some()
+            || self.mandel_texture.is_fading()
+    }
+
+    /// When the coloring label leaves the title, the title needs updating then but no frame.
+    pub fn coloring_label_expiry(&self) -> Option<Instant> {
+        self.coloring_switch
+            .map(|switch| switch + COLORING_LABEL)
+            .filter(|&expiry| expiry > Instant::now())
+    }
+
+    fn is_coloring_label_shown(&self) -> bool {
+        self.coloring_label_expiry().is_some()
+    }
+
+    /// Returns true while a screenshot or texture dump is still in flight and needs further polling.
+    pub fn poll_readbacks(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+        self.mandel_texture.poll_readbacks(device, queue);
+        self.mandel_texture.is_readback_pending()
+    }
+
+    pub fn title(&self) -> String {
+        let iterations = match self.mandel_texture.iter_override() {
+            _ if self.mandel_texture.iters_locked() => {
+                format!("{} (locked)", self.mandel_texture.max_iters())
+            }
+            Some(iters) => format!("{} (manual)", iters),
+            None => format!("{}", self.mandel_texture.max_iters()),
+        };
+
+        let boost = if self.mandel_texture.quality_boost() {
+            " | boosted"
+        } else {
+            ""
+        };
+        let detail = match self.mandel_texture.detail() {
+            0 => String::new(),
+            detail => format!(" | detail: {}", detail),
+        };
+        let glitches = if self.mandel_texture.detect_glitches() {
+            " | glitch detection"
+        } else {
+            ""
+        };
+        let kernel = match self.mandel_texture.kernel_params().kernel {
+            Kernel::Simd => String::new(),
+            kernel => format!(" | {} kernel", kernel.name()),
+        };
+        let boundary = if self.mandel_texture.show_boundary() {
+            " | boundary"
+        } else {
+            ""
+        };
+        let measure = match self.measure_points.as_slice() {
+            _ if !self.measure_mode => String::new(),
+            &[start, end] => {
+                let (distance, angle) = measure(start, end);
+                format!(" | distance: {:.4e}, angle: {:.2}°", distance, angle)
+            }
+            [_] => " | measure: click the second point".to_string(),
+            _ => " | measure: click two points".to_string(),
+        };
+        let render_scale = match self.mandel_texture.render_scale() {
+            1.0 => String::new(),
+            render_scale => format!(" | render scale: {}x", render_scale),
+        };
+        let fps_cap = match self.fps_cap {
+            Some(fps) => format!(" | fps cap: {}", fps),
+            None => String::new(),
+        };
+        let fit = if self.fit_aspect.is_some() {
+            " | fit"
+        } else {
+            ""
+        };
+        let region = match self.render_region {
+            1.0 => String::new(),
+            region => format!(" | region: {:.0}%", region * 100.0),
+        };
+        let zoom_anchor = match self.zoom_anchor {
+            ZoomAnchor::Cursor => String::new(),
+            zoom_anchor => format!(" | zoom anchor: {}", zoom_anchor.name()),
+        };
+        let interior = match self.mandel_texture.interior_coloring() {
+            InteriorColoring::Solid => String::new(),
+            interior_coloring => format!(" | interior: {}", interior_coloring.name()),
+        };
+        let band_aa = self.mandel_texture.band_aa();
+        let coloring = match self.mandel_texture.coloring_mode() {
+            ColoringMode::Bands if !band_aa && !self.is_coloring_label_shown() => String::new(),
+            coloring_mode => format!(
+                " | coloring: {}{}",
+                coloring_mode.name(),
+                if band_aa { ", soft edges" } else { "" }
+            ),
+        };
+        let palette = match self.mandel_texture.palette() {
+            PaletteKind::Classic => String::new(),
+            palette => format!(" | palette: {}", palette.name()),
+        };
+        let probe = if self.mandel_texture.is_probing() {
+            match self.mandel_texture.probed_count() {
+                None => " | cursor: -".to_string(),
+                Some(0) => " | cursor: interior".to_string(),
+                // `Pixel::GLITCH`
+                Some(u16::MAX) => " | cursor: glitch".to_string(),
+                Some(count) => format!(
+                    " | cursor: {} iterations",
+                    self.mandel_texture.iter_storage().decode(count)
+                ),
+            }
+        } else {
+            String::new()
+        };
+        let orbit = match self.mandel_texture.orbit() {
+            _ if !self.orbit_mode => String::new(),
+            None => " | orbit: click a point".to_string(),
+            Some(orbit) => {
+                let escaped = orbit
+                    .last()
+                    .is_some_and(|z| z.length_squared() >= ESCAPE_RADIUS_SQUARED);
+                format!(
+                    " | orbit: {} iterations, {}",
+                    orbit.len() - 1,
+                    if escaped { "escaped" } else { "bounded" }
+                )
+            }
+        };
+        let still = match self.mandel_texture.still_ready() {
+            None => "",
+            Some(false) => " | rendering still",
+            Some(true) => " | still",
+        };
+        let failed = match self.mandel_texture.failed_tile_count() {
+            0 => String::new(),
+            count => format!(" | {} tiles failed, move the view to retry", count),
+        };
+        // a single color looks like a broken render
+        let uniform = if self.mandel_texture.uniform_view() {
+            " | uniform region, try panning or increasing iterations"
+        } else {
+            ""
+        };
+        let compute_stats = if self.show_compute_stats {
+            let stats = self.mandel_texture.compute_stats();
+            format!(
+                " | tiles: {} computing, {} queued | buffers taken: {} | max iterations: {} | frame: {:.1} ms",
+                stats.computing,
+                stats.queued,
+                stats.buffers_taken,
+                self.mandel_texture.max_iters(),
+                self.frame_time.as_secs_f64() * 1000.0
+            )
+        } else {
+            String::new()
+        };
+        let adapter = if self.show_adapter_info {
+            format!(" | {}", adapter_description(&self.adapter_info))
+        } else {
+            String::new()
+        };
+        let navigation = if self.recorder.is_some() {
+            " | recording navigation"
+        } else if self.playback.is_some() {
+            " | replay"
+        } else {
+            ""
+        };
+        let help = if self.show_help {
+            " | keys printed to the console, press any key to close"
+        } else {
+            ""
+        };
+        let julia_c = match self.fractal.kind {
+            FractalKind::Julia => {
+                let c = self.fractal.julia_c;
+                let sign = if c.y < 0.0 { '-' } else { '+' };
+                format!(" | c: {:.4} {} {:.4}i", c.x, sign, c.y.abs())
+            }
+            _ => String::new(),
+        };
+        let location = match self.location_index {
+            Some(index) => format!(" | {}", LOCATIONS[index].name),
+            None => String::new(),
+        };
+        // there is no text rendering, the legend's tick values live in the title
+        let legend = if self.mandel_texture.show_legend() {
+            let palette = self.mandel_texture.palette();
+            let palette_period = self.mandel_texture.palette_period();
+            format!(
+                " | legend: 1..{} iterations, tick every {:.0}, {} palette ({})",
+                palette_period,
+                palette_period as f64 / LEGEND_TICKS as f64,
+                palette.name(),
+                palette.vision_note()
+            )
+        } else {
+            String::new()
+        };
+
+        format!(
+            "Mandelbrot explorer | {}{}{} | iterations: {}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}",
+            self.fractal.kind.name(),
+            julia_c,
+            location,
+            navigation,
+            iterations,
+            boost,
+            detail,
+            still,
+            failed,
+            uniform,
+            glitches,
+            kernel,
+            boundary,
+            interior,
+            coloring,
+            palette,
+            probe,
+            orbit,
+            measure,
+            render_scale,
+            fps_cap,
+            fit,
+            region,
+            zoom_anchor,
+            legend,
+            compute_stats,
+            adapter,
+            help
+        )
+    }
+
+    /// Follows the surface onto a new format, after a move to a monitor without the old one.
+    /// MSAA drops to off when the new format doesn't support the current sample count.
+    pub fn set_surface_format(&mut self, window_state: &WindowContext) {
+        self.msaa_sample_counts = supported_msaa_sample_counts(window_state);
+        if !self
+            .msaa_sample_counts
+            .contains(&self.mandel_texture.msaa_samples())
+        {
+            println!("MSAA off, the new surface format doesn't support it");
+            self.mandel_texture.set_msaa_samples(1);
+        }
+        self.mandel_texture
+            .set_surface_format(window_state.surface_config.view_formats[0]);
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    pub fn view_state(&self) -> ViewState {
+        ViewState {
+            center: self.frame_rect.center(),
+            height: self.frame_rect.size.y,
+            fractal: self.fractal,
+            iter_override: self.mandel_texture.iter_override(),
+            coloring: Some(Coloring {
+                palette: self.mandel_texture.palette(),
+                interior_coloring: self.mandel_texture.interior_coloring(),
+                interior_color: self.mandel_texture.interior_color(),
+                coloring_mode: self.mandel_texture.coloring_mode(),
+                color_offset: self.mandel_texture.color_offset(),
+                palette_period: self.mandel_texture.palette_period(),
+            }),
+        }
+    }
+
+    pub fn apply_view_state(&mut self, view_state: &ViewState) {
+        let aspect = self.window_size.x as f64 / self.window_size.y as f64;
+        self.frame_rect = DRect::from_center_size(
+            view_state.center,
+            DVec2::new(aspect, 1.0) * view_state.height,
+        );
+        self.glide = None;
+        self.playback = None;
+
+        self.fractal = view_state.fractal;
+        self.mandel_texture.set_fractal(view_state.fractal);
+        self.mandel_texture
+            .set_iter_override(view_state.iter_override);
+        self.location_index = None;
+
+        self.update_fractal(self.frame_rect.center());
+    }
+
+    /// Moves `step` entries through the famous locations, wrapping around at either end.
+    fn jump_to_location(&mut self, step: isize) {
+        let index = match self.location_index {
+            Some(index) => (index as isize + step).rem_euclid(LOCATIONS.len() as isize) as usize,
+            None if step < 0 => LOCATIONS.len() - 1,
+            None => 0,
+        };
+
+        let location = &LOCATIONS[index];
+        println!("Location: {}", location.name);
+        self.apply_view_state(&location.view);
+        self.location_index = Some(index);
+    }
+
+    /// Puts a human-readable location on the clipboard, for pasting into issues and posts.
+    fn copy_location(&mut self) {
+        let view_state = self.view_state();
+        let location = format!(
+            "center = ({}, {}), zoom = {}, iters = {}",
+            view_state.center.x,
+            view_state.center.y,
+            view_state.zoom(),
+            self.mandel_texture.max_iters()
+        );
+        println!("Location: {}", location);
+
+        let copied = match &mut self.clipboard {
+            Some(clipboard) => clipboard.set_text(location),
+            None => arboard::Clipboard::new()
+                .and_then(|clipboard| self.clipboard.insert(clipboard).set_text(location)),
+        };
+        if let Err(err) = copied {
+            println!("Failed to copy location to clipboard: {}", err);
+        }
+    }
+
+    fn set_fractal(&mut self, fractal: Fractal) {
+        println!("Fractal: {:?}", fractal);
+
+        self.fractal = fractal;
+        self.mandel_texture.set_fractal(fractal);
+        self.location_index = None;
+        self.recompute_fractal();
+    }
+
+    // only the Julia set has a constant to move
+    fn nudge_julia(&mut self, offset: DVec2) -> EventResult {
+        if self.fractal.kind != FractalKind::Julia {
+            return EventResult::Continue;
+        }
+
+        self.set_fractal(Fractal {
+            julia_c: self.fractal.julia_c + offset,
+            ..self.fractal
+        });
+        EventResult::Redraw
+    }
+
+    fn step_iterations(&mut self, factor: f64) {
+        self.mandel_texture.set_quality_boost(false);
+        let iters = (self.mandel_texture.max_iters() as f64 * factor).round() as u32;
+
+        self.mandel_texture.set_iter_override(Some(iters));
+        // a lock moves along with the manual steps, re-locking takes the new count
+        if self.mandel_texture.iters_locked() {
+            self.mandel_texture.set_iters_locked(true);
+        }
+        self.recompute_fractal();
+    }
+
+    /// Sizes the view for the window, filling it or letterboxed at `fit_aspect`,
+    /// then shrunk to the render region.
+    fn resize(&mut self, surface_size: UVec2) {
+        let window_size = match self.fit_aspect {
+            Some(aspect) => fit_size(surface_size, aspect),
+            None => surface_size,
+        };
+        let window_size = region_size(window_size, self.render_region);
+        self.frame_rect = match self.fit_aspect {
+            // the same region, only its pixels grow or shrink
+            Some(_) => DRect::from_center_size(
+                self.frame_rect.center(),
+                DVec2::new(window_size.x as f64 / window_size.y as f64, 1.0)
+                    * self.frame_rect.size.y,
+            ),
+            None => resize_frame(
+                self.frame_rect,
+                (self.window_size, self.frame_scale_factor),
+                (window_size, self.scale_factor),
+            ),
+        };
+        self.window_size = window_size;
+        self.surface_size = surface_size;
+        self.frame_scale_factor = self.scale_factor;
+        self.mandel_texture.resize_window(window_size);
+
+        self.update_fractal(self.frame_rect.center());
+    }
+
+    /// Moves window positions onto the view, clamped to its edge when over the letterbox bars.
+    fn to_view(&self, event: Event<UserEvent>) -> Event<UserEvent> {
+        let offset = (self.surface_size - self.window_size) / 2;
+        let to_view = |position: UVec2| {
+            position
+                .saturating_sub(offset)
+                .min(self.window_size - UVec2::ONE)
+        };
+
+        match event {
+            Event::MouseWheel(position, delta) => Event::MouseWheel(to_view(position), delta),
+            Event::MouseMove { position, delta } => Event::MouseMove {
+                position: to_view(position),
+                delta,
+            },
+            Event::MouseButton(button, state, position) => {
+                Event::MouseButton(button, state, to_view(position))
+            }
+            Event::Touch {
+                id,
+                phase,
+                position,
+            } => Event::Touch {
+                id,
+                phase,
+                position: position - offset.as_dvec2(),
+            },
+            event => event,
+        }
+    }
+
+    fn render_still(&mut self) {
+        // a still is the final image, give it the iterations the idle boost would
+        if !self.mandel_texture.quality_boost() {
+            self.mandel_texture.set_quality_boost(true);
+            self.schedule_tiles(self.frame_rect.center());
+        }
+
+        let event_loop_proxy = self.event_loop_proxy.clone();
+        self.mandel_texture.request_still(move || {
+            // the event loop may already be gone when exiting
+            let _ = event_loop_proxy.lock().send_event(UserEvent::Redraw);
+        });
+    }
+
+    fn screen_to_fractal(&self, position: UVec2) -> DVec2 {
+        let position = DVec2::new(
+            position.x as f64,
+            self.window_size.y as f64 - position.y as f64,
+        );
+        let position = position / DVec2::from(self.window_size) - 0.5;
+
+        self.frame_rect.center() + self.frame_rect.size * position
     }
 
     fn move_scale(&mut self, mouse_pos: UVec2, mouse_delta: IVec2, scroll_delta: f32) {
@@ -163,32 +969,662 @@ impl TiledFractalApp {
 
         let mouse_delta = DVec2::from(mouse_delta) / DVec2::from(self.window_size);
         let mouse_delta = DVec2::new(mouse_delta.x, -mouse_delta.y);
+        let mouse_delta =
+            mouse_delta * self.pan_sensitivity * if self.invert_pan { -1.0 } else { 1.0 };
 
-        let zoom = 1.15f64.powf(scroll_delta as f64 / 5.0f64);
+        let zoom = ZOOM_PER_WHEEL_STEP.powf(scroll_delta as f64);
 
-        let old_size = self.frame_rect.size;
-        let new_size = old_size * zoom;
+        // only a zoom keeps its anchor in place, a pan moves every point alike
+        let anchor = match self.zoom_anchor {
+            ZoomAnchor::Cursor => mouse_pos,
+            ZoomAnchor::Center => DVec2::ZERO,
+            ZoomAnchor::Pinned => match self.zoom_pin {
+                Some(pin) => (pin - self.frame_rect.center()) / self.frame_rect.size,
+                None => DVec2::ZERO,
+            },
+        };
+        self.frame_rect = pan_zoom(self.frame_rect, anchor, mouse_delta, zoom);
+        self.location_index = None;
+        self.glide = None;
+        self.playback = None;
 
-        let old_offset = self.frame_rect.center();
-        let new_offset = old_offset - mouse_delta * new_size - mouse_pos * (new_size - old_size);
+        let focus = self.drag_focus(if scroll_delta == 0.0 {
+            mouse_pos
+        } else {
+            anchor
+        });
+        self.update_fractal(focus);
+    }
 
-        self.frame_rect = DRect::from_center_size(new_offset, new_size);
+    // the plane sticks to the fingers, so unlike the mouse there's no sensitivity or inverted pan
+    fn touch_pan_zoom(&mut self, gesture: TouchGesture) {
+        let window_size = self.window_size.as_dvec2();
+        let position = DVec2::new(gesture.position.x, window_size.y - gesture.position.y);
+        let position = position / window_size - 0.5;
+        let delta = DVec2::new(gesture.delta.x, -gesture.delta.y) / window_size;
 
-        let focus = self.frame_rect.center() + self.frame_rect.size * mouse_pos;
+        self.frame_rect = pan_zoom(self.frame_rect, position, delta, 1.0 / gesture.spread);
+        self.location_index = None;
+        self.glide = None;
+        self.playback = None;
 
+        let focus = self.drag_focus(position);
         self.update_fractal(focus);
     }
 
+    // the tiles are ordered around the focus, following the cursor through a filter keeps
+    // the order from reshuffling the queued tiles on every move of a drag,
+    // the jumps that pass the frame center skip it and fill center-out right away
+    fn drag_focus(&mut self, position: DVec2) -> DVec2 {
+        let now = Instant::now();
+        let position = match self.drag_focus {
+            Some((previous, time)) => smooth_focus(previous, position, now - time),
+            None => position,
+        };
+        self.drag_focus = Some((position, now));
+
+        self.frame_rect.center() + self.frame_rect.size * position
+    }
+
+    // zooms by the time since the last frame, so the rate doesn't follow the frame rate
+    fn step_hold_zoom(&mut self) {
+        let Some((direction, last_step)) = self.hold_zoom else {
+            return;
+        };
+        let now = Instant::now();
+        let steps = direction * HOLD_ZOOM_STEPS_PER_SECOND * (now - last_step).as_secs_f32();
+        self.hold_zoom = Some((direction, now));
+
+        self.move_scale(
+            self.mouse_position,
+            IVec2::zeroed(),
+            steps * self.zoom_speed as f32,
+        );
+    }
+
+    // the tiles are ordered around the target, so its part of each step is filled first
+    fn step_glide(&mut self) {
+        let Some((start, target, start_time)) = self.glide else {
+            return;
+        };
+        let progress = start_time.elapsed().as_secs_f64() / AUTOFOCUS_GLIDE.as_secs_f64();
+        if progress >= 1.0 {
+            self.glide = None;
+        }
+
+        self.frame_rect = glide_rect(start, target, progress.min(1.0));
+        self.update_fractal(target.center());
+    }
+
+    // follows the replay, which stops at its last view
+    fn step_playback(&mut self) {
+        let Some(mut playback) = self.playback.take() else {
+            return;
+        };
+        let finished = playback.is_finished();
+
+        self.apply_view_state(&playback.step());
+        if finished {
+            println!("Replay finished");
+        } else {
+            self.playback = Some(playback);
+        }
+    }
+
+    // starts a glide towards the busiest region in view, zoomed in by `autofocus_zoom`
+    fn autofocus(&mut self) {
+        let Some(peak) = self.mandel_texture.detail_peak(self.autofocus_radius) else {
+            println!("Autofocus: no detail in view yet");
+            return;
+        };
+        println!("Autofocus: gliding to ({}, {})", peak.x, peak.y);
+
+        let target = DRect::from_center_size(peak, self.frame_rect.size / self.autofocus_zoom);
+        self.glide = Some((self.frame_rect, target, Instant::now()));
+        self.playback = None;
+        self.location_index = None;
+    }
+
+    // lifts the warmup limit once the preview is on screen, then waits for the full view
+    fn finish_startup_pass(&mut self) {
+        let Some(startup) = self.startup else {
+            return;
+        };
+        if !self.mandel_texture.is_view_complete() {
+            return;
+        }
+
+        let pass = if self.mandel_texture.is_warming_up() {
+            self.mandel_texture.set_warmup_iters(None);
+            self.schedule_tiles(self.frame_rect.center());
+            "Preview"
+        } else {
+            self.startup = None;
+            "Full view"
+        };
+        if self.measure_startup {
+            println!("{} complete {:.0?} after startup", pass, startup.elapsed());
+        }
+    }
+
+    fn perform(&mut self, action: Action) -> EventResult {
+        match action {
+            Action::ToggleHelp => {
+                println!("Keys:\n{}", help_text());
+                self.show_help = true;
+                EventResult::Continue
+            }
+            Action::CycleMsaa => {
+                self.cycle_msaa();
+                EventResult::Redraw
+            }
+            Action::NextFractalKind => {
+                self.set_fractal(Fractal {
+                    kind: self.fractal.kind.next(),
+                    ..self.fractal
+                });
+                EventResult::Redraw
+            }
+            Action::JuliaAtCursor => {
+                self.set_fractal(Fractal {
+                    kind: FractalKind::Julia,
+                    julia_c: self.screen_to_fractal(self.mouse_position),
+                    ..self.fractal
+                });
+                EventResult::Redraw
+            }
+            Action::JuliaRealUp => self.nudge_julia(DVec2::new(JULIA_NUDGE, 0.0)),
+            Action::JuliaRealDown => self.nudge_julia(DVec2::new(-JULIA_NUDGE, 0.0)),
+            Action::JuliaImagUp => self.nudge_julia(DVec2::new(0.0, JULIA_NUDGE)),
+            Action::JuliaImagDown => self.nudge_julia(DVec2::new(0.0, -JULIA_NUDGE)),
+            Action::MoreIterations => {
+                self.step_iterations(ITER_STEP);
+                EventResult::Redraw
+            }
+            Action::FewerIterations => {
+                self.step_iterations(1.0 / ITER_STEP);
+                EventResult::Redraw
+            }
+            Action::AutoIterations => {
+                self.mandel_texture.set_iter_override(None);
+                self.mandel_texture.set_iters_locked(false);
+                self.recompute_fractal();
+                EventResult::Redraw
+            }
+            Action::ToggleIterationLock => {
+                // locking keeps the count, unlocking after a zoom catches up with it
+                let max_iters = self.mandel_texture.max_iters();
+                let locked = !self.mandel_texture.iters_locked();
+                self.mandel_texture.set_iters_locked(locked);
+                if self.mandel_texture.max_iters() != max_iters {
+                    self.recompute_fractal();
+                }
+                EventResult::Redraw
+            }
+            Action::MoreDetail => {
+                let detail = self.mandel_texture.detail() + 1;
+                self.mandel_texture.set_detail(detail);
+                self.recompute_fractal();
+                EventResult::Redraw
+            }
+            Action::LessDetail => {
+                let detail = self.mandel_texture.detail().saturating_sub(1);
+                self.mandel_texture.set_detail(detail);
+                self.recompute_fractal();
+                EventResult::Redraw
+            }
+            Action::RenderScaleUp => {
+                self.step_render_scale(true);
+                EventResult::Redraw
+            }
+            Action::RenderScaleDown => {
+                self.step_render_scale(false);
+                EventResult::Redraw
+            }
+            Action::RenderRegionUp => {
+                self.step_render_region(true);
+                EventResult::Redraw
+            }
+            Action::RenderRegionDown => {
+                self.step_render_region(false);
+                EventResult::Redraw
+            }
+            Action::ToggleGlitchDetection => {
+                let detect_glitches = !self.mandel_texture.detect_glitches();
+                self.mandel_texture.set_detect_glitches(detect_glitches);
+                self.recompute_fractal();
+                EventResult::Redraw
+            }
+            Action::Autofocus => {
+                self.autofocus();
+                EventResult::Redraw
+            }
+            Action::ToggleLegend => {
+                let show_legend = !self.mandel_texture.show_legend();
+                self.mandel_texture.set_show_legend(show_legend);
+                EventResult::Redraw
+            }
+            Action::ToggleBoundary => {
+                let show_boundary = !self.mandel_texture.show_boundary();
+                self.mandel_texture.set_show_boundary(show_boundary);
+                EventResult::Redraw
+            }
+            Action::ToggleCrosshair => {
+                let show_crosshair = !self.mandel_texture.show_crosshair();
+                self.mandel_texture.set_show_crosshair(show_crosshair);
+                EventResult::Redraw
+            }
+            Action::CycleZoomAnchor => {
+                self.zoom_anchor = self.zoom_anchor.next();
+                match (self.zoom_anchor, self.zoom_pin) {
+                    (ZoomAnchor::Pinned, None) => println!(
+                        "Zoom anchor: pinned, the view center until Shift+C pins the point under the cursor"
+                    ),
+                    (zoom_anchor, _) => println!("Zoom anchor: {}", zoom_anchor.name()),
+                }
+                EventResult::Redraw
+            }
+            Action::PinZoomAnchor => {
+                let pin = self.screen_to_fractal(self.mouse_position);
+                println!("Zoom anchor pinned at ({}, {})", pin.x, pin.y);
+                self.zoom_pin = Some(pin);
+                self.zoom_anchor = ZoomAnchor::Pinned;
+                EventResult::Redraw
+            }
+            Action::CycleInteriorColoring => {
+                let interior_coloring = self.mandel_texture.interior_coloring().next();
+                self.mandel_texture.set_interior_coloring(interior_coloring);
+                EventResult::Redraw
+            }
+            Action::CycleColoringMode => {
+                let coloring_mode = self.mandel_texture.coloring_mode().next();
+                self.mandel_texture.set_coloring_mode(coloring_mode);
+                self.coloring_switch = Some(Instant::now());
+                EventResult::Redraw
+            }
+            Action::ToggleBandAa => {
+                let band_aa = !self.mandel_texture.band_aa();
+                self.mandel_texture.set_band_aa(band_aa);
+                self.coloring_switch = Some(Instant::now());
+                EventResult::Redraw
+            }
+            Action::LongerPalettePeriod => {
+                self.step_palette_period(PALETTE_PERIOD_STEP);
+                EventResult::Redraw
+            }
+            Action::ShorterPalettePeriod => {
+                self.step_palette_period(1.0 / PALETTE_PERIOD_STEP);
+                EventResult::Redraw
+            }
+            Action::CyclePalette => {
+                let mut palette = self.mandel_texture.palette().next();
+                if palette == PaletteKind::Gradient && !self.mandel_texture.has_gradient() {
+                    palette = palette.next();
+                }
+                if let Err(err) = self.mandel_texture.set_palette(palette) {
+                    println!("Failed to load the {} palette: {}", palette.name(), err);
+                }
+                EventResult::Redraw
+            }
+            Action::ToggleProbe => {
+                let point = (!self.mandel_texture.is_probing())
+                    .then(|| self.screen_to_fractal(self.mouse_position));
+                self.mandel_texture.set_probe_point(point);
+                EventResult::Continue
+            }
+            Action::ToggleOrbit => {
+                self.orbit_mode = !self.orbit_mode;
+                if !self.orbit_mode {
+                    self.orbit_point = None;
+                    self.orbit_inputs = None;
+                    self.mandel_texture.clear_orbit();
+                }
+                EventResult::Redraw
+            }
+            Action::ToggleMeasure => {
+                self.measure_mode = !self.measure_mode;
+                self.measure_points.clear();
+                self.mandel_texture.set_measure(None);
+                EventResult::Redraw
+            }
+            Action::CycleFpsCap => {
+                self.cycle_fps_cap();
+                EventResult::Continue
+            }
+            Action::ToggleFit => {
+                // fits at the current aspect, the bars show up once the window changes shape
+                self.fit_aspect = match self.fit_aspect {
+                    Some(_) => None,
+                    None => Some(self.window_size.x as f64 / self.window_size.y as f64),
+                };
+                self.resize(self.surface_size);
+                EventResult::Redraw
+            }
+            Action::Screenshot => {
+                self.mandel_texture
+                    .request_screenshot(timestamped_path("screenshot", "png"));
+                EventResult::Redraw
+            }
+            Action::ExportPalette => {
+                self.mandel_texture
+                    .request_palette_export(timestamped_path("palette", "png"));
+                EventResult::Redraw
+            }
+            Action::PrintLink => {
+                println!("Link: {}", self.view_state().to_link());
+                EventResult::Continue
+            }
+            Action::SaveViewState => {
+                let path = timestamped_path("view", "json");
+                match self.view_state().save(&path) {
+                    Ok(()) => println!("Saved {}", path.display()),
+                    Err(err) => println!("Failed to save {}: {}", path.display(), err),
+                }
+                EventResult::Continue
+            }
+            Action::ToggleNavigationRecording => {
+                match self.recorder.take() {
+                    Some(recorder) => {
+                        let path = timestamped_path("navigation", "json");
+                        match recorder.finish(self.view_state()).save(&path) {
+                            Ok(()) => {
+                                println!("Saved {}, play it back with --replay", path.display())
+                            }
+                            Err(err) => println!("Failed to save {}: {}", path.display(), err),
+                        }
+                    }
+                    None => {
+                        println!("Recording navigation, press Shift+T again to save it");
+                        self.recorder = Some(Recorder::new(self.view_state()));
+                    }
+                }
+                EventResult::Redraw
+            }
+            Action::CopyLocation => {
+                self.copy_location();
+                EventResult::Continue
+            }
+            Action::ToggleComputeStats => {
+                self.show_compute_stats = !self.show_compute_stats;
+                EventResult::Continue
+            }
+            Action::ToggleAdapterInfo => {
+                self.show_adapter_info = !self.show_adapter_info;
+                // the title can't be copied from
+                if self.show_adapter_info {
+                    println!("Adapter: {}", adapter_description(&self.adapter_info));
+                }
+                EventResult::Continue
+            }
+            Action::PrintTileTimings => {
+                match self.mandel_texture.take_tile_timings() {
+                    Some(summary) => println!("Tile timings: {}", summary),
+                    None => println!("No tile timings recorded (enable with --tile-timings)"),
+                }
+                EventResult::Continue
+            }
+            Action::RenderStill => {
+                self.render_still();
+                EventResult::Continue
+            }
+            Action::DumpIterations => {
+                self.render_still();
+                self.mandel_texture
+                    .request_iter_dump(timestamped_path("iterations", "bin"));
+                EventResult::Continue
+            }
+            Action::Recompute => {
+                println!("Recomputing every tile in view");
+                self.recompute_fractal();
+                EventResult::Redraw
+            }
+            Action::ResetView => {
+                let (center, height) = self.fractal.kind.default_view();
+                self.apply_view_state(&ViewState {
+                    center,
+                    height,
+                    ..self.view_state()
+                });
+                EventResult::Redraw
+            }
+            Action::NextLocation => {
+                self.jump_to_location(1);
+                EventResult::Redraw
+            }
+            Action::PreviousLocation => {
+                self.jump_to_location(-1);
+                EventResult::Redraw
+            }
+        }
+    }
+
+    fn update_debug_key(&mut self, key: PhysicalKey) -> EventResult {
+        match key {
+            PhysicalKey::Code(KeyCode::KeyA) => {
+                let count = self.mandel_texture.buf_pool.taken_buffer_count();
+                println!("Taken buffer count: {}", count);
+                EventResult::Continue
+            }
+            PhysicalKey::Code(KeyCode::KeyS) => EventResult::Redraw,
+            PhysicalKey::Code(KeyCode::KeyX) => {
+                self.mandel_texture
+                    .request_texture_dump(timestamped_path("texture", "png"));
+                EventResult::Redraw
+            }
+            PhysicalKey::Code(KeyCode::KeyD) => {
+                self.update_fractal(self.frame_rect.center());
+                EventResult::Redraw
+            }
+            // shows the buffer the last blit carried over, to check the swap
+            PhysicalKey::Code(KeyCode::KeyW) => {
+                let show = !self.mandel_texture.show_previous_texture();
+                let texture = if show { "previous" } else { "current" };
+                println!("Showing the {} texture", texture);
+                self.mandel_texture.set_show_previous_texture(show);
+                EventResult::Redraw
+            }
+            // recomputes the view with the other kernel to compare them
+            PhysicalKey::Code(KeyCode::KeyQ) => {
+                let kernel = match self.mandel_texture.kernel() {
+                    Kernel::Simd => Kernel::Scalar,
+                    Kernel::Scalar | Kernel::DoubleDouble => Kernel::Simd,
+                };
+                println!("Kernel: {}", kernel.name());
+                self.mandel_texture.set_kernel(kernel);
+                self.update_fractal(self.frame_rect.center());
+                EventResult::Redraw
+            }
+            _ => EventResult::Continue,
+        }
+    }
+
+    fn cycle_msaa(&mut self) {
+        let current = self.mandel_texture.msaa_samples();
+        let next = self
+            .msaa_sample_counts
+            .iter()
+            .copied()
+            .find(|&count| count > current)
+            .unwrap_or(1);
+
+        println!("MSAA: x{}", next);
+        self.mandel_texture.set_msaa_samples(next);
+    }
+
+    // the colors move, the tiles stay as they are
+    fn step_palette_period(&mut self, factor: f64) {
+        let period = (self.mandel_texture.palette_period() as f64 * factor).round() as u32;
+        self.mandel_texture.set_palette_period(period);
+        println!(
+            "Palette period: {} iterations",
+            self.mandel_texture.palette_period()
+        );
+    }
+
+    fn step_render_scale(&mut self, up: bool) {
+        let current = self.mandel_texture.render_scale();
+        let next = if up {
+            RENDER_SCALES.iter().copied().find(|&scale| scale > current)
+        } else {
+            RENDER_SCALES
+                .iter()
+                .rev()
+                .copied()
+                .find(|&scale| scale < current)
+        };
+        let Some(next) = next else {
+            return;
+        };
+
+        println!("Render scale: {}x", next);
+        self.mandel_texture.set_render_scale(next);
+        self.update_fractal(self.frame_rect.center());
+    }
+
+    // the plane keeps its pixel size, a smaller region shows less of it with fewer pixels to compute
+    fn step_render_region(&mut self, up: bool) {
+        let current = self.render_region;
+        let next = if up {
+            RENDER_REGIONS
+                .iter()
+                .copied()
+                .find(|&region| region > current)
+        } else {
+            RENDER_REGIONS
+                .iter()
+                .rev()
+                .copied()
+                .find(|&region| region < current)
+        };
+        let Some(next) = next else {
+            return;
+        };
+
+        println!("Render region: {:.0}% of the window", next * 100.0);
+        self.render_region = next;
+        self.resize(self.surface_size);
+    }
+
+    fn cycle_fps_cap(&mut self) {
+        // a custom cap from the command line continues with no cap
+        let next = FPS_CAPS
+            .iter()
+            .position(|&cap| cap == self.fps_cap)
+            .and_then(|index| FPS_CAPS[(index + 1) % FPS_CAPS.len()]);
+
+        self.fps_cap = next;
+    }
+
+    /// Until when to keep the last presented frame instead of rendering, see `MandelTexture::frame_hold`.
+    pub fn frame_hold(&self) -> Option<Instant> {
+        self.mandel_texture.frame_hold()
+    }
+
+    /// Shortest time between two frames, `None` when the frame rate isn't capped.
+    pub fn frame_interval(&self) -> Option<Duration> {
+        self.fps_cap
+            .map(|fps| Duration::from_secs_f64(1.0 / fps as f64))
+    }
+
     fn update_user_event(&mut self, event: UserEvent) -> EventResult {
         match event {
             UserEvent::Redraw => EventResult::Redraw,
             UserEvent::TileReady {
                 tile_index: _tile_index,
             } => EventResult::Redraw,
+            UserEvent::Idle { generation } => {
+                if generation != self.idle_generation {
+                    return EventResult::Continue;
+                }
+
+                // more iterations, supersampling with a settle pattern, or neither when locked
+                let kernel_params = self.mandel_texture.kernel_params();
+                self.mandel_texture.set_quality_boost(true);
+                if self.mandel_texture.kernel_params() == kernel_params {
+                    return EventResult::Continue;
+                }
+
+                self.schedule_tiles(self.frame_rect.center());
+                EventResult::Redraw
+            }
         }
     }
 
     fn update_fractal(&mut self, focus: DVec2) {
+        self.mandel_texture.set_quality_boost(false);
+        self.schedule_tiles(focus);
+        self.update_orbit();
+        self.restart_idle_timer();
+
+        let view_state = self.view_state();
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(view_state);
+        }
+    }
+
+    // for changes to what the kernel computes, the tiles in flight started with the old settings
+    fn recompute_fractal(&mut self) {
+        self.mandel_texture.invalidate();
+        self.update_fractal(self.frame_rect.center());
+    }
+
+    fn add_measure_point(&mut self, point: DVec2) {
+        if self.measure_points.len() == 2 {
+            self.measure_points.clear();
+        }
+        self.measure_points.push(point);
+
+        let &[start, end] = self.measure_points.as_slice() else {
+            self.mandel_texture.set_measure(None);
+            return;
+        };
+        let (distance, angle) = measure(start, end);
+        println!(
+            "Measured from ({}, {}) to ({}, {}): distance {:e}, angle {:.2}°",
+            start.x, start.y, end.x, end.y, distance, angle
+        );
+        self.mandel_texture.set_measure(Some((start, end)));
+    }
+
+    // the orbit follows the fractal and the iteration count, a pan or zoom keeps it
+    fn update_orbit(&mut self) {
+        let Some(point) = self.orbit_point else {
+            return;
+        };
+        let inputs = (point, self.fractal, self.mandel_texture.max_iters());
+        if self.orbit_inputs == Some(inputs) {
+            return;
+        }
+
+        self.orbit_inputs = Some(inputs);
+        let orbit = self.fractal.orbit(point, inputs.2);
+        self.mandel_texture.set_orbit(point, orbit);
+    }
+
+    fn restart_idle_timer(&mut self) {
+        if let Some(idle_timer) = self.idle_timer.take() {
+            idle_timer.abort();
+        }
+
+        self.idle_generation += 1;
+        let generation = self.idle_generation;
+        let event_loop_proxy = self.event_loop_proxy.clone();
+        let idle_delay = self.idle_delay;
+
+        self.idle_timer = Some(self.runtime.spawn(async move {
+            tokio::time::sleep(idle_delay).await;
+            // the event loop may already be gone when exiting
+            let _ = event_loop_proxy
+                .lock()
+                .send_event(UserEvent::Idle { generation });
+        }));
+    }
+
+    fn schedule_tiles(&mut self, focus: DVec2) {
+        if self.minimized {
+            return;
+        }
+
         let event_loop_proxy = self.event_loop_proxy.clone();
 
         self.mandel_texture
@@ -200,3 +1636,231 @@ impl TiledFractalApp {
             });
     }
 }
+
+/// Moves `previous` towards `target` by how much of `FOCUS_SMOOTHING` passed since,
+/// after a pause of `FOCUS_RESET` it's `target` itself.
+fn smooth_focus(previous: DVec2, target: DVec2, elapsed: Duration) -> DVec2 {
+    if elapsed >= FOCUS_RESET {
+        return target;
+    }
+
+    let share = 1.0 - (-elapsed.as_secs_f64() / FOCUS_SMOOTHING.as_secs_f64()).exp();
+    previous.lerp(target, share)
+}
+
+/// The frame `progress` of the way from `start` to `target`, eased at both ends.
+/// The glide zooms about one point that stays put on screen.
+fn glide_rect(start: DRect, target: DRect, progress: f64) -> DRect {
+    let eased = progress * progress * (3.0 - 2.0 * progress);
+    start.zoom_towards(&target, eased)
+}
+
+fn timestamped_path(prefix: &str, extension: &str) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+    PathBuf::from(format!("{}_{}.{}", prefix, timestamp, extension))
+}
+
+/// The largest size of `aspect` inside `surface_size`.
+fn fit_size(surface_size: UVec2, aspect: f64) -> UVec2 {
+    let size = surface_size.as_dvec2();
+    let size = if size.x > size.y * aspect {
+        DVec2::new(size.y * aspect, size.y)
+    } else {
+        DVec2::new(size.x, size.x / aspect)
+    };
+
+    size.round().as_uvec2().clamp(UVec2::ONE, surface_size)
+}
+
+/// `size` scaled by `region` in both directions, at least a pixel.
+fn region_size(size: UVec2, region: f64) -> UVec2 {
+    (size.as_dvec2() * region)
+        .round()
+        .as_uvec2()
+        .clamp(UVec2::ONE, size)
+}
+
+/// Keeps the frame center and the fractal size of a logical pixel across a resize,
+/// so a scale factor change alone shows the same region at the new device resolution.
+fn resize_frame(frame_rect: DRect, old: (UVec2, f64), new: (UVec2, f64)) -> DRect {
+    let old_logical = DVec2::from(old.0) / old.1;
+    let new_logical = DVec2::from(new.0) / new.1;
+
+    DRect::from_center_size(
+        frame_rect.center(),
+        frame_rect.size * new_logical / old_logical,
+    )
+}
+
+/// Distance from `start` to `end` in the plane and the direction in degrees,
+/// counterclockwise from the positive real axis in (-180, 180].
+fn measure(start: DVec2, end: DVec2) -> (f64, f64) {
+    let delta = end - start;
+    (delta.length(), delta.y.atan2(delta.x).to_degrees())
+}
+
+/// Pans by `mouse_delta` and zooms by `zoom` about `mouse_pos`, both in frame units
+/// relative to the frame center. The point grabbed at `mouse_pos - mouse_delta`
+/// ends up under `mouse_pos`, so a drag and a scroll can be applied at once.
+fn pan_zoom(frame_rect: DRect, mouse_pos: DVec2, mouse_delta: DVec2, zoom: f64) -> DRect {
+    let old_size = frame_rect.size;
+    let new_size = old_size * zoom;
+
+    let old_offset = frame_rect.center();
+    let new_offset = old_offset - mouse_delta * old_size - mouse_pos * (new_size - old_size);
+
+    DRect::from_center_size(new_offset, new_size)
+}
+
+// name, backend and kind of the GPU, plus the driver when the backend reports one
+fn adapter_description(info: &wgpu::AdapterInfo) -> String {
+    let device_type = match info.device_type {
+        wgpu::DeviceType::DiscreteGpu => "discrete GPU",
+        wgpu::DeviceType::IntegratedGpu => "integrated GPU",
+        wgpu::DeviceType::VirtualGpu => "virtual GPU",
+        wgpu::DeviceType::Cpu => "software renderer",
+        wgpu::DeviceType::Other => "unknown device type",
+    };
+    let driver = format!("{} {}", info.driver, info.driver_info);
+    let driver = match driver.trim() {
+        "" => String::new(),
+        driver => format!(", driver {}", driver),
+    };
+
+    format!(
+        "{} ({}, {}{})",
+        info.name,
+        info.backend.to_str(),
+        device_type,
+        driver
+    )
+}
+
+fn supported_msaa_sample_counts(window_state: &WindowContext) -> Vec<u32> {
+    let format = window_state.surface_config.view_formats[0];
+    let features = if window_state
+        .device
+        .features()
+        .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+    {
+        window_state.adapter.get_texture_format_features(format)
+    } else {
+        format.guaranteed_format_features(window_state.device.features())
+    };
+
+    // 8x and 16x are rarely worth it for a single textured quad
+    features
+        .flags
+        .supported_sample_counts()
+        .into_iter()
+        .filter(|&count| count <= 4)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pan_zoom_keeps_grabbed_point_under_cursor() {
+        let frame_rect = DRect::from_center_size(DVec2::new(-0.74, 0.1), DVec2::new(3.2, 2.0));
+        let mouse_pos = DVec2::new(0.2, -0.3);
+        let mouse_delta = DVec2::new(0.05, 0.02);
+        let grabbed = frame_rect.center() + frame_rect.size * (mouse_pos - mouse_delta);
+
+        for zoom in [1.0, 0.5, 1.7] {
+            let result = pan_zoom(frame_rect, mouse_pos, mouse_delta, zoom);
+            let under_cursor = result.center() + result.size * mouse_pos;
+
+            assert!((under_cursor - grabbed).length() < 1e-12);
+            assert!((result.size - frame_rect.size * zoom).length() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn pan_zoom_without_delta_keeps_cursor_point() {
+        let frame_rect = DRect::from_center_size(DVec2::new(0.3, -0.2), DVec2::new(1.6, 1.0));
+        let mouse_pos = DVec2::new(-0.4, 0.25);
+        let anchor = frame_rect.center() + frame_rect.size * mouse_pos;
+
+        let result = pan_zoom(frame_rect, mouse_pos, DVec2::ZERO, 0.25);
+
+        assert!((result.center() + result.size * mouse_pos - anchor).length() < 1e-12);
+    }
+
+    #[test]
+    fn measure_angle_is_counterclockwise_from_the_real_axis() {
+        let start = DVec2::new(-0.5, 0.25);
+        let (distance, angle) = measure(start, start + DVec2::new(0.0, 2.0));
+        assert!((distance - 2.0).abs() < 1e-12);
+        assert!((angle - 90.0).abs() < 1e-12);
+
+        assert_eq!(measure(start, start + DVec2::new(-3.0, 0.0)).1, 180.0);
+        assert!((measure(start, start + DVec2::new(1.0, -1.0)).1 + 45.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn glide_zooms_about_a_fixed_point() {
+        let start = DRect::from_center_size(DVec2::ZERO, DVec2::new(3.0, 2.0));
+        let target = DRect::from_center_size(DVec2::new(1.0, 0.5), DVec2::new(0.75, 0.5));
+        assert_eq!(glide_rect(start, target, 0.0), start);
+        assert_eq!(glide_rect(start, target, 1.0), target);
+
+        // the point at the same frame position in both ends
+        let fixed = DVec2::new(4.0 / 3.0, 2.0 / 3.0);
+        let position = |rect: DRect| (fixed - rect.pos) / rect.size;
+        let middle = glide_rect(start, target, 0.3);
+        assert!(position(middle).distance(position(start)) < 1e-12);
+    }
+
+    #[test]
+    fn fit_size_letterboxes_either_way() {
+        assert_eq!(fit_size(UVec2::new(1000, 500), 1.6), UVec2::new(800, 500));
+        assert_eq!(fit_size(UVec2::new(800, 1000), 1.6), UVec2::new(800, 500));
+        assert_eq!(fit_size(UVec2::new(800, 500), 1.6), UVec2::new(800, 500));
+        assert_eq!(fit_size(UVec2::new(3, 1), 0.01), UVec2::new(1, 1));
+    }
+
+    #[test]
+    fn resize_frame_keeps_region_on_scale_factor_change() {
+        let frame_rect = DRect::from_center_size(DVec2::new(-0.74, 0.1), DVec2::new(3.2, 2.0));
+
+        // same logical window moved to a 200% display
+        let result = resize_frame(
+            frame_rect,
+            (UVec2::new(800, 500), 1.0),
+            (UVec2::new(1600, 1000), 2.0),
+        );
+        assert_eq!(result, frame_rect);
+
+        // a plain resize still keeps the scale, showing more of the plane
+        let result = resize_frame(
+            frame_rect,
+            (UVec2::new(800, 500), 2.0),
+            (UVec2::new(1600, 500), 2.0),
+        );
+        assert_eq!(result.center(), frame_rect.center());
+        assert_eq!(result.size, DVec2::new(6.4, 2.0));
+    }
+
+    #[test]
+    fn focus_trails_the_drag_and_catches_up_after_a_pause() {
+        let (start, target) = (DVec2::ZERO, DVec2::new(0.4, -0.2));
+
+        // a move per millisecond barely shifts it, the order of the queued tiles holds
+        let step = smooth_focus(start, target, Duration::from_millis(1));
+        assert!(step.distance(start) < 0.02 * target.length());
+
+        let mut focus = start;
+        for _ in 0..100 {
+            focus = smooth_focus(focus, target, Duration::from_millis(1));
+        }
+        let expected = start.lerp(target, 1.0 - (-1.0f64).exp());
+        assert!(focus.distance(expected) < 1e-9);
+
+        assert_eq!(smooth_focus(start, target, FOCUS_RESET), target);
+    }
+}
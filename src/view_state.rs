@@ -0,0 +1,488 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use glam::{DVec2, Vec4};
+use serde::{Deserialize, Serialize};
+
+use crate::fractal::{Fractal, MULTIBROT_POWERS};
+use crate::mandel_texture::{
+    ColoringMode, InteriorColoring, DEFAULT_INTERIOR_COLOR, DEFAULT_PALETTE_PERIOD,
+    MAX_PALETTE_PERIOD, MIN_PALETTE_PERIOD,
+};
+use crate::mandelbrot_simd::{ITER_STORAGE_LIMIT, MIN_ITER};
+use crate::palette::PaletteKind;
+
+const LINK_PREFIX: &str = "mandelbrot://view?";
+
+/// Startup view, the whole Mandelbrot set slightly off its own center.
+pub const DEFAULT_CENTER: DVec2 = DVec2::new(-0.74, 0.0);
+pub const DEFAULT_HEIGHT: f64 = 2.5;
+
+/// Everything needed to reproduce what is on screen, independent of the window size.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "StateFile", into = "StateFile")]
+pub struct ViewState {
+    pub center: DVec2,
+    /// Visible height in fractal units, the width follows the window aspect.
+    pub height: f64,
+    pub fractal: Fractal,
+    pub iter_override: Option<u32>,
+    /// Saved with `to_json` and `to_link`, `--load` and `--link` apply it through `Config`.
+    /// Locations and links from older builds don't carry one and leave the coloring as it is.
+    pub coloring: Option<Coloring>,
+}
+
+/// How the iterations are shaded, everything the screen shader needs besides the counts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coloring {
+    /// `PaletteKind::Gradient` takes its colors from `--gradient`, the file isn't saved.
+    pub palette: PaletteKind,
+    pub interior_coloring: InteriorColoring,
+    pub interior_color: Vec4,
+    pub coloring_mode: ColoringMode,
+    pub color_offset: f32,
+    /// Iterations of one pass through the palette.
+    pub palette_period: u32,
+}
+
+impl Default for ViewState {
+    fn default() -> Self {
+        Self {
+            center: DEFAULT_CENTER,
+            height: DEFAULT_HEIGHT,
+            fractal: Fractal::default(),
+            iter_override: None,
+            coloring: None,
+        }
+    }
+}
+
+impl Default for Coloring {
+    fn default() -> Self {
+        Self {
+            palette: PaletteKind::default(),
+            interior_coloring: InteriorColoring::default(),
+            interior_color: DEFAULT_INTERIOR_COLOR,
+            coloring_mode: ColoringMode::default(),
+            color_offset: 0.0,
+            palette_period: DEFAULT_PALETTE_PERIOD,
+        }
+    }
+}
+
+// The JSON layout of `ViewState`, enums by name. Missing fields take the defaults and unknown
+// ones are skipped, so files from older and newer builds both load. Coloring names this build
+// doesn't know take the default too, the fractal kind decides what the view is and must match.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct StateFile {
+    center: [f64; 2],
+    height: f64,
+    kind: String,
+    julia_c: [f64; 2],
+    multibrot_power: u64,
+    iterations: Option<u64>,
+    coloring: Option<ColoringFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct ColoringFile {
+    palette: String,
+    interior_coloring: String,
+    interior_color: [f32; 4],
+    coloring_mode: String,
+    // the coloring mode of files from before there were modes, only read
+    #[serde(skip_serializing)]
+    smooth_coloring: Option<bool>,
+    color_offset: f32,
+    palette_period: u32,
+}
+
+impl Default for StateFile {
+    fn default() -> Self {
+        ViewState::default().into()
+    }
+}
+
+impl Default for ColoringFile {
+    fn default() -> Self {
+        Coloring::default().into()
+    }
+}
+
+impl From<ViewState> for StateFile {
+    fn from(state: ViewState) -> Self {
+        Self {
+            center: state.center.to_array(),
+            height: state.height,
+            kind: state.fractal.kind.name().to_string(),
+            julia_c: state.fractal.julia_c.to_array(),
+            multibrot_power: state.fractal.multibrot_power as u64,
+            iterations: state.iter_override.map(u64::from),
+            coloring: state.coloring.map(ColoringFile::from),
+        }
+    }
+}
+
+impl From<Coloring> for ColoringFile {
+    fn from(coloring: Coloring) -> Self {
+        Self {
+            palette: coloring.palette.name().to_string(),
+            interior_coloring: coloring.interior_coloring.name().to_string(),
+            interior_color: coloring.interior_color.to_array(),
+            coloring_mode: coloring.coloring_mode.name().to_string(),
+            smooth_coloring: None,
+            color_offset: coloring.color_offset,
+            palette_period: coloring.palette_period,
+        }
+    }
+}
+
+impl TryFrom<StateFile> for ViewState {
+    type Error = anyhow::Error;
+
+    fn try_from(file: StateFile) -> anyhow::Result<Self> {
+        let coloring = match file.coloring {
+            Some(coloring) => Some(Coloring {
+                palette: parse_name(&coloring.palette),
+                interior_coloring: parse_name(&coloring.interior_coloring),
+                interior_color: Vec4::from_array(coloring.interior_color),
+                coloring_mode: match coloring.smooth_coloring {
+                    Some(true) => ColoringMode::Smooth,
+                    _ => parse_name(&coloring.coloring_mode),
+                },
+                color_offset: coloring.color_offset.max(0.0),
+                palette_period: coloring
+                    .palette_period
+                    .clamp(MIN_PALETTE_PERIOD, MAX_PALETTE_PERIOD),
+            }),
+            None => None,
+        };
+        let state = Self {
+            center: DVec2::from_array(file.center),
+            height: file.height,
+            fractal: Fractal {
+                kind: file.kind.parse()?,
+                julia_c: DVec2::from_array(file.julia_c),
+                multibrot_power: clamp_power(file.multibrot_power),
+            },
+            iter_override: file.iterations.map(clamp_iters),
+            coloring,
+        };
+        if !state.has_usable_position() {
+            return Err(anyhow!("View state has no usable position"));
+        }
+
+        Ok(state)
+    }
+}
+
+impl ViewState {
+    /// Magnification relative to the default startup view.
+    pub fn zoom(&self) -> f64 {
+        DEFAULT_HEIGHT / self.height
+    }
+
+    pub fn to_link(self) -> String {
+        let mut link = format!(
+            "{}x={}&y={}&h={}&kind={}&jx={}&jy={}&power={}",
+            LINK_PREFIX,
+            self.center.x,
+            self.center.y,
+            self.height,
+            self.fractal.kind.name(),
+            self.fractal.julia_c.x,
+            self.fractal.julia_c.y,
+            self.fractal.multibrot_power,
+        );
+        if let Some(iters) = self.iter_override {
+            link += &format!("&iters={}", iters);
+        }
+        if let Some(coloring) = self.coloring {
+            let [r, g, b, a] = coloring.interior_color.to_array();
+            link += &format!(
+                "&palette={}&interior={}&icolor={},{},{},{}&coloring={}&offset={}&period={}",
+                coloring.palette.name(),
+                coloring.interior_coloring.name(),
+                r,
+                g,
+                b,
+                a,
+                coloring.coloring_mode.name(),
+                coloring.color_offset,
+                coloring.palette_period,
+            );
+        }
+
+        link
+    }
+
+    /// Parses a link produced by `to_link`, fields missing from older links keep their defaults.
+    /// Counts out of this build's range are clamped, links from other builds may have other limits.
+    /// Any coloring field gives the state a coloring, the ones not in the link are the defaults,
+    /// as are palette and mode names this build doesn't know.
+    pub fn from_link(link: &str) -> anyhow::Result<Self> {
+        let query = link
+            .trim()
+            .strip_prefix(LINK_PREFIX)
+            .ok_or_else(|| anyhow!("Not a view link: {}", link))?;
+
+        let mut state = Self::default();
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Malformed link parameter: {}", pair))?;
+
+            match key {
+                "x" => state.center.x = parse_param(key, value)?,
+                "y" => state.center.y = parse_param(key, value)?,
+                "h" => state.height = parse_param(key, value)?,
+                "kind" => state.fractal.kind = value.parse()?,
+                "jx" => state.fractal.julia_c.x = parse_param(key, value)?,
+                "jy" => state.fractal.julia_c.y = parse_param(key, value)?,
+                "power" => state.fractal.multibrot_power = clamp_power(parse_param(key, value)?),
+                "iters" => state.iter_override = Some(clamp_iters(parse_param(key, value)?)),
+                "palette" => state.coloring.get_or_insert_default().palette = parse_name(value),
+                "interior" => {
+                    state.coloring.get_or_insert_default().interior_coloring = parse_name(value)
+                }
+                "icolor" => {
+                    state.coloring.get_or_insert_default().interior_color =
+                        parse_color_param(key, value)?
+                }
+                "coloring" => {
+                    state.coloring.get_or_insert_default().coloring_mode = parse_name(value)
+                }
+                "offset" => {
+                    state.coloring.get_or_insert_default().color_offset =
+                        parse_param::<f32>(key, value)?.max(0.0)
+                }
+                "period" => {
+                    state.coloring.get_or_insert_default().palette_period =
+                        parse_param::<u32>(key, value)?
+                            .clamp(MIN_PALETTE_PERIOD, MAX_PALETTE_PERIOD)
+                }
+                // links from newer builds may carry fields this one doesn't know
+                _ => {}
+            }
+        }
+
+        if !state.has_usable_position() {
+            return Err(anyhow!("View link has no usable position: {}", link));
+        }
+
+        Ok(state)
+    }
+
+    /// Pretty-printed JSON with the coloring, when the state has one.
+    pub fn to_json(self) -> String {
+        // only strings, numbers and arrays, nothing that can fail to serialize
+        serde_json::to_string_pretty(&self).unwrap()
+    }
+
+    /// Parses JSON from `to_json`, counts out of range are clamped like `from_link` does.
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|err| anyhow!("Failed to read {}: {}", path.display(), err))?;
+        Self::from_json(&json)
+            .map_err(|err| anyhow!("Invalid view state {}: {}", path.display(), err))
+    }
+
+    pub fn save(self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, self.to_json())?;
+
+        Ok(())
+    }
+
+    fn has_usable_position(&self) -> bool {
+        let finite = self.center.is_finite() && self.fractal.julia_c.is_finite();
+        finite && self.height.is_finite() && self.height > 0.0
+    }
+}
+
+pub(crate) fn clamp_power(power: u64) -> u32 {
+    power.clamp(
+        *MULTIBROT_POWERS.start() as u64,
+        *MULTIBROT_POWERS.end() as u64,
+    ) as u32
+}
+
+fn clamp_iters(iters: u64) -> u32 {
+    iters.clamp(MIN_ITER as u64, ITER_STORAGE_LIMIT as u64) as u32
+}
+
+impl FromStr for ViewState {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_link(s)
+    }
+}
+
+fn parse_param<T: FromStr>(key: &str, value: &str) -> anyhow::Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("Invalid link parameter {}: {}", key, value))
+}
+
+// a coloring name from a newer build, like a palette this one lacks, is the default
+fn parse_name<T: FromStr + Default>(name: &str) -> T {
+    name.parse().unwrap_or_default()
+}
+
+// four comma separated channels, red to alpha, each from 0 to 1
+fn parse_color_param(key: &str, value: &str) -> anyhow::Result<Vec4> {
+    let channels = value
+        .split(',')
+        .map(|channel| parse_param::<f32>(key, channel))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    match channels.as_slice() {
+        &[r, g, b, a] if channels.iter().all(|channel| (0.0..=1.0).contains(channel)) => {
+            Ok(Vec4::new(r, g, b, a))
+        }
+        _ => Err(anyhow!("Invalid link parameter {}: {}", key, value)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fractal::FractalKind;
+
+    #[test]
+    fn links_from_other_builds_load_within_range() {
+        let view = ViewState {
+            center: DVec2::new(-0.75, 0.1),
+            height: 0.01,
+            fractal: Fractal {
+                kind: FractalKind::Multibrot,
+                julia_c: DVec2::new(0.3, -0.5),
+                multibrot_power: 4,
+            },
+            iter_override: Some(2000),
+            coloring: Some(Coloring {
+                palette: PaletteKind::Inferno,
+                interior_coloring: InteriorColoring::Phase,
+                interior_color: Vec4::new(0.1, 0.2, 0.3, 1.0),
+                coloring_mode: ColoringMode::Smooth,
+                color_offset: 12.5,
+                palette_period: 96,
+            }),
+        };
+        assert_eq!(ViewState::from_link(&view.to_link()).unwrap(), view);
+
+        // older builds wrote fewer fields
+        let old = ViewState::from_link("mandelbrot://view?x=-0.75&y=0.1&h=0.01").unwrap();
+        assert_eq!(old.fractal, Fractal::default());
+        assert_eq!(old.iter_override, None);
+        assert_eq!(old.coloring, None);
+
+        let old = ViewState::from_link("mandelbrot://view?x=0&y=0&h=1&palette=viridis").unwrap();
+        assert_eq!(
+            old.coloring,
+            Some(Coloring {
+                palette: PaletteKind::Viridis,
+                ..Coloring::default()
+            })
+        );
+
+        // newer builds may allow more, or add fields and names
+        let new = ViewState::from_link(
+            "mandelbrot://view?x=0&y=0&h=1&kind=multibrot&power=100&iters=10000000000&period=0&lens=7&palette=plasma&coloring=stripe",
+        )
+        .unwrap();
+        assert_eq!(new.fractal.multibrot_power, *MULTIBROT_POWERS.end());
+        assert_eq!(new.iter_override, Some(ITER_STORAGE_LIMIT));
+        assert_eq!(
+            new.coloring,
+            Some(Coloring {
+                palette_period: MIN_PALETTE_PERIOD,
+                ..Coloring::default()
+            })
+        );
+
+        let low = ViewState::from_link("mandelbrot://view?x=0&y=0&h=1&power=0&iters=0").unwrap();
+        assert_eq!(low.fractal.multibrot_power, *MULTIBROT_POWERS.start());
+        assert_eq!(low.iter_override, Some(MIN_ITER));
+
+        for link in [
+            "mandelbrot://view?x=0&y=0&h=0",
+            "mandelbrot://view?x=0&y=0&h=-1",
+            "mandelbrot://view?x=NaN&y=0&h=1",
+            "mandelbrot://view?x=0&y=0&h=inf",
+            "mandelbrot://view?x=0&y=0&h=1&iters=-5",
+            "mandelbrot://view?x=0&y=0&h=1&icolor=1,0,0",
+            "mandelbrot://view?x=0&y=0&h=1&icolor=2,0,0,1",
+        ] {
+            assert!(ViewState::from_link(link).is_err(), "{}", link);
+        }
+    }
+
+    #[test]
+    fn json_keeps_the_coloring_and_tolerates_other_builds() {
+        let view = ViewState {
+            center: DVec2::new(-0.75, 0.1),
+            height: 0.01,
+            fractal: Fractal {
+                kind: FractalKind::Julia,
+                julia_c: DVec2::new(0.3, -0.5),
+                multibrot_power: 5,
+            },
+            iter_override: Some(2000),
+            coloring: Some(Coloring {
+                palette: PaletteKind::Inferno,
+                interior_coloring: InteriorColoring::Phase,
+                interior_color: Vec4::new(0.1, 0.2, 0.3, 1.0),
+                coloring_mode: ColoringMode::Smooth,
+                color_offset: 12.5,
+                palette_period: 96,
+            }),
+        };
+        assert_eq!(ViewState::from_json(&view.to_json()).unwrap(), view);
+
+        // older builds wrote fewer fields, newer ones may add some
+        let state = ViewState::from_json(
+            r#"{"center": [0.25, 0.0], "height": 0.5, "iterations": 0, "bookmark": "x"}"#,
+        )
+        .unwrap();
+        assert_eq!(state.center, DVec2::new(0.25, 0.0));
+        assert_eq!(state.fractal, Fractal::default());
+        assert_eq!(state.iter_override, Some(MIN_ITER));
+        assert_eq!(state.coloring, None);
+
+        let state = ViewState::from_json(r#"{"coloring": {"smooth_coloring": true}}"#).unwrap();
+        assert_eq!(state.coloring.unwrap().coloring_mode, ColoringMode::Smooth);
+        assert!(!view.to_json().contains("smooth_coloring"));
+
+        let state = ViewState::from_json(r#"{"coloring": {"palette": "viridis"}}"#).unwrap();
+        assert_eq!(
+            state.coloring,
+            Some(Coloring {
+                palette: PaletteKind::Viridis,
+                ..Coloring::default()
+            })
+        );
+
+        let state = ViewState::from_json(
+            r#"{"coloring": {"palette": "unknown", "interior_coloring": "orbit_trap", "coloring_mode": "stripe", "palette_period": 96}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            state.coloring,
+            Some(Coloring {
+                palette_period: 96,
+                ..Coloring::default()
+            })
+        );
+
+        for json in [r#"{"height": 0}"#, r#"{"kind": "unknown"}"#, "not json"] {
+            assert!(ViewState::from_json(json).is_err(), "{}", json);
+        }
+    }
+}
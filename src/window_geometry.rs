@@ -0,0 +1,162 @@
+use anyhow::anyhow;
+use glam::{IVec2, UVec2};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{Window, WindowAttributes};
+
+// next to palette.png, screenshots go to the working directory too
+const PATH: &str = "window.txt";
+
+/// Where the window was when the app last closed, restored on the next start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowGeometry {
+    // not every platform reports it, Wayland doesn't
+    pub position: Option<IVec2>,
+    pub size: UVec2,
+}
+
+impl WindowGeometry {
+    /// The current geometry, `None` while minimized.
+    pub fn of(window: &Window) -> Option<Self> {
+        let size = window.inner_size();
+        if size.width == 0 || size.height == 0 {
+            return None;
+        }
+
+        Some(Self {
+            position: window
+                .outer_position()
+                .ok()
+                .map(|position| IVec2::new(position.x, position.y)),
+            size: UVec2::new(size.width, size.height),
+        })
+    }
+
+    /// The saved geometry, `None` when nothing was saved or the file doesn't parse.
+    pub fn load() -> Option<Self> {
+        let text = std::fs::read_to_string(PATH).ok()?;
+        Self::parse(&text).ok()
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let text = match self.position {
+            Some(position) => format!(
+                "{} {} {} {}\n",
+                position.x, position.y, self.size.x, self.size.y
+            ),
+            None => format!("{} {}\n", self.size.x, self.size.y),
+        };
+        std::fs::write(PATH, text)?;
+
+        Ok(())
+    }
+
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        let values = text
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<i32>, _>>()?;
+        let (position, size) = match values[..] {
+            [x, y, width, height] => (Some(IVec2::new(x, y)), IVec2::new(width, height)),
+            [width, height] => (None, IVec2::new(width, height)),
+            _ => return Err(anyhow!("Expected `x y width height` or `width height`")),
+        };
+        if size.min_element() <= 0 {
+            return Err(anyhow!("Window size must be positive"));
+        }
+
+        Ok(Self {
+            position,
+            size: size.as_uvec2(),
+        })
+    }
+
+    /// Applies the geometry, moved onto a connected monitor if it was saved on another one.
+    pub fn apply(
+        &self,
+        event_loop: &ActiveEventLoop,
+        attributes: WindowAttributes,
+    ) -> WindowAttributes {
+        let attributes = attributes.with_inner_size(PhysicalSize::new(self.size.x, self.size.y));
+        let monitors: Vec<(IVec2, UVec2)> = event_loop
+            .available_monitors()
+            .map(|monitor| {
+                let position = monitor.position();
+                let size = monitor.size();
+                (
+                    IVec2::new(position.x, position.y),
+                    UVec2::new(size.width, size.height),
+                )
+            })
+            .collect();
+
+        match self
+            .position
+            .and_then(|position| clamp_to_monitors(position, self.size, &monitors))
+        {
+            Some(position) => {
+                attributes.with_position(PhysicalPosition::new(position.x, position.y))
+            }
+            None => attributes,
+        }
+    }
+}
+
+// keeps the window on the monitor it overlaps most, or the nearest one when it overlaps none
+fn clamp_to_monitors(position: IVec2, size: UVec2, monitors: &[(IVec2, UVec2)]) -> Option<IVec2> {
+    let window_center = position + size.as_ivec2() / 2;
+    let overlap = |&(monitor_position, monitor_size): &(IVec2, UVec2)| {
+        let min = position.max(monitor_position);
+        let max = (position + size.as_ivec2()).min(monitor_position + monitor_size.as_ivec2());
+        let extent = (max - min).max(IVec2::ZERO);
+        extent.x as i64 * extent.y as i64
+    };
+    let distance = |&(monitor_position, monitor_size): &(IVec2, UVec2)| {
+        let monitor_center = monitor_position + monitor_size.as_ivec2() / 2;
+        (monitor_center - window_center)
+            .as_i64vec2()
+            .length_squared()
+    };
+
+    let (monitor_position, monitor_size) = monitors
+        .iter()
+        .max_by_key(|monitor| (overlap(monitor), -distance(monitor)))?;
+    // a window larger than the monitor keeps its top left corner on screen
+    let max = monitor_position + (monitor_size.as_ivec2() - size.as_ivec2()).max(IVec2::ZERO);
+
+    Some(position.clamp(*monitor_position, max))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn saved_positions_end_up_on_a_connected_monitor() {
+        let monitors = [
+            (IVec2::ZERO, UVec2::new(1920, 1080)),
+            (IVec2::new(1920, 0), UVec2::new(1280, 1024)),
+        ];
+        let size = UVec2::new(800, 600);
+
+        // already on screen
+        let position = IVec2::new(100, 100);
+        assert_eq!(clamp_to_monitors(position, size, &monitors), Some(position));
+        // hanging off the right edge of the second monitor
+        assert_eq!(
+            clamp_to_monitors(IVec2::new(3000, 900), size, &monitors),
+            Some(IVec2::new(2400, 424))
+        );
+        // saved on a display left of the primary one that is gone now
+        assert_eq!(
+            clamp_to_monitors(IVec2::new(-1500, 200), size, &monitors),
+            Some(IVec2::new(0, 200))
+        );
+        assert_eq!(clamp_to_monitors(position, size, &[]), None);
+
+        let geometry = WindowGeometry::parse("-1500 200 800 600\n").unwrap();
+        assert_eq!(geometry.position, Some(IVec2::new(-1500, 200)));
+        assert_eq!(geometry.size, size);
+        assert!(WindowGeometry::parse("800 0").is_err());
+    }
+}
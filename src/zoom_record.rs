@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+
+use glam::DVec2;
+
+use crate::config::Config;
+use crate::fractal::Fractal;
+use crate::headless::{frame_rect, Frames, FRAME_SIZE};
+use crate::mandelbrot_simd::is_beyond_precision;
+use crate::view_state::ViewState;
+
+/// Zooms into the center of the starting view by `ratio` per frame, saving every frame to `dir`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoomRecord {
+    pub dir: PathBuf,
+    pub ratio: f64,
+    pub frames: u32,
+}
+
+impl ZoomRecord {
+    pub const DEFAULT_RATIO: f64 = 1.02;
+    pub const DEFAULT_FRAMES: u32 = 1000;
+}
+
+/// Renders the frames without opening a window, stopping early once f64 precision runs out.
+pub fn run(config: &Config, record: &ZoomRecord) -> anyhow::Result<()> {
+    let mut view = config.view.unwrap_or(ViewState {
+        fractal: Fractal {
+            kind: config.fractal_kind,
+            ..Fractal::default()
+        },
+        ..ViewState::default()
+    });
+    let frames = Frames::new(config, &record.dir)?;
+
+    for index in 0..record.frames {
+        let frame = frame_rect(&view, FRAME_SIZE);
+        if is_beyond_precision(frame, frame.size / DVec2::from(FRAME_SIZE)) {
+            println!("Stopped at frame {}: f64 precision exhausted", index);
+            break;
+        }
+
+        let path = frames.save(index, &view)?;
+        println!("Saved {}", path.display());
+
+        view.height /= record.ratio;
+    }
+
+    Ok(())
+}